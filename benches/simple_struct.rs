@@ -1,7 +1,7 @@
 use std::hint::black_box;
 
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
-use rand::{random, Rng};
+use rand::random;
 use sov::StructOfVecs;
 
 #[derive(Clone, StructOfVecs)]
@@ -11,17 +11,13 @@ struct Foo {
 }
 
 fn random_vec(len: usize) -> (Vec<Foo>, VecFoo) {
-    let mut vec_foo = VecFoo::with_capacity(len);
-    let mut vec = Vec::with_capacity(len);
-
-    for _ in 0..len {
-        let foo = Foo {
+    let vec: Vec<Foo> = (0..len)
+        .map(|_| Foo {
             x: random(),
             y: random(),
-        };
-        vec_foo.push(foo.clone());
-        vec.push(foo);
-    }
+        })
+        .collect();
+    let vec_foo = VecFoo::from(vec.clone());
 
     (vec, vec_foo)
 }
@@ -40,7 +36,7 @@ pub fn bench_sum_u64s(c: &mut Criterion) {
 
     group.bench_with_input(BenchmarkId::new("u64s", "naive"), &vec, |b, input| {
         b.iter(|| {
-            let sum: u64 = black_box(input).iter().map(|foo| foo.x).sum();
+            let sum: u64 = black_box(input).iter().map(|value| value.x).sum();
             black_box(sum);
         })
     });
@@ -54,7 +50,7 @@ pub fn bench_sum_u64s(c: &mut Criterion) {
 
     group.bench_with_input(BenchmarkId::new("u8s", "naive"), &vec, |b, input| {
         b.iter(|| {
-            let sum: u8 = black_box(input).iter().map(|foo| foo.y).sum();
+            let sum: u8 = black_box(input).iter().map(|value| value.y).sum();
             black_box(sum);
         })
     });
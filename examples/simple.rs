@@ -17,4 +17,3 @@ struct Foo {
     x: i32,
     y: String,
 }
-
@@ -0,0 +1,554 @@
+//! The fixed-capacity `ArrayFoo<N>`, generated with `#[sov(array)]`.
+//!
+//! Every column is an `ArrayColumn<T, N>`, which stores its rows inline, so the array never
+//! allocates. The columns don't know how many of their rows are initialized, so the length is
+//! stored once in the array, and every method that reads a column passes it in.
+//!
+//! `#[sov(derive(...))]` applies to the array too. It compares and hashes column by column, and
+//! prints and orders row by row, in the same way as the `Vec`-like type
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, GenericParam, LifetimeParam, Member, Visibility};
+
+use crate::{
+    parse::{Derive, Field, Input},
+    util,
+};
+
+use super::{
+    impl_block::accessor_names,
+    structs::{advance, define_struct, IterKind, StructNames},
+};
+
+/// The array and its owning iterator, or nothing if `#[sov(array)]` isn't set
+pub(super) fn codegen(input: &Input, names: &StructNames) -> TokenStream {
+    if !input.config.array {
+        return TokenStream::new();
+    }
+
+    let len = input.extra_member("len");
+    let definitions = definitions(input, names, &len);
+    let methods = methods(input, names, &len);
+    let iters = iters(input, names, &len);
+    let derives = derives(input, names);
+
+    quote! {
+        #definitions
+        #methods
+        #iters
+        #derives
+    }
+}
+
+fn definitions(input: &Input, names: &StructNames, len: &Member) -> TokenStream {
+    let vis = util::hidden_module_vis(&input.vis);
+    let generics = names.array_generics();
+    let krate = &names.krate;
+    let capacity = &names.capacity;
+
+    let map_field = |field: &Field, column: TokenStream| {
+        let ty = &field.ty;
+        let field = syn::Field {
+            vis: Visibility::Inherited,
+            ty: parse_quote!(#krate::__private::#column<#ty, #capacity>),
+            ..field.field.clone()
+        };
+
+        quote!(#field)
+    };
+
+    // the length goes after the columns, named so that it doesn't clash with any of them
+    let len_field = match len {
+        Member::Named(name) => quote!(#name: ::core::primitive::usize),
+        Member::Unnamed(_) => quote!(::core::primitive::usize),
+    };
+    let array_fields = input
+        .fields
+        .iter()
+        .map(|field| map_field(field, quote!(ArrayColumn)))
+        .chain([len_field]);
+    let array_fields = match len {
+        Member::Named(_) => quote!({ #(#array_fields,)* }),
+        Member::Unnamed(_) => quote!(( #(#array_fields,)* )),
+    };
+
+    let iter_fields = input.map_fields_with_delimiters(
+        |field| map_field(field, quote!(ArrayColumnIntoIter)),
+        |field, _index| map_field(field, quote!(ArrayColumnIntoIter)),
+    );
+
+    let array = define_struct(input, &vis, &names.array, &generics, array_fields);
+    let into_iter = define_struct(input, &vis, &names.array_into_iter, &generics, iter_fields);
+
+    quote! {
+        #array
+        #into_iter
+    }
+}
+
+fn methods(input: &Input, names: &StructNames, len: &Member) -> TokenStream {
+    let array = &names.array;
+    let original = &names.original;
+    let original_type = names.original_type();
+    let krate = &names.krate;
+    let capacity = &names.capacity;
+    let lifetime = &names.lifetime;
+    let generics = names.array_generics();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let members = input.fields.members();
+    // the values are bound to `field0`, `field1`, etc. so they can't shadow `index`
+    let bindings: Vec<_> = (0..members.len()).map(util::nth_field).collect();
+
+    let shared_ref = &names.shared_ref;
+    let mut_ref = &names.mut_ref;
+    let ref_type = names.ref_type(false);
+    let ref_mut_type = names.ref_type(true);
+    let slices = &names.slices;
+    let slices_mut = &names.slices_mut;
+    let slices_type = names.slices_type(false);
+    let slices_mut_type = names.slices_type(true);
+    let slice = &names.slice;
+    let slice_mut = &names.slice_mut;
+    let slice_type = names.slice_type(false);
+    let slice_mut_type = names.slice_type(true);
+    let iter = &names.iter;
+    let iter_mut = &names.iter_mut;
+    let iter_type = names.iter_type(IterKind::Shared);
+    let iter_mut_type = names.iter_type(IterKind::Mutable);
+
+    let accessors = input
+        .fields
+        .iter()
+        .zip(&members)
+        .zip(accessor_names(input))
+        .map(|((field, member), (vis, fn_name, fn_name_mut))| {
+            let ty = &field.ty;
+
+            quote! {
+                #vis fn #fn_name(&self) -> &[#ty] {
+                    self.as_slices().#member
+                }
+
+                #vis fn #fn_name_mut(&mut self) -> &mut [#ty] {
+                    self.as_mut_slices().#member
+                }
+            }
+        });
+
+    quote! {
+        impl #impl_generics #array #ty_generics #where_clause {
+            /// An empty array
+            #[inline]
+            pub const fn new() -> Self {
+                Self {
+                    #(#members: #krate::__private::ArrayColumn::new(),)*
+                    #len: 0,
+                }
+            }
+
+            #[inline]
+            pub const fn len(&self) -> ::core::primitive::usize {
+                self.#len
+            }
+
+            #[inline]
+            pub const fn is_empty(&self) -> ::core::primitive::bool {
+                self.#len == 0
+            }
+
+            /// The number of rows the array can hold, which is its const parameter
+            #[inline]
+            pub const fn capacity(&self) -> ::core::primitive::usize {
+                #capacity
+            }
+
+            /// Whether the array holds as many rows as it can, so that `push` would fail
+            #[inline]
+            pub const fn is_full(&self) -> ::core::primitive::bool {
+                self.#len == #capacity
+            }
+
+            /// Add a row to the end of the array, or give it back if the array is full
+            #[inline]
+            pub fn push(&mut self, value: #original_type) -> ::core::result::Result<(), #original_type> {
+                let len = self.#len;
+                self.insert(len, value)
+            }
+
+            /// Insert a row at `index`, moving every row after it along by one, or give it back if
+            /// the array is full.
+            ///
+            /// Panics if `index` is greater than the length
+            pub fn insert(
+                &mut self,
+                index: ::core::primitive::usize,
+                value: #original_type,
+            ) -> ::core::result::Result<(), #original_type> {
+                let len = self.#len;
+                ::core::assert!(index <= len, "insertion index (is {index}) should be <= len (is {len})");
+
+                if len == #capacity {
+                    return ::core::result::Result::Err(value);
+                }
+
+                let #original { #(#members: #bindings,)* } = value;
+                // SAFETY: the first `len` rows of every column are initialized, `index` is at most
+                // `len`, and the array isn't full
+                unsafe {
+                    #(self.#members.insert(len, index, #bindings);)*
+                }
+                self.#len = len + 1;
+
+                ::core::result::Result::Ok(())
+            }
+
+            /// Remove the last row and return it, or `None` if the array is empty
+            #[inline]
+            pub fn pop(&mut self) -> ::core::option::Option<#original_type> {
+                let index = self.#len.checked_sub(1)?;
+                // the length goes down first, so a panic can't lead to the row being dropped twice
+                self.#len = index;
+
+                // SAFETY: the last row is initialized, and is no longer counted in the length
+                unsafe {
+                    ::core::option::Option::Some(#original {
+                        #(#members: self.#members.remove(index + 1, index),)*
+                    })
+                }
+            }
+
+            /// Remove the row at `index` and return it, moving every row after it back by one.
+            ///
+            /// Panics if `index` is out of bounds
+            pub fn remove(&mut self, index: ::core::primitive::usize) -> #original_type {
+                let len = self.#len;
+                ::core::assert!(index < len, "removal index (is {index}) should be < len (is {len})");
+                self.#len = len - 1;
+
+                // SAFETY: the first `len` rows of every column are initialized, and `index` is in
+                // bounds
+                unsafe {
+                    #original { #(#members: self.#members.remove(len, index),)* }
+                }
+            }
+
+            /// Remove the row at `index` and return it, replacing it with the last row.
+            ///
+            /// Panics if `index` is out of bounds
+            pub fn swap_remove(&mut self, index: ::core::primitive::usize) -> #original_type {
+                let len = self.#len;
+                ::core::assert!(index < len, "swap_remove index (is {index}) should be < len (is {len})");
+                self.#len = len - 1;
+
+                // SAFETY: the first `len` rows of every column are initialized, and `index` is in
+                // bounds
+                unsafe {
+                    #original { #(#members: self.#members.swap_remove(len, index),)* }
+                }
+            }
+
+            /// Remove every row after the first `len`, doing nothing if there are fewer rows
+            pub fn truncate(&mut self, len: ::core::primitive::usize) {
+                let old_len = self.#len;
+
+                if len >= old_len {
+                    return;
+                }
+
+                self.#len = len;
+
+                // SAFETY: the rows in `len..old_len` are initialized, and are no longer counted in
+                // the length
+                unsafe {
+                    #(self.#members.drop_rows(len, old_len);)*
+                }
+            }
+
+            /// Remove every row
+            #[inline]
+            pub fn clear(&mut self) {
+                self.truncate(0);
+            }
+
+            #[inline]
+            pub fn get<#lifetime>(&#lifetime self, index: ::core::primitive::usize) -> #ref_type {
+                let slices = self.as_slices();
+                #shared_ref { #(#members: &slices.#members[index],)* }
+            }
+
+            #[inline]
+            pub fn get_mut<#lifetime>(&#lifetime mut self, index: ::core::primitive::usize) -> #ref_mut_type {
+                let slices = self.as_mut_slices();
+                #mut_ref { #(#members: &mut slices.#members[index],)* }
+            }
+
+            /// Borrow every column at once
+            #[inline]
+            pub fn as_slices<#lifetime>(&#lifetime self) -> #slices_type {
+                let len = self.#len;
+
+                // SAFETY: the first `len` rows of every column are initialized
+                unsafe {
+                    #slices { #(#members: self.#members.as_slice(len),)* }
+                }
+            }
+
+            /// Mutably borrow every column at once, so that different columns can be modified at
+            /// the same time
+            #[inline]
+            pub fn as_mut_slices<#lifetime>(&#lifetime mut self) -> #slices_mut_type {
+                let len = self.#len;
+
+                // SAFETY: the first `len` rows of every column are initialized
+                unsafe {
+                    #slices_mut { #(#members: self.#members.as_mut_slice(len),)* }
+                }
+            }
+
+            /// Borrow every row as a view, which can be sliced, split and chunked like a `&[T]`
+            #[inline]
+            pub fn as_slice<#lifetime>(&#lifetime self) -> #slice_type {
+                let slices = self.as_slices();
+                #slice { #(#members: slices.#members,)* }
+            }
+
+            /// Mutably borrow every row as a view, which can be sliced, split and chunked like a
+            /// `&mut [T]`
+            #[inline]
+            pub fn as_mut_slice<#lifetime>(&#lifetime mut self) -> #slice_mut_type {
+                let slices = self.as_mut_slices();
+                #slice_mut { #(#members: slices.#members,)* }
+            }
+
+            #[inline]
+            pub fn iter<#lifetime>(&#lifetime self) -> #iter_type {
+                let slices = self.as_slices();
+                #iter { #(#members: ::core::iter::IntoIterator::into_iter(slices.#members),)* }
+            }
+
+            #[inline]
+            pub fn iter_mut<#lifetime>(&#lifetime mut self) -> #iter_mut_type {
+                let slices = self.as_mut_slices();
+                #iter_mut { #(#members: ::core::iter::IntoIterator::into_iter(slices.#members),)* }
+            }
+
+            #(#accessors)*
+        }
+
+        impl #impl_generics ::core::ops::Drop for #array #ty_generics #where_clause {
+            fn drop(&mut self) {
+                self.clear();
+            }
+        }
+    }
+}
+
+fn iters(input: &Input, names: &StructNames, len: &Member) -> TokenStream {
+    let array = &names.array;
+    let into_iter = &names.array_into_iter;
+    let original_type = names.original_type();
+    let lifetime = &names.lifetime;
+    let generics = names.array_generics();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let mut ref_generics = generics.clone();
+    let lifetime_param = GenericParam::Lifetime(LifetimeParam::new(lifetime.clone()));
+    ref_generics.params.insert(0, lifetime_param);
+    let (ref_impl_generics, _, _) = ref_generics.split_for_impl();
+
+    let members = input.fields.members();
+    let iter_type = names.iter_type(IterKind::Shared);
+    let iter_mut_type = names.iter_type(IterKind::Mutable);
+    let item_type = names.iter_item_type(IterKind::Shared);
+    let item_mut_type = names.iter_item_type(IterKind::Mutable);
+
+    // the columns of the owning iterator are advanced just like the columns of `FooIntoIter`
+    let next = advance(input, names, IterKind::Owned, quote!(next()));
+    let nth = advance(input, names, IterKind::Owned, quote!(nth(n)));
+    let next_back = advance(input, names, IterKind::Owned, quote!(next_back()));
+    let nth_back = advance(input, names, IterKind::Owned, quote!(nth_back(n)));
+    let first = &members[0];
+
+    quote! {
+        impl #ref_impl_generics ::core::iter::IntoIterator for &#lifetime #array #ty_generics #where_clause {
+            type Item = #item_type;
+            type IntoIter = #iter_type;
+
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+
+        impl #ref_impl_generics ::core::iter::IntoIterator for &#lifetime mut #array #ty_generics #where_clause {
+            type Item = #item_mut_type;
+            type IntoIter = #iter_mut_type;
+
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter_mut()
+            }
+        }
+
+        impl #impl_generics ::core::iter::IntoIterator for #array #ty_generics #where_clause {
+            type Item = #original_type;
+            type IntoIter = #into_iter #ty_generics;
+
+            #[inline]
+            fn into_iter(mut self) -> Self::IntoIter {
+                // the length is zeroed first, so the rows aren't dropped along with the array
+                let len = ::core::mem::replace(&mut self.#len, 0);
+
+                // SAFETY: the first `len` rows of every column are initialized, and are moved into
+                // the iterator
+                unsafe {
+                    #into_iter { #(#members: self.#members.take(len),)* }
+                }
+            }
+        }
+
+        impl #impl_generics ::core::iter::Iterator for #into_iter #ty_generics #where_clause {
+            type Item = #original_type;
+
+            #[inline]
+            fn next(&mut self) -> ::core::option::Option<Self::Item> {
+                #next
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (::core::primitive::usize, ::core::option::Option<::core::primitive::usize>) {
+                self.#first.size_hint()
+            }
+
+            #[inline]
+            fn nth(&mut self, n: ::core::primitive::usize) -> ::core::option::Option<Self::Item> {
+                #nth
+            }
+        }
+
+        impl #impl_generics ::core::iter::DoubleEndedIterator for #into_iter #ty_generics #where_clause {
+            #[inline]
+            fn next_back(&mut self) -> ::core::option::Option<Self::Item> {
+                #next_back
+            }
+
+            #[inline]
+            fn nth_back(&mut self, n: ::core::primitive::usize) -> ::core::option::Option<Self::Item> {
+                #nth_back
+            }
+        }
+
+        impl #impl_generics ::core::iter::ExactSizeIterator for #into_iter #ty_generics #where_clause {}
+
+        impl #impl_generics ::core::iter::FusedIterator for #into_iter #ty_generics #where_clause {}
+    }
+}
+
+/// The traits from `#[sov(derive(...))]`. The rows of every column are the field's type, since
+/// the array can't hold flattened fields, so each field's type needs to implement the trait
+fn derives(input: &Input, names: &StructNames) -> TokenStream {
+    let array = &names.array;
+    let original = &names.original;
+    let members = input.fields.members();
+
+    input
+        .config
+        .derives
+        .iter()
+        .map(|derive| {
+            let (trait_path, body) = match derive {
+                // the clone has the same capacity, so every row fits
+                Derive::Clone => (
+                    quote!(::core::clone::Clone),
+                    quote! {
+                        #[inline]
+                        fn clone(&self) -> Self {
+                            let mut array = Self::new();
+
+                            for row in self.iter() {
+                                let row = #original {
+                                    #(#members: ::core::clone::Clone::clone(row.#members),)*
+                                };
+                                let _ = array.push(row);
+                            }
+
+                            array
+                        }
+                    },
+                ),
+                Derive::Debug => (
+                    quote!(::core::fmt::Debug),
+                    quote! {
+                        fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                            f.debug_list().entries(self.iter()).finish()
+                        }
+                    },
+                ),
+                Derive::Default => (
+                    quote!(::core::default::Default),
+                    quote! {
+                        #[inline]
+                        fn default() -> Self {
+                            Self::new()
+                        }
+                    },
+                ),
+                Derive::PartialEq => (
+                    quote!(::core::cmp::PartialEq),
+                    quote! {
+                        #[inline]
+                        fn eq(&self, other: &Self) -> bool {
+                            let (lhs, rhs) = (self.as_slices(), other.as_slices());
+                            #(lhs.#members == rhs.#members)&&*
+                        }
+                    },
+                ),
+                Derive::Eq => (quote!(::core::cmp::Eq), quote!()),
+                Derive::PartialOrd => (
+                    quote!(::core::cmp::PartialOrd),
+                    quote! {
+                        #[inline]
+                        fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+                            ::core::iter::Iterator::partial_cmp(self.iter(), other.iter())
+                        }
+                    },
+                ),
+                Derive::Ord => (
+                    quote!(::core::cmp::Ord),
+                    quote! {
+                        #[inline]
+                        fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                            ::core::iter::Iterator::cmp(self.iter(), other.iter())
+                        }
+                    },
+                ),
+                // each column hashes as a slice, which is the same as the `Vec` it would be in
+                // the `Vec`-like type
+                Derive::Hash => (
+                    quote!(::core::hash::Hash),
+                    quote! {
+                        #[inline]
+                        fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+                            let slices = self.as_slices();
+                            #(::core::hash::Hash::hash(slices.#members, state);)*
+                        }
+                    },
+                ),
+            };
+
+            let bounds = input.fields.iter().map(|field| -> syn::WherePredicate {
+                let ty = &field.ty;
+                parse_quote!(#ty: #trait_path)
+            });
+            let generics = util::with_bounds(&names.array_generics(), bounds);
+            let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+            quote! {
+                impl #impl_generics #trait_path for #array #ty_generics #where_clause {
+                    #body
+                }
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,172 @@
+//! Conversions between the `Vec`-like type and other collections of rows.
+//!
+//! These only need `push` and `reserve`, so they are shared by structs and enums. The only
+//! difference is how a `FooRef` is cloned into a `Foo`, which the caller provides
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, WherePredicate};
+
+use crate::{parse::Input, util};
+
+use super::structs::StructNames;
+
+/// How to clone a `FooRef` into a `Foo`
+pub(super) struct RefToOwned {
+    /// An expression which clones the row in the variable `row` into the original type
+    pub expr: TokenStream,
+    /// The bounds needed for `expr` to compile
+    pub bounds: Vec<WherePredicate>,
+}
+
+impl RefToOwned {
+    /// Clone each field of a struct, or convert the nested reference type for flattened fields.
+    /// Nullable fields are an `Option<&T>`, which is cloned into an `Option<T>`, and dictionary
+    /// fields are a `&T` like fields that aren't flattened
+    pub(super) fn for_struct(input: &Input, names: &StructNames) -> Self {
+        let original = &names.original;
+        let members = input.fields.members();
+
+        if input.fields.is_empty() {
+            return Self {
+                expr: quote!({
+                    let _ = row;
+                    #original {}
+                }),
+                bounds: Vec::new(),
+            };
+        }
+
+        let fields = input.fields.iter().zip(&members).map(|(field, member)| {
+            match (field.config.nullable, field.config.flatten) {
+                (true, _) => quote!(<::core::option::Option<&_>>::cloned(row.#member)),
+                (false, true) if !field.config.dictionary => {
+                    quote!(::core::convert::From::from(row.#member))
+                }
+                (false, _) => quote!(::core::clone::Clone::clone(row.#member)),
+            }
+        });
+
+        let bounds = input
+            .fields
+            .iter()
+            .map(|field| {
+                let ty = &field.ty;
+
+                match field.row_type() {
+                    Some(ty) => util::non_trivial_bound(ty, quote!(::core::clone::Clone)),
+                    None => {
+                        let ref_type = names.ref_field_type(field, false);
+                        parse_quote!(#ty: ::core::convert::From<#ref_type>)
+                    }
+                }
+            })
+            .collect();
+
+        Self {
+            expr: quote!(#original { #(#members: #fields,)* }),
+            bounds,
+        }
+    }
+}
+
+pub(super) fn codegen(names: &StructNames, ref_to_owned: RefToOwned) -> TokenStream {
+    let krate = &names.krate;
+    let original_type = names.original_type();
+    let shared_ref_type = names.ref_type(false);
+    let vec_type = names.vec_type();
+    let vec_generics = names.vec_generics();
+    let (impl_generics, _, where_clause) = vec_generics.split_for_impl();
+
+    // collecting creates a new vec, so it can only be done with the global allocator
+    let global_vec_type = names.global_vec_type();
+    let (global_impl_generics, _, _) = names.generics.split_for_impl();
+
+    let ref_generics = util::with_bounds(
+        &names.with_allocator(names.ref_generics()),
+        ref_to_owned.bounds,
+    );
+    let (ref_impl_generics, _, ref_where_clause) = ref_generics.split_for_impl();
+    let ref_to_owned = ref_to_owned.expr;
+
+    let mut array_generics = names.generics.clone();
+    array_generics
+        .params
+        .push(parse_quote!(const __SOV_N: ::core::primitive::usize));
+    let (array_impl_generics, _, _) = array_generics.split_for_impl();
+
+    let clone_bound = util::non_trivial_bound(&original_type, quote!(::core::clone::Clone));
+
+    quote! {
+        impl #impl_generics #vec_type #where_clause {
+            /// Clone every row in `values` and add them to the end
+            #[inline]
+            pub fn extend_from_slice(&mut self, values: &[#original_type])
+            where
+                #clone_bound
+            {
+                ::core::iter::Extend::extend(self, values.iter().cloned());
+            }
+        }
+
+        impl #impl_generics ::core::iter::Extend<#original_type> for #vec_type #where_clause {
+            fn extend<I>(&mut self, iter: I)
+            where
+                I: ::core::iter::IntoIterator<Item = #original_type>,
+            {
+                let iter = ::core::iter::IntoIterator::into_iter(iter);
+                self.reserve(::core::iter::Iterator::size_hint(&iter).0);
+
+                for value in iter {
+                    self.push(value);
+                }
+            }
+        }
+
+        impl #ref_impl_generics ::core::iter::Extend<#shared_ref_type> for #vec_type #ref_where_clause {
+            fn extend<I>(&mut self, iter: I)
+            where
+                I: ::core::iter::IntoIterator<Item = #shared_ref_type>,
+            {
+                let iter = ::core::iter::IntoIterator::into_iter(iter);
+                self.reserve(::core::iter::Iterator::size_hint(&iter).0);
+
+                for row in iter {
+                    self.push(#ref_to_owned);
+                }
+            }
+        }
+
+        impl #global_impl_generics ::core::iter::FromIterator<#original_type> for #global_vec_type #where_clause {
+            #[inline]
+            fn from_iter<I>(iter: I) -> Self
+            where
+                I: ::core::iter::IntoIterator<Item = #original_type>,
+            {
+                let mut vec = Self::new();
+                ::core::iter::Extend::extend(&mut vec, iter);
+                vec
+            }
+        }
+
+        impl #global_impl_generics ::core::convert::From<#krate::__private::Vec<#original_type>> for #global_vec_type #where_clause {
+            #[inline]
+            fn from(values: #krate::__private::Vec<#original_type>) -> Self {
+                ::core::iter::FromIterator::from_iter(values)
+            }
+        }
+
+        impl #array_impl_generics ::core::convert::From<[#original_type; __SOV_N]> for #global_vec_type #where_clause {
+            #[inline]
+            fn from(values: [#original_type; __SOV_N]) -> Self {
+                ::core::iter::FromIterator::from_iter(values)
+            }
+        }
+
+        impl #impl_generics ::core::convert::From<#vec_type> for #krate::__private::Vec<#original_type> #where_clause {
+            #[inline]
+            fn from(vec: #vec_type) -> Self {
+                ::core::iter::Iterator::collect(::core::iter::IntoIterator::into_iter(vec))
+            }
+        }
+    }
+}
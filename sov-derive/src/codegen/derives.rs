@@ -0,0 +1,617 @@
+//! Standard trait impls, opted into with `#[sov(derive(...))]`.
+//!
+//! The `Vec`-like type compares and hashes column by column, which is equivalent to doing it row by
+//! row, and prints as a list of rows. It's ordered row by row, like a `Vec` of the original type.
+//!
+//! The reference types behave like the row they point to: they can be compared with each other
+//! and with the original type, and hash the same way as a derived `Hash` for the original type.
+//! Rather than `Clone`, they get `to_owned` and `From` impls for the original type
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{ext::IdentExt, parse_quote, Member, WherePredicate};
+
+use crate::{
+    parse::{Derive, Field, Fields, Input},
+    util,
+};
+
+use super::structs::{IterKind, StructNames};
+
+/// The pairs of rows that `PartialEq` and `PartialOrd` are implemented for, other than the
+/// original type with itself
+pub(super) const ROW_PAIRS: [(IterKind, IterKind); 6] = [
+    (IterKind::Shared, IterKind::Shared),
+    (IterKind::Mutable, IterKind::Mutable),
+    (IterKind::Shared, IterKind::Owned),
+    (IterKind::Owned, IterKind::Shared),
+    (IterKind::Mutable, IterKind::Owned),
+    (IterKind::Owned, IterKind::Mutable),
+];
+
+/// The pairs of rows that `Eq` and `Ord` are implemented for, which have to be the same type
+pub(super) const SAME_ROW_PAIRS: [(IterKind, IterKind); 2] = [
+    (IterKind::Shared, IterKind::Shared),
+    (IterKind::Mutable, IterKind::Mutable),
+];
+
+pub(super) fn codegen(input: &Input, names: &StructNames) -> TokenStream {
+    input
+        .config
+        .derives
+        .iter()
+        .map(|derive| match derive {
+            Derive::Clone => clone(input, names),
+            Derive::Debug => debug(input, names),
+            Derive::Default => default(names),
+            Derive::PartialEq => partial_eq(input, names),
+            Derive::Eq => eq(input, names),
+            Derive::PartialOrd => partial_ord(input, names),
+            Derive::Ord => ord(input, names),
+            Derive::Hash => hash(input, names),
+        })
+        .collect()
+}
+
+fn clone(input: &Input, names: &StructNames) -> TokenStream {
+    let members = input.fields.members();
+
+    let trait_path = quote!(::core::clone::Clone);
+
+    // the columns of a single allocation can't be cloned on their own, so the clone gets a new
+    // allocation with space for every row, and each column is copied into it
+    let (body, bounds) = match (input.fields.is_empty(), input.single_allocation()) {
+        (true, _) => (quote!(Self { len: self.len }), Vec::new()),
+        (false, true) => (
+            quote! {
+                let mut vec = Self::with_capacity(self.len());
+                #(vec.#members.extend_from_slice(&self.#members);)*
+                vec
+            },
+            field_bounds(input, &trait_path),
+        ),
+        (false, false) => (
+            quote!(Self { #(#members: ::core::clone::Clone::clone(&self.#members),)* }),
+            input
+                .fields
+                .iter()
+                .map(|field| {
+                    let ty = names.column_type(field);
+                    parse_quote!(#ty: #trait_path)
+                })
+                .collect(),
+        ),
+    };
+
+    let vec = vec_impl(
+        names,
+        bounds,
+        &trait_path,
+        quote! {
+            #[inline]
+            fn clone(&self) -> Self {
+                #body
+            }
+        },
+    );
+    let to_owned = to_owned(input, names);
+
+    quote! {
+        #vec
+        #to_owned
+    }
+}
+
+/// `to_owned` for both reference types, and `From` impls to convert them to the original type
+fn to_owned(input: &Input, names: &StructNames) -> TokenStream {
+    let original = &names.original;
+    let original_type = names.original_type();
+    let members = input.fields.members();
+
+    // flattened fields are the nested type's reference types, which have their own `to_owned`.
+    // Bit-packed fields are a `bool` in `FooRef`, and a proxy with `get` in `FooRefMut`,
+    // nullable fields are an `Option<&T>` in `FooRef`, and dictionary fields are a `&T` in
+    // `FooRef` and a proxy that dereferences to `T` in `FooRefMut`
+    let body = |mutable: bool| {
+        let fields = input.fields.iter().zip(&members).map(|(field, member)| {
+            match (
+                field.config.bitpacked,
+                field.config.nullable,
+                field.config.flatten && !field.config.dictionary,
+            ) {
+                (true, _, _) if mutable => quote!(self.#member.get()),
+                (true, _, _) => quote!(self.#member),
+                (false, true, _) if !mutable => {
+                    quote!(<::core::option::Option<&_>>::cloned(self.#member))
+                }
+                (false, _, true) => quote!(self.#member.to_owned()),
+                (false, _, false) => quote!(::core::clone::Clone::clone(&*self.#member)),
+            }
+        });
+
+        quote!(#original { #(#members: #fields,)* })
+    };
+
+    let bounds = field_bounds(input, &quote!(::core::clone::Clone));
+    let generics = util::with_bounds(&names.ref_generics(), bounds);
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let impls = [false, true].map(|mutable| {
+        let ref_type = names.ref_type(mutable);
+        let body = body(mutable);
+
+        quote! {
+            impl #impl_generics #ref_type #where_clause {
+                /// Clone the fields of the row into a new value of the original type
+                #[inline]
+                pub fn to_owned(&self) -> #original_type {
+                    #body
+                }
+            }
+
+            impl #impl_generics ::core::convert::From<#ref_type> for #original_type #where_clause {
+                #[inline]
+                fn from(value: #ref_type) -> Self {
+                    value.to_owned()
+                }
+            }
+        }
+    });
+
+    quote!(#(#impls)*)
+}
+
+fn debug(input: &Input, names: &StructNames) -> TokenStream {
+    let name = names.original.unraw().to_string();
+    let members = input.fields.members();
+
+    // rows are printed as if they were the original type
+    let row = match &input.fields {
+        Fields::Named(fields) => {
+            let field_names = fields
+                .iter()
+                .map(|field| field.ident.as_ref().unwrap().unraw().to_string());
+
+            quote! {
+                f.debug_struct(#name)
+                    #(.field(#field_names, &self.#members))*
+                    .finish()
+            }
+        }
+        Fields::Unnamed(_) => quote! {
+            f.debug_tuple(#name)
+                #(.field(&self.#members))*
+                .finish()
+        },
+        Fields::Unit => quote!(f.write_str(#name)),
+    };
+
+    let fmt = |body: TokenStream| {
+        quote! {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #body
+            }
+        }
+    };
+
+    let trait_path = quote!(::core::fmt::Debug);
+    let bounds = field_bounds(input, &trait_path);
+    let vec = vec_impl(
+        names,
+        bounds,
+        &trait_path,
+        fmt(quote!(f.debug_list().entries(self.iter()).finish())),
+    );
+    let refs = ref_impls(input, names, &trait_path, fmt(row));
+
+    quote! {
+        #vec
+        #refs
+    }
+}
+
+fn default(names: &StructNames) -> TokenStream {
+    let vec_type = names.global_vec_type();
+    let (impl_generics, _, where_clause) = names.generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics ::core::default::Default for #vec_type #where_clause {
+            #[inline]
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    }
+}
+
+fn partial_eq(input: &Input, names: &StructNames) -> TokenStream {
+    let members = input.fields.members();
+
+    let vec_body = match input.fields.is_empty() {
+        true => quote!(self.len == other.len),
+        false => {
+            let comparisons = input.fields.iter().zip(&members).map(|(field, member)| {
+                let lhs = column(names, field, quote!(self.#member));
+                let rhs = column(names, field, quote!(other.#member));
+                quote!(#lhs == #rhs)
+            });
+            quote!(#(#comparisons)&&*)
+        }
+    };
+
+    let trait_path = quote!(::core::cmp::PartialEq);
+    let bounds = column_bounds(input, names, &trait_path);
+    let vec = vec_impl(
+        names,
+        bounds,
+        &trait_path,
+        quote! {
+            #[inline]
+            fn eq(&self, other: &Self) -> bool {
+                #vec_body
+            }
+        },
+    );
+
+    let rows = ROW_PAIRS.map(|(lhs, rhs)| {
+        row_impl(
+            input,
+            names,
+            (lhs, rhs),
+            &trait_path,
+            true,
+            |fields, rhs_type| {
+                let comparisons = fields
+                    .iter()
+                    .map(|(lhs, rhs)| quote!(::core::cmp::PartialEq::eq(#lhs, #rhs)));
+
+                quote! {
+                    #[inline]
+                    fn eq(&self, other: &#rhs_type) -> bool {
+                        true #(&& #comparisons)*
+                    }
+                }
+            },
+        )
+    });
+
+    quote! {
+        #vec
+        #(#rows)*
+    }
+}
+
+fn eq(input: &Input, names: &StructNames) -> TokenStream {
+    let trait_path = quote!(::core::cmp::Eq);
+    let bounds = column_bounds(input, names, &trait_path);
+    let vec = vec_impl(names, bounds, &trait_path, quote!());
+    let rows = SAME_ROW_PAIRS
+        .map(|pair| row_impl(input, names, pair, &trait_path, false, |_, _| quote!()));
+
+    quote! {
+        #vec
+        #(#rows)*
+    }
+}
+
+fn partial_ord(input: &Input, names: &StructNames) -> TokenStream {
+    let trait_path = quote!(::core::cmp::PartialOrd);
+    let bounds = field_bounds(input, &trait_path);
+    let vec = vec_impl(
+        names,
+        bounds,
+        &trait_path,
+        quote! {
+            #[inline]
+            fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+                ::core::iter::Iterator::partial_cmp(self.iter(), other.iter())
+            }
+        },
+    );
+
+    // fields are compared in order, like a derived `PartialOrd`
+    let rows = ROW_PAIRS.map(|(lhs, rhs)| {
+        row_impl(input, names, (lhs, rhs), &trait_path, true, |fields, rhs_type| {
+            let comparisons = fields.iter().map(|(lhs, rhs)| {
+                quote! {
+                    match ::core::cmp::PartialOrd::partial_cmp(#lhs, #rhs) {
+                        ::core::option::Option::Some(::core::cmp::Ordering::Equal) => {}
+                        ordering => return ordering,
+                    }
+                }
+            });
+
+            quote! {
+                #[inline]
+                fn partial_cmp(&self, other: &#rhs_type) -> ::core::option::Option<::core::cmp::Ordering> {
+                    #(#comparisons)*
+                    ::core::option::Option::Some(::core::cmp::Ordering::Equal)
+                }
+            }
+        })
+    });
+
+    quote! {
+        #vec
+        #(#rows)*
+    }
+}
+
+fn ord(input: &Input, names: &StructNames) -> TokenStream {
+    let trait_path = quote!(::core::cmp::Ord);
+    let bounds = field_bounds(input, &trait_path);
+    let vec = vec_impl(
+        names,
+        bounds,
+        &trait_path,
+        quote! {
+            #[inline]
+            fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                ::core::iter::Iterator::cmp(self.iter(), other.iter())
+            }
+        },
+    );
+
+    let rows = SAME_ROW_PAIRS.map(|pair| {
+        row_impl(
+            input,
+            names,
+            pair,
+            &trait_path,
+            false,
+            |fields, rhs_type| {
+                let comparisons = fields.iter().map(|(lhs, rhs)| {
+                    quote! {
+                        match ::core::cmp::Ord::cmp(#lhs, #rhs) {
+                            ::core::cmp::Ordering::Equal => {}
+                            ordering => return ordering,
+                        }
+                    }
+                });
+
+                quote! {
+                    #[inline]
+                    fn cmp(&self, other: &#rhs_type) -> ::core::cmp::Ordering {
+                        #(#comparisons)*
+                        ::core::cmp::Ordering::Equal
+                    }
+                }
+            },
+        )
+    });
+
+    quote! {
+        #vec
+        #(#rows)*
+    }
+}
+
+fn hash(input: &Input, names: &StructNames) -> TokenStream {
+    let members = input.fields.members();
+
+    let (vec_body, ref_body) = match input.fields.is_empty() {
+        true => (
+            quote!(::core::hash::Hash::hash(&self.len, state);),
+            quote!(let _ = state;),
+        ),
+        false => {
+            let columns = input
+                .fields
+                .iter()
+                .zip(&members)
+                .map(|(field, member)| column(names, field, quote!(self.#member)));
+            (
+                quote!(#(::core::hash::Hash::hash(&#columns, state);)*),
+                quote!(#(::core::hash::Hash::hash(&self.#members, state);)*),
+            )
+        }
+    };
+
+    let hash = |body: TokenStream| {
+        quote! {
+            #[inline]
+            fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+                #body
+            }
+        }
+    };
+
+    let trait_path = quote!(::core::hash::Hash);
+    let bounds = column_bounds(input, names, &trait_path);
+    let vec = vec_impl(names, bounds, &trait_path, hash(vec_body));
+    let refs = ref_impls(input, names, &trait_path, hash(ref_body));
+
+    quote! {
+        #vec
+        #refs
+    }
+}
+
+/// `#column_type: #trait_path` for every column that is compared or hashed directly. Custom columns
+/// are compared and hashed as slices of their rows instead (see [`column`]), so they need
+/// `#field_type: #trait_path`
+fn column_bounds(
+    input: &Input,
+    names: &StructNames,
+    trait_path: &TokenStream,
+) -> Vec<WherePredicate> {
+    input
+        .fields
+        .iter()
+        .map(|field| match field.config.column {
+            Some(_) => {
+                let ty = &field.ty;
+                parse_quote!(#ty: #trait_path)
+            }
+            None => {
+                let ty = names.column_type(field);
+                parse_quote!(#ty: #trait_path)
+            }
+        })
+        .collect()
+}
+
+/// A column of the vec, to compare or hash. Custom columns are borrowed as a slice, so that only
+/// their rows are compared, and not any other state they keep
+fn column(names: &StructNames, field: &Field, column: TokenStream) -> TokenStream {
+    match field.config.column {
+        Some(_) => names.column_call(field, column, "as_slice", quote!()),
+        None => column,
+    }
+}
+
+/// `#field_type: #trait_path` for every field, for traits that work with rows rather than columns.
+///
+/// The rows of flattened fields are the nested type's reference types rather than the field's
+/// type, so they get the nested type's bounds instead (see `util::nested_bounds`). Nullable fields are compared as
+/// an `Option<&T>`, so they need `T` to implement the trait, and dictionary fields are compared as
+/// the field's type
+fn field_bounds(input: &Input, trait_path: &TokenStream) -> Vec<WherePredicate> {
+    input
+        .fields
+        .iter()
+        .flat_map(|field| match field.row_type() {
+            Some(ty) => vec![parse_quote!(#ty: #trait_path)],
+            None => util::nested_bounds(&input.generics, field, trait_path),
+        })
+        .collect()
+}
+
+fn vec_impl(
+    names: &StructNames,
+    bounds: Vec<WherePredicate>,
+    trait_path: &TokenStream,
+    body: TokenStream,
+) -> TokenStream {
+    let vec_type = names.vec_type();
+    let generics = util::with_bounds(&names.vec_generics(), bounds);
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #trait_path for #vec_type #where_clause {
+            #body
+        }
+    }
+}
+
+/// Implement `trait_path` for both reference types, as long as each field implements it.
+///
+/// The bounds are on the types being referenced, since `&'a mut T: PartialEq` would force both
+/// sides of a comparison to be borrowed for `'a`
+fn ref_impls(
+    input: &Input,
+    names: &StructNames,
+    trait_path: &TokenStream,
+    body: TokenStream,
+) -> TokenStream {
+    let impls = [false, true].map(|mutable| {
+        let name = match mutable {
+            true => &names.mut_ref,
+            false => &names.shared_ref,
+        };
+
+        let bounds = input.fields.iter().map(|field| -> WherePredicate {
+            let ty = match field.config.flatten {
+                true => names.ref_field_type(field, mutable),
+                false => field.ty.to_token_stream(),
+            };
+
+            parse_quote!(#ty: #trait_path)
+        });
+
+        let generics = util::with_bounds(&names.ref_generics(), bounds);
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+        quote! {
+            impl #impl_generics #trait_path for #name #ty_generics #where_clause {
+                #body
+            }
+        }
+    });
+
+    quote!(#(#impls)*)
+}
+
+/// Implement a comparison trait between two kinds of row, comparing them field by field.
+///
+/// If `generic` is set, the trait takes the type being compared against as a parameter, like
+/// `PartialEq<Rhs>`. `body` is called with a reference to each field of `self` and `other`, and
+/// the type of `other`
+fn row_impl(
+    input: &Input,
+    names: &StructNames,
+    (lhs, rhs): (IterKind, IterKind),
+    trait_path: &TokenStream,
+    generic: bool,
+    body: impl Fn(&[(TokenStream, TokenStream)], &TokenStream) -> TokenStream,
+) -> TokenStream {
+    let lhs_type = names.iter_item_type(lhs);
+    let rhs_type = names.iter_item_type(rhs);
+
+    let mut bounds = Vec::<WherePredicate>::new();
+    let mut fields = Vec::new();
+
+    for (field, member) in input.fields.iter().zip(input.fields.members()) {
+        let (lhs_field_type, lhs_field) = row_field(names, field, &member, lhs, quote!(self));
+        let (rhs_field_type, rhs_field) = row_field(names, field, &member, rhs, quote!(other));
+
+        bounds.push(match generic {
+            true => parse_quote!(#lhs_field_type: #trait_path<#rhs_field_type>),
+            false => parse_quote!(#lhs_field_type: #trait_path),
+        });
+        fields.push((lhs_field, rhs_field));
+    }
+
+    let trait_path = match generic {
+        true => quote!(#trait_path<#rhs_type>),
+        false => trait_path.clone(),
+    };
+
+    let generics = util::with_bounds(&names.ref_generics(), bounds);
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let body = body(&fields, &rhs_type);
+
+    // rows without any fields are all equal, so `other` isn't used
+    let allow_unused = match input.fields.is_empty() {
+        true => quote!(#[allow(unused_variables)]),
+        false => quote!(),
+    };
+
+    quote! {
+        #allow_unused
+        impl #impl_generics #trait_path for #lhs_type #where_clause {
+            #body
+        }
+    }
+}
+
+/// The type of a field of a row, and an expression for a reference to it. Reference types hold
+/// references to their fields, except for flattened fields which are the nested reference types.
+///
+/// Nullable fields are an `Option<&T>` in `FooRef` but a proxy in `FooRefMut`, so every kind of
+/// row is borrowed as an `Option<&T>`, and the type is `T`. Dictionary fields are borrowed like
+/// fields that aren't flattened, since their proxy dereferences to `T`
+fn row_field(
+    names: &StructNames,
+    field: &Field,
+    member: &Member,
+    kind: IterKind,
+    row: TokenStream,
+) -> (TokenStream, TokenStream) {
+    if let Some(ty) = field.nullable_type() {
+        return match kind {
+            IterKind::Shared => (ty.to_token_stream(), quote!(&#row.#member)),
+            IterKind::Mutable | IterKind::Owned => {
+                (ty.to_token_stream(), quote!(&#row.#member.as_ref()))
+            }
+        };
+    }
+
+    match (kind, field.config.flatten && !field.config.dictionary) {
+        (IterKind::Owned, _) => (field.ty.to_token_stream(), quote!(&#row.#member)),
+        (_, false) => (field.ty.to_token_stream(), quote!(&*#row.#member)),
+        (_, true) => (
+            names.ref_field_type(field, kind == IterKind::Mutable),
+            quote!(&#row.#member),
+        ),
+    }
+}
@@ -0,0 +1,139 @@
+//! `drain`, which removes a range of rows and yields them by value.
+//!
+//! Moving the rows out of the middle of every column would depend on how each column is stored, so
+//! the drained rows are moved to the end of the vec up front with the same permutation machinery as
+//! sorting, and then popped one at a time. Only the rows from the start of the range onwards are
+//! permuted, and the rows after the range are moved up at the same time, so the gap is already
+//! closed when the drain is dropped, and only the rows that weren't yielded are left to remove.
+//!
+//! The drained rows are reversed, so popping yields them from the front. Rows can only be taken
+//! from the end of the vec, so the first `next_back` pops every remaining row into a buffer, which
+//! both ends are then taken from. Only `apply_permutation`, `pop` and `truncate` are used, so this
+//! is shared by structs and enums
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Visibility;
+
+use super::structs::StructNames;
+
+pub(super) fn codegen(names: &StructNames, vis: &Visibility) -> TokenStream {
+    let krate = &names.krate;
+    let name = &names.drain;
+    let vec = &names.vec;
+    let vec_type = names.vec_type();
+    let original_type = names.original_type();
+    let lifetime = &names.lifetime;
+
+    let vec_generics = names.vec_generics();
+    let (vec_impl_generics, _, vec_where_clause) = vec_generics.split_for_impl();
+
+    let generics = names.with_allocator(names.ref_generics());
+    let definition_generics = names.with_default_allocator(generics.clone());
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let definition_where_clause = &definition_generics.where_clause;
+    let drain_type = quote!(#name #ty_generics);
+
+    let doc = format!(
+        "An iterator that removes a range of rows from a [`{vec}`], yielding them by value.\n\n\
+         Created by [`{vec}::drain`]"
+    );
+
+    quote! {
+        #[doc = #doc]
+        #vis struct #name #definition_generics #definition_where_clause {
+            vec: & #lifetime mut #vec_type,
+            /// The number of rows that haven't been yielded yet, which are at the end of the vec,
+            /// last row first
+            remaining: ::core::primitive::usize,
+            /// The rows that haven't been yielded yet, in order, once iterating from the back has
+            /// taken them out of the vec
+            buffer: ::core::option::Option<#krate::__private::VecIntoIter<#original_type>>,
+        }
+
+        impl #vec_impl_generics #vec_type #vec_where_clause {
+            /// Remove the rows in `range`, returning an iterator over them by value. The rows
+            /// after the range are moved up straight away, and any rows that aren't iterated over
+            /// are dropped along with the iterator.
+            ///
+            /// The rows from the start of the range onwards are moved with a permutation, like
+            /// sorting. Iterating from the back moves the rows that are left into a buffer the
+            /// first time, so each row is still only moved once. If the iterator is leaked, the
+            /// rows that weren't yielded may be left at the end of the vec.
+            ///
+            /// Panics if the range is out of bounds, or its start is after its end
+            pub fn drain<#lifetime, R>(& #lifetime mut self, range: R) -> #drain_type
+            where
+                R: ::core::ops::RangeBounds<::core::primitive::usize>,
+            {
+                let len = self.len();
+                let ::core::ops::Range { start, end } = #krate::__private::range(range, len);
+
+                // popping from the end yields the rows in order, so they're moved there reversed
+                if start != end {
+                    let order = (end..len).chain((start..end).rev()).collect();
+                    self.apply_permutation(&#krate::__private::Permutation::with_offset(start, order));
+                }
+
+                #name {
+                    vec: self,
+                    remaining: end - start,
+                    buffer: ::core::option::Option::None,
+                }
+            }
+        }
+
+        impl #impl_generics ::core::iter::Iterator for #drain_type #where_clause {
+            type Item = #original_type;
+
+            #[inline]
+            fn next(&mut self) -> ::core::option::Option<Self::Item> {
+                if let ::core::option::Option::Some(buffer) = &mut self.buffer {
+                    return buffer.next();
+                }
+
+                if self.remaining == 0 {
+                    return ::core::option::Option::None;
+                }
+
+                self.remaining -= 1;
+                self.vec.pop()
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (::core::primitive::usize, ::core::option::Option<::core::primitive::usize>) {
+                let len = match &self.buffer {
+                    ::core::option::Option::Some(buffer) => ::core::iter::ExactSizeIterator::len(buffer),
+                    ::core::option::Option::None => self.remaining,
+                };
+                (len, ::core::option::Option::Some(len))
+            }
+        }
+
+        impl #impl_generics ::core::iter::DoubleEndedIterator for #drain_type #where_clause {
+            #[inline]
+            fn next_back(&mut self) -> ::core::option::Option<Self::Item> {
+                let remaining = ::core::mem::take(&mut self.remaining);
+                let vec = &mut *self.vec;
+
+                self.buffer
+                    .get_or_insert_with(|| {
+                        let rows: #krate::__private::Vec<_> =
+                            (0..remaining).map_while(|_| vec.pop()).collect();
+                        ::core::iter::IntoIterator::into_iter(rows)
+                    })
+                    .next_back()
+            }
+        }
+
+        impl #impl_generics ::core::iter::ExactSizeIterator for #drain_type #where_clause {}
+
+        impl #impl_generics ::core::iter::FusedIterator for #drain_type #where_clause {}
+
+        impl #impl_generics ::core::ops::Drop for #drain_type #where_clause {
+            fn drop(&mut self) {
+                let len = self.vec.len() - self.remaining;
+                self.vec.truncate(len);
+            }
+        }
+    }
+}
@@ -0,0 +1,598 @@
+//! Standard trait impls for enums, opted into with `#[sov(derive(...))]`, in the same way as for
+//! structs. Rows of different variants are ordered by the order of the variants, like a derived
+//! `PartialOrd`
+use syn::{parse_quote, WherePredicate};
+
+use crate::{
+    codegen::derives::{ROW_PAIRS, SAME_ROW_PAIRS},
+    parse::Derive,
+};
+
+use super::*;
+
+pub(super) fn codegen(
+    names: &EnumNames,
+    variants: &[VariantColumns],
+    derives: &[Derive],
+) -> TokenStream {
+    derives
+        .iter()
+        .map(|derive| match derive {
+            Derive::Clone => clone(names, variants),
+            Derive::Debug => debug(names, variants),
+            Derive::Default => default(names),
+            Derive::PartialEq => partial_eq(names, variants),
+            Derive::Eq => eq(names, variants),
+            Derive::PartialOrd => partial_ord(names, variants),
+            Derive::Ord => ord(names, variants),
+            Derive::Hash => hash(names, variants),
+        })
+        .collect()
+}
+
+fn clone(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let columns = column_names(variants);
+
+    let vec = vec_impl(
+        names,
+        variants,
+        quote!(::core::clone::Clone),
+        quote! {
+            #[inline]
+            fn clone(&self) -> Self {
+                Self {
+                    kinds: ::core::clone::Clone::clone(&self.kinds),
+                    slots: ::core::clone::Clone::clone(&self.slots),
+                    #(#columns: ::core::clone::Clone::clone(&self.#columns),)*
+                }
+            }
+        },
+    );
+    let to_owned = to_owned(names, variants);
+
+    quote! {
+        #vec
+        #to_owned
+    }
+}
+
+/// `to_owned` for both reference types, and `From` impls to convert them to the original type
+fn to_owned(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let original = &names.structs.original;
+    let original_type = names.structs.original_type();
+
+    let arms: Vec<_> = variants
+        .iter()
+        .map(|variant| {
+            let ident = variant.ident();
+            let pattern = variant.pattern();
+            let fields = variant.with_fields(variant.columns.iter().map(|column| {
+                let binding = &column.binding;
+                quote!(::core::clone::Clone::clone(&**#binding))
+            }));
+
+            quote!(Self::#ident #pattern => #original::#ident #fields)
+        })
+        .collect();
+
+    let bounds = columns(variants)
+        .map(|Column { ty, .. }| -> WherePredicate { parse_quote!(#ty: ::core::clone::Clone) });
+    let generics = util::with_bounds(&names.structs.ref_generics(), bounds);
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let impls = [false, true].map(|mutable| {
+        let ref_type = names.structs.ref_type(mutable);
+
+        quote! {
+            impl #impl_generics #ref_type #where_clause {
+                /// Clone the fields of the row into a new value of the original type
+                #[inline]
+                pub fn to_owned(&self) -> #original_type {
+                    match self {
+                        #(#arms,)*
+                    }
+                }
+            }
+
+            impl #impl_generics ::core::convert::From<#ref_type> for #original_type #where_clause {
+                #[inline]
+                fn from(value: #ref_type) -> Self {
+                    value.to_owned()
+                }
+            }
+        }
+    });
+
+    quote!(#(#impls)*)
+}
+
+fn debug(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let fmt = |body: TokenStream| {
+        quote! {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #body
+            }
+        }
+    };
+
+    let vec = vec_impl(
+        names,
+        variants,
+        quote!(::core::fmt::Debug),
+        fmt(quote!(f.debug_list().entries(self.iter()).finish())),
+    );
+
+    // rows are printed as if they were the original type
+    let refs = ref_impls(names, variants, quote!(::core::fmt::Debug), |ref_name| {
+        let arms = variants.iter().map(|variant| {
+            let ident = variant.ident();
+            let name = ident.unraw().to_string();
+            let pattern = variant.pattern();
+            let bindings = variant.columns.iter().map(|column| &column.binding);
+
+            let body = match &variant.variant.fields {
+                Fields::Named(_) => {
+                    let field_names = variant
+                        .columns
+                        .iter()
+                        .map(|column| column.binding.unraw().to_string());
+
+                    quote! {
+                        f.debug_struct(#name)
+                            #(.field(#field_names, #bindings))*
+                            .finish()
+                    }
+                }
+                Fields::Unnamed(_) => quote! {
+                    f.debug_tuple(#name)
+                        #(.field(#bindings))*
+                        .finish()
+                },
+                Fields::Unit => quote!(f.write_str(#name)),
+            };
+
+            quote!(#ref_name::#ident #pattern => #body)
+        });
+
+        fmt(quote! {
+            match self {
+                #(#arms,)*
+            }
+        })
+    });
+
+    quote! {
+        #vec
+        #refs
+    }
+}
+
+fn default(names: &EnumNames) -> TokenStream {
+    let vec = &names.structs.vec;
+    let (impl_generics, ty_generics, where_clause) = names.structs.generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics ::core::default::Default for #vec #ty_generics #where_clause {
+            #[inline]
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    }
+}
+
+fn partial_eq(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let columns = column_names(variants);
+    let trait_path = quote!(::core::cmp::PartialEq);
+
+    // the slots are determined by the kinds, so they don't need to be compared
+    let vec = vec_impl(
+        names,
+        variants,
+        trait_path.clone(),
+        quote! {
+            #[inline]
+            fn eq(&self, other: &Self) -> bool {
+                self.kinds == other.kinds #(&& self.#columns == other.#columns)*
+            }
+        },
+    );
+
+    let rows = ROW_PAIRS.map(|(lhs, rhs)| {
+        row_impl(
+            names,
+            variants,
+            (lhs, rhs),
+            &trait_path,
+            true,
+            |matches, _| {
+                let arms = matches.iter().map(|(patterns, fields)| {
+                    let comparisons = fields
+                        .iter()
+                        .map(|(lhs, rhs)| quote!(::core::cmp::PartialEq::eq(#lhs, #rhs)));
+
+                    quote!(#patterns => true #(&& #comparisons)*)
+                });
+
+                let rhs_type = row_type(names, rhs);
+
+                quote! {
+                    #[inline]
+                    fn eq(&self, other: &#rhs_type) -> bool {
+                        #[allow(unreachable_patterns)]
+                        match (self, other) {
+                            #(#arms,)*
+                            _ => false,
+                        }
+                    }
+                }
+            },
+        )
+    });
+
+    quote! {
+        #vec
+        #(#rows)*
+    }
+}
+
+fn eq(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let trait_path = quote!(::core::cmp::Eq);
+    let vec = vec_impl(names, variants, trait_path.clone(), quote!());
+    let rows = SAME_ROW_PAIRS
+        .map(|pair| row_impl(names, variants, pair, &trait_path, false, |_, _| quote!()));
+
+    quote! {
+        #vec
+        #(#rows)*
+    }
+}
+
+fn partial_ord(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let trait_path = quote!(::core::cmp::PartialOrd);
+
+    let vec = vec_impl(
+        names,
+        variants,
+        trait_path.clone(),
+        quote! {
+            #[inline]
+            fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+                ::core::iter::Iterator::partial_cmp(self.iter(), other.iter())
+            }
+        },
+    );
+
+    let rows = ROW_PAIRS.map(|(lhs, rhs)| {
+        row_impl(names, variants, (lhs, rhs), &trait_path, true, |matches, indices| {
+            let arms = matches.iter().map(|(patterns, fields)| {
+                let comparisons = fields.iter().map(|(lhs, rhs)| {
+                    quote! {
+                        match ::core::cmp::PartialOrd::partial_cmp(#lhs, #rhs) {
+                            ::core::option::Option::Some(::core::cmp::Ordering::Equal) => {}
+                            ordering => return ordering,
+                        }
+                    }
+                });
+
+                quote! {
+                    #patterns => {
+                        #(#comparisons)*
+                        ::core::option::Option::Some(::core::cmp::Ordering::Equal)
+                    }
+                }
+            });
+
+            let rhs_type = row_type(names, rhs);
+            let (lhs_index, rhs_index) = indices;
+
+            quote! {
+                #[inline]
+                fn partial_cmp(&self, other: &#rhs_type) -> ::core::option::Option<::core::cmp::Ordering> {
+                    #[allow(unreachable_patterns)]
+                    match (self, other) {
+                        #(#arms,)*
+                        _ => ::core::cmp::PartialOrd::partial_cmp(&#lhs_index, &#rhs_index),
+                    }
+                }
+            }
+        })
+    });
+
+    quote! {
+        #vec
+        #(#rows)*
+    }
+}
+
+fn ord(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let trait_path = quote!(::core::cmp::Ord);
+
+    let vec = vec_impl(
+        names,
+        variants,
+        trait_path.clone(),
+        quote! {
+            #[inline]
+            fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                ::core::iter::Iterator::cmp(self.iter(), other.iter())
+            }
+        },
+    );
+
+    let rows = SAME_ROW_PAIRS.map(|pair| {
+        row_impl(
+            names,
+            variants,
+            pair,
+            &trait_path,
+            false,
+            |matches, indices| {
+                let arms = matches.iter().map(|(patterns, fields)| {
+                    let comparisons = fields.iter().map(|(lhs, rhs)| {
+                        quote! {
+                            match ::core::cmp::Ord::cmp(#lhs, #rhs) {
+                                ::core::cmp::Ordering::Equal => {}
+                                ordering => return ordering,
+                            }
+                        }
+                    });
+
+                    quote! {
+                        #patterns => {
+                            #(#comparisons)*
+                            ::core::cmp::Ordering::Equal
+                        }
+                    }
+                });
+
+                let (lhs_index, rhs_index) = indices;
+
+                quote! {
+                    #[inline]
+                    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                        #[allow(unreachable_patterns)]
+                        match (self, other) {
+                            #(#arms,)*
+                            _ => ::core::cmp::Ord::cmp(&#lhs_index, &#rhs_index),
+                        }
+                    }
+                }
+            },
+        )
+    });
+
+    quote! {
+        #vec
+        #(#rows)*
+    }
+}
+
+fn hash(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let columns = column_names(variants);
+
+    let hash = |body: TokenStream| {
+        quote! {
+            #[inline]
+            fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+                #body
+            }
+        }
+    };
+
+    let vec = vec_impl(
+        names,
+        variants,
+        quote!(::core::hash::Hash),
+        hash(quote! {
+            ::core::hash::Hash::hash(&self.kinds, state);
+            #(::core::hash::Hash::hash(&self.#columns, state);)*
+        }),
+    );
+
+    let refs = ref_impls(names, variants, quote!(::core::hash::Hash), |ref_name| {
+        let arms = variants.iter().map(|variant| {
+            let ident = variant.ident();
+            let pattern = variant.pattern();
+            let bindings = variant.columns.iter().map(|column| &column.binding);
+
+            quote! {
+                #ref_name::#ident #pattern => {
+                    #(::core::hash::Hash::hash(#bindings, state);)*
+                }
+            }
+        });
+
+        // like a derived `Hash`, which only hashes the discriminant if there's more than one
+        // variant, so that rows hash the same as the original type
+        let discriminant = match variants.len() {
+            1 => quote!(),
+            _ => quote!(::core::hash::Hash::hash(&::core::mem::discriminant(self), state);),
+        };
+
+        hash(quote! {
+            #discriminant
+
+            match self {
+                #(#arms,)*
+            }
+        })
+    });
+
+    quote! {
+        #vec
+        #refs
+    }
+}
+
+fn columns<'a>(variants: &'a [VariantColumns]) -> impl Iterator<Item = &'a Column<'a>> {
+    variants.iter().flat_map(|variant| &variant.columns)
+}
+
+fn column_names<'a>(variants: &'a [VariantColumns]) -> Vec<&'a Ident> {
+    columns(variants).map(|column| &column.name).collect()
+}
+
+/// Implement `trait_path` for the `Vec`-like type, as long as the type of every field implements it
+fn vec_impl(
+    names: &EnumNames,
+    variants: &[VariantColumns],
+    trait_path: TokenStream,
+    body: TokenStream,
+) -> TokenStream {
+    let vec = &names.structs.vec;
+
+    let bounds = columns(variants)
+        .map(|Column { ty, .. }| -> WherePredicate { parse_quote!(#ty: #trait_path) });
+
+    let generics = util::with_bounds(&names.structs.generics, bounds);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #trait_path for #vec #ty_generics #where_clause {
+            #body
+        }
+    }
+}
+
+/// Implement `trait_path` for both reference types, as long as the type of each field implements it.
+/// `body` is called with the name of the reference type
+fn ref_impls(
+    names: &EnumNames,
+    variants: &[VariantColumns],
+    trait_path: TokenStream,
+    body: impl Fn(&Ident) -> TokenStream,
+) -> TokenStream {
+    let bounds: Vec<WherePredicate> = columns(variants)
+        .map(|Column { ty, .. }| parse_quote!(#ty: #trait_path))
+        .collect();
+
+    let impls = [false, true].map(|mutable| {
+        let name = match mutable {
+            true => &names.structs.mut_ref,
+            false => &names.structs.shared_ref,
+        };
+
+        let generics = util::with_bounds(&names.structs.ref_generics(), bounds.clone());
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let body = body(name);
+
+        quote! {
+            impl #impl_generics #trait_path for #name #ty_generics #where_clause {
+                #body
+            }
+        }
+    });
+
+    quote!(#(#impls)*)
+}
+
+/// The pattern matching a variant in both rows being compared, and references to each pair of
+/// fields it binds
+type VariantMatch = (TokenStream, Vec<(TokenStream, TokenStream)>);
+
+/// Implement a comparison trait between two kinds of row, comparing them field by field when they
+/// are the same variant.
+///
+/// If `generic` is set, the trait takes the type being compared against as a parameter, like
+/// `PartialEq<Rhs>`. `body` is called with a match for each variant, and the index of the variant
+/// of `self` and `other`
+fn row_impl(
+    names: &EnumNames,
+    variants: &[VariantColumns],
+    (lhs, rhs): (IterKind, IterKind),
+    trait_path: &TokenStream,
+    generic: bool,
+    body: impl Fn(&[VariantMatch], (TokenStream, TokenStream)) -> TokenStream,
+) -> TokenStream {
+    let lhs_name = row_name(names, lhs);
+    let rhs_name = row_name(names, rhs);
+    let lhs_type = row_type(names, lhs);
+    let rhs_type = row_type(names, rhs);
+
+    let matches: Vec<VariantMatch> = variants
+        .iter()
+        .map(|variant| {
+            let ident = variant.ident();
+            let ours: Vec<_> = (0..variant.columns.len())
+                .map(|index| format_ident!("__self_{index}"))
+                .collect();
+            let theirs: Vec<_> = (0..variant.columns.len())
+                .map(|index| format_ident!("__other_{index}"))
+                .collect();
+
+            let our_pattern = variant.with_fields(ours.iter().map(|ident| quote!(#ident)));
+            let their_pattern = variant.with_fields(theirs.iter().map(|ident| quote!(#ident)));
+            let patterns = quote! {
+                (#lhs_name::#ident #our_pattern, #rhs_name::#ident #their_pattern)
+            };
+
+            let fields = ours
+                .iter()
+                .zip(&theirs)
+                .map(|(ours, theirs)| (binding_ref(lhs, ours), binding_ref(rhs, theirs)))
+                .collect();
+
+            (patterns, fields)
+        })
+        .collect();
+
+    let indices = (
+        variant_index(variants, lhs_name, quote!(self)),
+        variant_index(variants, rhs_name, quote!(other)),
+    );
+
+    let bounds = columns(variants)
+        .map(|Column { ty, .. }| -> WherePredicate { parse_quote!(#ty: #trait_path) });
+    let generics = util::with_bounds(&names.structs.ref_generics(), bounds);
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let trait_path = match generic {
+        true => quote!(#trait_path<#rhs_type>),
+        false => trait_path.clone(),
+    };
+    let body = body(&matches, indices);
+
+    quote! {
+        impl #impl_generics #trait_path for #lhs_type #where_clause {
+            #body
+        }
+    }
+}
+
+fn row_name(names: &EnumNames, kind: IterKind) -> &Ident {
+    match kind {
+        IterKind::Shared => &names.structs.shared_ref,
+        IterKind::Mutable => &names.structs.mut_ref,
+        IterKind::Owned => &names.structs.original,
+    }
+}
+
+fn row_type(names: &EnumNames, kind: IterKind) -> TokenStream {
+    names.structs.iter_item_type(kind)
+}
+
+/// A reference to a field bound by matching on a reference to a row. The reference types hold
+/// references, so those bindings are a reference to a reference
+fn binding_ref(kind: IterKind, binding: &Ident) -> TokenStream {
+    match kind {
+        IterKind::Owned => quote!(#binding),
+        IterKind::Shared | IterKind::Mutable => quote!(&**#binding),
+    }
+}
+
+/// The index of the variant of `row`, for ordering rows of different variants
+fn variant_index(variants: &[VariantColumns], name: &Ident, row: TokenStream) -> TokenStream {
+    let arms = variants.iter().enumerate().map(|(index, variant)| {
+        let ident = variant.ident();
+        let index = proc_macro2::Literal::usize_suffixed(index);
+
+        quote!(#name::#ident { .. } => #index)
+    });
+
+    quote! {
+        match #row {
+            #(#arms,)*
+        }
+    }
+}
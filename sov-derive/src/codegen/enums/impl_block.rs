@@ -0,0 +1,732 @@
+use super::*;
+use crate::codegen::conversions::RefToOwned;
+
+pub(super) fn generate_impl_block(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let name = &names.structs.vec;
+    let (impl_generics, ty_generics, where_clause) = names.structs.generics.split_for_impl();
+
+    let constructors = constructors(names, variants);
+    let capacity = capacity(names, variants);
+    let get = get(names, variants);
+    let insert = insert(names, variants);
+    let remove = remove(names, variants);
+    let rows = rows(names, variants);
+    let slices = slices(names, variants);
+    let accessors = accessors(names, variants);
+    let apply_permutation = apply_permutation(names, variants);
+    let (par_methods, par_impls) = parallel(names, variants);
+
+    quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #constructors
+            #capacity
+            #get
+            #insert
+            #remove
+            #rows
+            #slices
+            #accessors
+            #apply_permutation
+            #par_methods
+        }
+
+        #par_impls
+    }
+}
+
+/// Parallel iteration, when the `rayon` feature is enabled.
+///
+/// The rows of a variant aren't at the same indices as its columns, so the columns can't be zipped
+/// like they are for structs. Instead, each row is looked up by index, which means there is no
+/// `par_iter_mut`
+#[cfg(feature = "rayon")]
+fn parallel(names: &EnumNames, variants: &[VariantColumns]) -> (TokenStream, TokenStream) {
+    use crate::codegen::impl_block::rayon;
+
+    let rayon_path = rayon::rayon_path(&names.structs);
+    let lifetime = &names.structs.lifetime;
+    let ref_type = names.structs.ref_type(false);
+    let bounds = variants
+        .iter()
+        .flat_map(|variant| &variant.columns)
+        .map(|column| util::non_trivial_bound(column.ty, quote!(::core::marker::Sync)));
+
+    let methods = quote! {
+        /// A parallel iterator over shared references to each row
+        #[inline]
+        pub fn par_iter<#lifetime>(& #lifetime self)
+            -> impl #rayon_path::iter::IndexedParallelIterator<Item = #ref_type>
+        where
+            #(#bounds,)*
+        {
+            use #rayon_path::prelude::*;
+            (0..self.len()).into_par_iter().map(move |index| self.get(index))
+        }
+    };
+
+    (methods, rayon::collect_impls(&names.structs))
+}
+
+#[cfg(not(feature = "rayon"))]
+fn parallel(_names: &EnumNames, _variants: &[VariantColumns]) -> (TokenStream, TokenStream) {
+    (TokenStream::new(), TokenStream::new())
+}
+
+fn constructors(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let krate = &names.structs.krate;
+    let columns: Vec<_> = variants
+        .iter()
+        .flat_map(|variant| &variant.columns)
+        .map(|Column { name, .. }| quote!(#name: #krate::__private::Vec::new()))
+        .collect();
+
+    quote! {
+        #[inline]
+        pub fn new() -> Self {
+            Self {
+                kinds: #krate::__private::Vec::new(),
+                slots: #krate::__private::Vec::new(),
+                #(#columns,)*
+            }
+        }
+
+        /// Create an empty vec with space for at least `capacity` rows.
+        ///
+        /// Since it isn't known in advance which variants will be pushed, only the discriminant
+        /// and slot columns are preallocated
+        #[inline]
+        pub fn with_capacity(capacity: ::core::primitive::usize) -> Self {
+            Self {
+                kinds: #krate::__private::Vec::with_capacity(capacity),
+                slots: #krate::__private::Vec::with_capacity(capacity),
+                #(#columns,)*
+            }
+        }
+
+        /// Reserve space for at least `additional` more rows.
+        ///
+        /// Like `with_capacity`, only the discriminant and slot columns are reserved
+        #[inline]
+        pub fn reserve(&mut self, additional: ::core::primitive::usize) {
+            self.kinds.reserve(additional);
+            self.slots.reserve(additional);
+        }
+
+        #[inline]
+        pub fn len(&self) -> usize {
+            self.kinds.len()
+        }
+
+        #[inline]
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+    }
+}
+
+/// `capacity` and the methods that change it. Like `reserve`, the methods that grow the vec only
+/// apply to the discriminant and slot columns, while the methods that shrink it apply to every
+/// column
+fn capacity(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let original_type = names.structs.original_type();
+    let clone_bound = util::non_trivial_bound(&original_type, quote!(::core::clone::Clone));
+    let variant_count = variants.len();
+    let columns: Vec<_> = variants
+        .iter()
+        .flat_map(|variant| &variant.columns)
+        .map(|Column { name, .. }| name)
+        .collect();
+
+    let truncate_columns = variants
+        .iter()
+        .enumerate()
+        .filter(|(_, variant)| !variant.columns.is_empty())
+        .map(|(index, variant)| {
+            let columns = variant.columns.iter().map(|Column { name, .. }| name);
+
+            quote! {
+                #(self.#columns.truncate(self.#columns.len() - removed[#index]);)*
+            }
+        });
+
+    quote! {
+        /// The number of rows the vec can hold without reallocating the discriminant and slot
+        /// columns
+        #[inline]
+        pub fn capacity(&self) -> ::core::primitive::usize {
+            ::core::cmp::min(self.kinds.capacity(), self.slots.capacity())
+        }
+
+        /// Reserve space for exactly `additional` more rows in the discriminant and slot columns
+        #[inline]
+        pub fn reserve_exact(&mut self, additional: ::core::primitive::usize) {
+            self.kinds.reserve_exact(additional);
+            self.slots.reserve_exact(additional);
+        }
+
+        /// Shrink the capacity of every column as much as possible
+        #[inline]
+        pub fn shrink_to_fit(&mut self) {
+            self.kinds.shrink_to_fit();
+            self.slots.shrink_to_fit();
+            #(self.#columns.shrink_to_fit();)*
+        }
+
+        /// Shrink the capacity of every column to at least `min_capacity` and the number of rows
+        /// in it
+        #[inline]
+        pub fn shrink_to(&mut self, min_capacity: ::core::primitive::usize) {
+            self.kinds.shrink_to(min_capacity);
+            self.slots.shrink_to(min_capacity);
+            #(self.#columns.shrink_to(min_capacity);)*
+        }
+
+        /// Remove every row after the first `len`, doing nothing if there are fewer rows.
+        ///
+        /// The rows of each variant are stored in order, so the removed rows are at the end of
+        /// their variant's columns
+        pub fn truncate(&mut self, len: ::core::primitive::usize) {
+            if len >= self.len() {
+                return;
+            }
+
+            let mut removed = [0; #variant_count];
+            for kind in &self.kinds[len..] {
+                removed[*kind as ::core::primitive::usize] += 1;
+            }
+
+            self.kinds.truncate(len);
+            self.slots.truncate(len);
+            #(#truncate_columns)*
+        }
+
+        /// Remove every row, keeping the capacity of every column
+        #[inline]
+        pub fn clear(&mut self) {
+            self.kinds.clear();
+            self.slots.clear();
+            #(self.#columns.clear();)*
+        }
+
+        /// Resize the vec to `new_len` rows, either by removing rows from the end or by adding
+        /// clones of `value`
+        pub fn resize(&mut self, new_len: ::core::primitive::usize, value: #original_type)
+        where
+            #clone_bound
+        {
+            match new_len.checked_sub(self.len()) {
+                ::core::option::Option::Some(additional) => ::core::iter::Extend::extend(
+                    self,
+                    ::core::iter::repeat(value).take(additional),
+                ),
+                ::core::option::Option::None => self.truncate(new_len),
+            }
+        }
+
+        /// Resize the vec to `new_len` rows, either by removing rows from the end or by adding
+        /// rows returned by `f`
+        pub fn resize_with<F>(&mut self, new_len: ::core::primitive::usize, f: F)
+        where
+            F: ::core::ops::FnMut() -> #original_type,
+        {
+            match new_len.checked_sub(self.len()) {
+                ::core::option::Option::Some(additional) => ::core::iter::Extend::extend(
+                    self,
+                    ::core::iter::repeat_with(f).take(additional),
+                ),
+                ::core::option::Option::None => self.truncate(new_len),
+            }
+        }
+    }
+}
+
+fn get(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    fn get_impl(names: &EnumNames, variants: &[VariantColumns], mutable: bool) -> TokenStream {
+        let name = match mutable {
+            true => &names.structs.mut_ref,
+            false => &names.structs.shared_ref,
+        };
+        let ref_type = names.structs.ref_type(mutable);
+        let lifetime = &names.structs.lifetime;
+        let kind = &names.kind;
+
+        let fn_name = match mutable {
+            true => quote!(get_mut),
+            false => quote!(get),
+        };
+
+        let maybe_mut = match mutable {
+            true => quote!(mut),
+            false => quote!(),
+        };
+
+        let arms = variants.iter().map(|variant| {
+            let ident = variant.ident();
+            let fields = variant.with_fields(
+                variant
+                    .columns
+                    .iter()
+                    .map(|Column { name, .. }| quote!(& #maybe_mut self.#name[slot])),
+            );
+
+            quote!(#kind::#ident => #name::#ident #fields)
+        });
+
+        quote! {
+            #[inline]
+            pub fn #fn_name<#lifetime>(& #lifetime #maybe_mut self, index: ::core::primitive::usize) -> #ref_type {
+                let slot = self.slots[index];
+                match self.kinds[index] {
+                    #(#arms,)*
+                }
+            }
+        }
+    }
+
+    let shared = get_impl(names, variants, false);
+    let mutable = get_impl(names, variants, true);
+    let lifetime = &names.structs.lifetime;
+    let iter_type = names.structs.iter_type(IterKind::Shared);
+    let iter_mut_type = names.structs.iter_type(IterKind::Mutable);
+    let iter = types::construct_iter(names, variants, IterKind::Shared);
+    let iter_mut = types::construct_iter(names, variants, IterKind::Mutable);
+
+    quote! {
+        #shared
+        #mutable
+
+        #[inline]
+        pub fn iter<#lifetime>(& #lifetime self) -> #iter_type {
+            #iter
+        }
+
+        #[inline]
+        pub fn iter_mut<#lifetime>(& #lifetime mut self) -> #iter_mut_type {
+            #iter_mut
+        }
+    }
+}
+
+fn insert(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let original = &names.structs.original;
+    let original_type = names.structs.original_type();
+    let kind = &names.kind;
+
+    let push_arms = variants.iter().map(|variant| {
+        let ident = variant.ident();
+        let pattern = variant.pattern();
+        let slot = match variant.columns.first() {
+            Some(Column { name, .. }) => quote!(self.#name.len()),
+            None => quote!(0),
+        };
+        let pushes = variant
+            .columns
+            .iter()
+            .map(|Column { name, binding, .. }| quote!(self.#name.push(#binding);));
+
+        quote! {
+            #original::#ident #pattern => {
+                self.kinds.push(#kind::#ident);
+                self.slots.push(#slot);
+                #(#pushes)*
+            }
+        }
+    });
+
+    let insert_arms = variants.iter().map(|variant| {
+        let ident = variant.ident();
+        let pattern = variant.pattern();
+
+        if variant.columns.is_empty() {
+            return quote! {
+                #original::#ident #pattern => {
+                    self.insert_row(index, #kind::#ident);
+                }
+            };
+        }
+
+        let inserts = variant
+            .columns
+            .iter()
+            .map(|Column { name, binding, .. }| quote!(self.#name.insert(slot, #binding);));
+
+        quote! {
+            #original::#ident #pattern => {
+                let slot = self.insert_row(index, #kind::#ident);
+                #(#inserts)*
+            }
+        }
+    });
+
+    quote! {
+        #[inline]
+        pub fn push(&mut self, value: #original_type) {
+            self.debug_assert_consistent();
+            match value {
+                #(#push_arms)*
+            }
+        }
+
+        /// Insert a row at `index`, shifting all rows after it.
+        ///
+        /// This is `O(n)` in the total number of rows, since the slots of all later rows of the
+        /// same variant need to be updated
+        pub fn insert(&mut self, index: usize, value: #original_type) {
+            self.debug_assert_consistent();
+            match value {
+                #(#insert_arms)*
+            }
+        }
+    }
+}
+
+fn remove(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let original = &names.structs.original;
+    let original_type = names.structs.original_type();
+    let kind = &names.kind;
+
+    let arms = |method: TokenStream| {
+        let arms = variants.iter().map(|variant| {
+            let ident = variant.ident();
+            let fields = variant.with_fields(
+                variant
+                    .columns
+                    .iter()
+                    .map(|Column { name, .. }| quote!(self.#name.#method)),
+            );
+
+            quote!(#kind::#ident => #original::#ident #fields)
+        });
+
+        quote!(#(#arms,)*)
+    };
+
+    let pop_arms = arms(quote!(pop()?));
+    let remove_arms = arms(quote!(remove(slot)));
+
+    quote! {
+        #[inline]
+        pub fn pop(&mut self) -> Option<#original_type> {
+            self.debug_assert_consistent();
+
+            // rows of each variant are stored in order, so the last row is also the last row in
+            // its variant's columns
+            let kind = self.kinds.pop()?;
+            self.slots.pop();
+
+            Some(match kind {
+                #pop_arms
+            })
+        }
+
+        /// Remove the row at `index`, shifting all rows after it.
+        ///
+        /// This is `O(n)` in the total number of rows, since the slots of all later rows of the
+        /// same variant need to be updated
+        pub fn remove(&mut self, index: usize) -> #original_type {
+            self.debug_assert_consistent();
+            let (kind, slot) = self.remove_row(index);
+
+            match kind {
+                #remove_arms
+            }
+        }
+
+        /// Remove the row at `index`, replacing it with the last row.
+        ///
+        /// Unlike `Vec::swap_remove`, this is `O(n)`, since the rows of each variant are kept in
+        /// order
+        pub fn swap_remove(&mut self, index: usize) -> #original_type {
+            let value = self.remove(index);
+
+            if index < self.len() {
+                if let Some(last) = self.pop() {
+                    self.insert(index, last);
+                }
+            }
+
+            value
+        }
+    }
+}
+
+/// Bookkeeping for the discriminant and slot columns
+fn rows(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let kind = &names.kind;
+
+    // counting the rows of each variant would make every method `O(n)`, so only the columns of
+    // each variant are checked against each other
+    let checks = variants
+        .iter()
+        .filter_map(|variant| variant.columns.split_first())
+        .flat_map(|(first, rest)| {
+            let first = &first.name;
+
+            rest.iter().map(move |Column { name, .. }| {
+                let message =
+                    format!("column `{name}` has {{}} rows, but column `{first}` has {{}} rows");
+
+                quote! {
+                    ::core::debug_assert_eq!(self.#name.len(), self.#first.len(), #message, self.#name.len(), self.#first.len());
+                }
+            })
+        });
+
+    let with_columns = variants
+        .iter()
+        .filter(|variant| !variant.columns.is_empty())
+        .map(|variant| {
+            let ident = variant.ident();
+            quote!(#kind::#ident)
+        });
+
+    quote! {
+        /// Whether rows of the given kind have any columns. The rows of variants without fields
+        /// all have slot `0`, so their slots don't need to be updated
+        #[inline]
+        fn has_columns(kind: #kind) -> bool {
+            ::core::matches!(kind, #(#with_columns)|*)
+        }
+
+        /// Insert a row of the given kind at `index`, returning the slot its fields should be
+        /// inserted at in the variant's columns
+        fn insert_row(&mut self, index: usize, kind: #kind) -> usize {
+            if !Self::has_columns(kind) {
+                self.kinds.insert(index, kind);
+                self.slots.insert(index, 0);
+                return 0;
+            }
+
+            let slot = self.kinds[..index]
+                .iter()
+                .filter(|other| **other == kind)
+                .count();
+
+            for (other, slot) in self.kinds[index..].iter().zip(&mut self.slots[index..]) {
+                if *other == kind {
+                    *slot += 1;
+                }
+            }
+
+            self.kinds.insert(index, kind);
+            self.slots.insert(index, slot);
+
+            slot
+        }
+
+        /// A check that the columns are the same length as each other, which is called at the start
+        /// of every method that changes the length. The methods keep the columns in sync, but
+        /// `#[sov(expose_columns)]` allows modifying them directly
+        #[inline]
+        fn debug_assert_consistent(&self) {
+            ::core::debug_assert_eq!(self.kinds.len(), self.slots.len());
+            #(#checks)*
+        }
+
+        /// Remove the row at `index`, returning its kind and the slot its fields should be
+        /// removed from in the variant's columns
+        fn remove_row(&mut self, index: usize) -> (#kind, usize) {
+            let kind = self.kinds.remove(index);
+            let slot = self.slots.remove(index);
+
+            if !Self::has_columns(kind) {
+                return (kind, slot);
+            }
+
+            for (other, slot) in self.kinds[index..].iter().zip(&mut self.slots[index..]) {
+                if *other == kind {
+                    *slot -= 1;
+                }
+            }
+
+            (kind, slot)
+        }
+    }
+}
+
+/// Reorder the rows, for sorting. The rows of each variant need to stay in order in its columns,
+/// so each variant's columns get their own permutation, and the slots are renumbered. The rows of
+/// each variant before the permutation's offset keep their slots, so its rows after the offset
+/// start from the smallest of their slots
+fn apply_permutation(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let krate = &names.structs.krate;
+    let variant_count = variants.len();
+
+    let permute_columns = variants
+        .iter()
+        .enumerate()
+        .filter(|(_, variant)| !variant.columns.is_empty())
+        .map(|(index, variant)| {
+            let columns = variant.columns.iter().map(|Column { name, .. }| name);
+
+            quote! {
+                if !column_orders[#index].is_empty() {
+                    let permutation = #krate::__private::Permutation::with_offset(
+                        starts[#index],
+                        ::core::mem::take(&mut column_orders[#index]),
+                    );
+                    #(permutation.apply(&mut self.#columns);)*
+                }
+            }
+        });
+
+    quote! {
+        #[doc(hidden)]
+        pub fn apply_permutation(&mut self, permutation: &#krate::__private::Permutation) {
+            let mut column_orders: [#krate::__private::Vec<::core::primitive::usize>; #variant_count] =
+                ::core::array::from_fn(|_| #krate::__private::Vec::new());
+
+            for &row in permutation.order() {
+                column_orders[self.kinds[row] as ::core::primitive::usize].push(self.slots[row]);
+            }
+
+            permutation.apply(&mut self.kinds);
+
+            let starts: [::core::primitive::usize; #variant_count] = ::core::array::from_fn(|kind| {
+                column_orders[kind].iter().copied().min().unwrap_or(0)
+            });
+
+            let offset = permutation.offset();
+            let mut counts = starts;
+            for (kind, slot) in self.kinds[offset..].iter().zip(&mut self.slots[offset..]) {
+                if Self::has_columns(*kind) {
+                    *slot = counts[*kind as ::core::primitive::usize];
+                    counts[*kind as ::core::primitive::usize] += 1;
+                }
+            }
+
+            #(#permute_columns)*
+        }
+    }
+}
+
+fn slices(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let slices = &names.structs.slices;
+    let slices_mut = &names.structs.slices_mut;
+    let slices_type = names.structs.slices_type(false);
+    let slices_mut_type = names.structs.slices_type(true);
+    let slice = &names.structs.slice;
+    let slice_mut = &names.structs.slice_mut;
+    let slice_type = names.structs.slice_type(false);
+    let slice_mut_type = names.structs.slice_type(true);
+    let lifetime = &names.structs.lifetime;
+    let variant_count = variants.len();
+
+    let columns: Vec<_> = variants
+        .iter()
+        .flat_map(|variant| &variant.columns)
+        .map(|Column { name, .. }| name)
+        .collect();
+
+    quote! {
+        /// Borrow every column at once
+        #[inline]
+        pub fn as_slices<#lifetime>(& #lifetime self) -> #slices_type {
+            #slices {
+                kinds: &self.kinds,
+                #(#columns: &self.#columns,)*
+            }
+        }
+
+        /// Mutably borrow every column at once, so that different columns can be modified at the
+        /// same time
+        #[inline]
+        pub fn as_mut_slices<#lifetime>(& #lifetime mut self) -> #slices_mut_type {
+            #slices_mut {
+                kinds: &self.kinds,
+                #(#columns: &mut self.#columns,)*
+            }
+        }
+
+        /// Borrow every row as a view, which can be sliced, split and chunked like a `&[T]`
+        #[inline]
+        pub fn as_slice<#lifetime>(& #lifetime self) -> #slice_type {
+            #slice {
+                kinds: &self.kinds,
+                slots: &self.slots,
+                offsets: [0; #variant_count],
+                #(#columns: &self.#columns,)*
+            }
+        }
+
+        /// Mutably borrow every row as a view, which can be sliced, split and chunked like a
+        /// `&mut [T]`
+        #[inline]
+        pub fn as_mut_slice<#lifetime>(& #lifetime mut self) -> #slice_mut_type {
+            #slice_mut {
+                kinds: &self.kinds,
+                slots: &self.slots,
+                offsets: [0; #variant_count],
+                #(#columns: &mut self.#columns,)*
+            }
+        }
+    }
+}
+
+fn accessors(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let kind = &names.kind;
+
+    let columns = variants.iter().flat_map(|variant| &variant.columns).map(
+        |Column {
+             ty,
+             name,
+             accessor,
+             vis,
+             ..
+         }| {
+            let accessor_mut = format_ident!("{accessor}_mut");
+
+            quote! {
+                #vis fn #accessor(&self) -> &[#ty] {
+                    &self.#name
+                }
+
+                #vis fn #accessor_mut(&mut self) -> &mut [#ty] {
+                    &mut self.#name
+                }
+            }
+        },
+    );
+
+    quote! {
+        /// The kind of each row
+        #[inline]
+        pub fn kinds(&self) -> &[#kind] {
+            &self.kinds
+        }
+
+        #(#columns)*
+    }
+}
+
+/// Clone each field of the variant a `FooRef` points to, for `Extend<FooRef>`
+pub(super) fn ref_to_owned(names: &EnumNames, variants: &[VariantColumns]) -> RefToOwned {
+    let shared_ref = &names.structs.shared_ref;
+    let original = &names.structs.original;
+
+    let arms = variants.iter().map(|variant| {
+        let ident = variant.ident();
+        let pattern = variant.pattern();
+        let fields = variant.with_fields(variant.columns.iter().map(|column| {
+            let binding = &column.binding;
+            quote!(::core::clone::Clone::clone(#binding))
+        }));
+
+        quote!(#shared_ref::#ident #pattern => #original::#ident #fields)
+    });
+
+    let bounds = variants
+        .iter()
+        .flat_map(|variant| &variant.columns)
+        .map(|column| util::non_trivial_bound(column.ty, quote!(::core::clone::Clone)))
+        .collect();
+
+    RefToOwned {
+        expr: quote! {
+            match row {
+                #(#arms,)*
+            }
+        },
+        bounds,
+    }
+}
@@ -0,0 +1,209 @@
+//! Enums are stored as a "tagged" struct of vecs: a column of discriminants (the "kind" of each
+//! row), a column of "slots", and a set of columns for each variant's fields.
+//!
+//! The slot of a row is its index into the columns of its variant, or `0` for variants without
+//! any fields. The rows of each variant are stored in the same order in the variant's columns as
+//! they are in the overall vec, so that scanning one variant's columns visits its rows in order.
+use convert_case::{Case, Casing};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{ext::IdentExt, Ident, Type, Visibility};
+
+use crate::{
+    parse::{EnumInput, Fields, Variant},
+    util,
+};
+
+use super::{
+    conversions, drain, sort,
+    structs::{IterKind, StructNames},
+    traits,
+};
+
+mod derives;
+mod impl_block;
+mod types;
+mod views;
+
+pub fn codegen(input: EnumInput) -> TokenStream {
+    if input
+        .variants
+        .iter()
+        .all(|variant| variant.fields.iter().next().is_none())
+    {
+        let name = &input.name;
+        return quote::quote_spanned! { name.span() =>
+            compile_error!("enums without any fields are not supported, use a `Vec` instead");
+        };
+    }
+
+    let names = EnumNames::new(&input);
+    let variants: Vec<_> = input
+        .variants
+        .iter()
+        .map(|variant| VariantColumns::new(variant, &input.vis))
+        .collect();
+
+    let vis = &input.vis;
+    let mod_name = &names.structs.module;
+    let vec_name = &names.structs.vec;
+    let iter_name = &names.structs.iter;
+    let iter_mut_name = &names.structs.iter_mut;
+    let into_iter_name = &names.structs.into_iter;
+    let drain_name = &names.structs.drain;
+    let slice_name = &names.structs.slice;
+    let slice_mut_name = &names.structs.slice_mut;
+
+    let kind = types::kind(&names, &variants);
+    let ref_and_ref_mut = types::ref_and_ref_mut(&names, &variants);
+    let slices = types::slices(&names, &variants);
+    let vec = types::vec(&names, &variants);
+    let iters = types::iters(&names, &variants);
+    let views = views::definitions(&names, &variants);
+    let view_methods = super::views::codegen(&names.structs, views::primitives(&names, &variants));
+    let impl_block = impl_block::generate_impl_block(&names, &variants);
+    let traits = traits::codegen(&names.structs);
+    let derives = derives::codegen(&names, &variants, &input.config.derives);
+    let conversions =
+        conversions::codegen(&names.structs, impl_block::ref_to_owned(&names, &variants));
+    let sort = sort::codegen(&names.structs);
+    let drain = drain::codegen(&names.structs, &names.vis);
+
+    quote! {
+        #vis use #mod_name::{
+            #vec_name, #iter_name, #iter_mut_name, #into_iter_name, #drain_name, #slice_name,
+            #slice_mut_name,
+        };
+
+        #kind
+        #ref_and_ref_mut
+        #slices
+
+        #[doc(hidden)]
+        mod #mod_name {
+            use super::*;
+
+            #vec
+            #iters
+            #views
+            #view_methods
+            #impl_block
+            #traits
+            #derives
+            #conversions
+            #sort
+            #drain
+        }
+    }
+}
+
+struct EnumNames {
+    structs: StructNames,
+    /// The visibility of the types in the hidden module
+    vis: Visibility,
+    kind: Ident,
+    /// Whether the variants' columns should be visible outside of the `Vec`-like type
+    expose_columns: bool,
+}
+
+impl EnumNames {
+    fn new(input: &EnumInput) -> Self {
+        let name = &input.name;
+
+        Self {
+            structs: StructNames::new(name, &input.generics, &input.config),
+            vis: util::hidden_module_vis(&input.vis),
+            kind: input
+                .config
+                .kind
+                .clone()
+                .unwrap_or_else(|| format_ident!("{name}Kind")),
+            expose_columns: input.config.expose_columns,
+        }
+    }
+}
+
+/// A variant of the original enum, along with the names of the columns its fields are stored in
+struct VariantColumns<'a> {
+    variant: &'a Variant,
+    columns: Vec<Column<'a>>,
+}
+
+struct Column<'a> {
+    ty: &'a Type,
+    /// The name of the field in the `Vec`-like type, e.g. `click_x` for `Click { x }`
+    name: Ident,
+    /// The name this field is bound to when destructuring the variant, e.g. `x` or `field0`
+    binding: Ident,
+    accessor: Ident,
+    vis: Visibility,
+}
+
+impl<'a> VariantColumns<'a> {
+    fn new(variant: &'a Variant, vis: &Visibility) -> Self {
+        let prefix = variant.ident.unraw().to_string().to_case(Case::Snake);
+
+        let columns = variant
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                let binding = match &field.ident {
+                    Some(ident) => ident.clone(),
+                    None => util::nth_field(index),
+                };
+                let name = format_ident!("{prefix}_{}", binding.unraw());
+                let accessor = field
+                    .config
+                    .accessor
+                    .clone()
+                    .unwrap_or_else(|| util::pluralize(&name));
+                let vis = field
+                    .config
+                    .vis
+                    .clone()
+                    .unwrap_or_else(|| util::hidden_module_vis(vis));
+
+                Column {
+                    ty: &field.ty,
+                    name,
+                    binding,
+                    accessor,
+                    vis,
+                }
+            })
+            .collect();
+
+        Self { variant, columns }
+    }
+
+    fn ident(&self) -> &Ident {
+        &self.variant.ident
+    }
+
+    /// Put the given expressions in the shape of this variant's fields, i.e.
+    /// `{ x: #expr, y: #expr }`, `(#expr, #expr)` or nothing
+    fn with_fields(&self, exprs: impl IntoIterator<Item = TokenStream>) -> TokenStream {
+        let exprs = exprs.into_iter();
+
+        match &self.variant.fields {
+            Fields::Named(fields) => {
+                let names = fields.iter().map(|field| &field.ident);
+                quote!({ #(#names: #exprs,)* })
+            }
+            Fields::Unnamed(_) => quote!(( #(#exprs,)* )),
+            Fields::Unit => quote!(),
+        }
+    }
+
+    /// A pattern which binds each field of this variant to its `binding`
+    fn pattern(&self) -> TokenStream {
+        let bindings = self.columns.iter().map(|column| &column.binding);
+
+        match &self.variant.fields {
+            Fields::Named(_) => quote!({ #(#bindings,)* }),
+            Fields::Unnamed(_) => quote!(( #(#bindings,)* )),
+            Fields::Unit => quote!(),
+        }
+    }
+}
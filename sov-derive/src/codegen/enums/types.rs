@@ -0,0 +1,433 @@
+use super::*;
+
+/// A fieldless enum with the same variants as the original, used as the discriminant column
+pub(super) fn kind(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let kind = &names.kind;
+    let variants = variants.iter().map(VariantColumns::ident);
+
+    quote! {
+        #[derive(
+            ::core::clone::Clone,
+            ::core::marker::Copy,
+            ::core::fmt::Debug,
+            ::core::cmp::PartialEq,
+            ::core::cmp::Eq,
+            ::core::cmp::PartialOrd,
+            ::core::cmp::Ord,
+            ::core::hash::Hash,
+        )]
+        pub enum #kind {
+            #(#variants,)*
+        }
+    }
+}
+
+pub(super) fn ref_and_ref_mut(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let shared = ref_impl(names, variants, false);
+    let mutable = ref_impl(names, variants, true);
+    let methods = ref_methods(names, variants);
+
+    quote! {
+        #shared
+        #mutable
+        #methods
+    }
+}
+
+/// `FooRef` is `Copy`, like a `&Foo` would be, and `FooRefMut` can be reborrowed.
+///
+/// Unlike structs, `FooRefMut` can't overwrite the row it points to, since the new value might be
+/// a different variant
+fn ref_methods(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let shared_ref = &names.structs.shared_ref;
+    let mut_ref = &names.structs.mut_ref;
+    let generics = names.structs.ref_generics();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let reborrow_lifetime = util::fresh_lifetime(&generics, "b");
+    let shared_ref_type = names
+        .structs
+        .ref_type_with_lifetime(false, &reborrow_lifetime);
+    let mut_ref_type = names
+        .structs
+        .ref_type_with_lifetime(true, &reborrow_lifetime);
+
+    let arms = |target: &Ident, field: fn(&Ident) -> TokenStream| -> Vec<TokenStream> {
+        variants
+            .iter()
+            .map(|variant| {
+                let ident = variant.ident();
+                let pattern = variant.pattern();
+                let fields = variant
+                    .with_fields(variant.columns.iter().map(|column| field(&column.binding)));
+
+                quote!(Self::#ident #pattern => #target::#ident #fields)
+            })
+            .collect()
+    };
+    let as_ref = arms(shared_ref, |binding| quote!(&**#binding));
+    let reborrow = arms(mut_ref, |binding| quote!(&mut **#binding));
+
+    quote! {
+        impl #impl_generics ::core::clone::Clone for #shared_ref #ty_generics #where_clause {
+            #[inline]
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl #impl_generics ::core::marker::Copy for #shared_ref #ty_generics #where_clause {}
+
+        impl #impl_generics #mut_ref #ty_generics #where_clause {
+            /// Borrow the row immutably, for as long as this reference is borrowed
+            #[inline]
+            pub fn as_ref<#reborrow_lifetime>(& #reborrow_lifetime self) -> #shared_ref_type {
+                match self {
+                    #(#as_ref,)*
+                }
+            }
+
+            /// Borrow the row mutably for a shorter lifetime, so that this reference can be used
+            /// again afterwards
+            #[inline]
+            pub fn reborrow<#reborrow_lifetime>(& #reborrow_lifetime mut self) -> #mut_ref_type {
+                match self {
+                    #(#reborrow,)*
+                }
+            }
+        }
+    }
+}
+
+fn ref_impl(names: &EnumNames, variants: &[VariantColumns], mutable: bool) -> TokenStream {
+    let name = match mutable {
+        true => &names.structs.mut_ref,
+        false => &names.structs.shared_ref,
+    };
+
+    let maybe_mut = match mutable {
+        true => quote! { mut },
+        false => quote! {},
+    };
+
+    let lifetime = &names.structs.lifetime;
+    let generics = names.structs.ref_generics();
+    let where_clause = &generics.where_clause;
+
+    let variants = variants.iter().map(|variant| {
+        let ident = variant.ident();
+        let fields = variant.with_fields(variant.columns.iter().map(|column| {
+            let ty = column.ty;
+            quote!(& #lifetime #maybe_mut #ty)
+        }));
+
+        quote!(#ident #fields)
+    });
+
+    // the user didn't write these enums, so they shouldn't be warned about fields they never read
+    quote! {
+        #[allow(dead_code)]
+        pub enum #name #generics #where_clause {
+            #(#variants,)*
+        }
+    }
+}
+
+/// Structs holding a slice of every column. The kinds column is always shared, since changing it
+/// would desync it from the variants' columns
+pub(super) fn slices(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let shared = slices_impl(names, variants, false);
+    let mutable = slices_impl(names, variants, true);
+
+    quote! {
+        #shared
+        #mutable
+    }
+}
+
+fn slices_impl(names: &EnumNames, variants: &[VariantColumns], mutable: bool) -> TokenStream {
+    let name = match mutable {
+        true => &names.structs.slices_mut,
+        false => &names.structs.slices,
+    };
+
+    let maybe_mut = match mutable {
+        true => quote! { mut },
+        false => quote! {},
+    };
+
+    let kind = &names.kind;
+    let lifetime = &names.structs.lifetime;
+    let generics = names.structs.ref_generics();
+    let where_clause = &generics.where_clause;
+
+    let columns = variants
+        .iter()
+        .flat_map(|variant| &variant.columns)
+        .map(|Column { name, ty, .. }| quote!(pub #name: & #lifetime #maybe_mut [#ty]));
+
+    quote! {
+        pub struct #name #generics #where_clause {
+            pub kinds: & #lifetime [#kind],
+            #(#columns,)*
+        }
+    }
+}
+
+pub(super) fn vec(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let name = &names.structs.vec;
+    let vis = &names.vis;
+    let kind = &names.kind;
+    let krate = &names.structs.krate;
+    let generics = &names.structs.generics;
+    let where_clause = &generics.where_clause;
+
+    // the kinds and slots are never exposed, since they're only meaningful together
+    let columns = variants.iter().flat_map(|variant| &variant.columns).map(
+        |Column { name, ty, vis, .. }| {
+            let vis = match names.expose_columns {
+                true => Some(vis),
+                false => None,
+            };
+
+            quote!(#vis #name: #krate::__private::Vec<#ty>)
+        },
+    );
+
+    quote! {
+        #vis struct #name #generics #where_clause {
+            kinds: #krate::__private::Vec<#kind>,
+            slots: #krate::__private::Vec<::core::primitive::usize>,
+            #(#columns,)*
+        }
+    }
+}
+
+/// Iterators over the rows of the `Vec`-like type.
+///
+/// The rows of each variant are stored in order, so these walk the kinds column, and take the next
+/// value from an iterator over each of the row's variant's columns
+pub(super) fn iters(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let iters = IterKind::ALL.map(|kind| {
+        let definition = define_iter(names, variants, kind);
+        let iterator = iterator_impls(names, variants, kind);
+        let into_iterator = into_iterator_impl(names, variants, kind);
+
+        quote! {
+            #definition
+            #iterator
+            #into_iterator
+        }
+    });
+
+    quote!(#(#iters)*)
+}
+
+fn define_iter(names: &EnumNames, variants: &[VariantColumns], kind: IterKind) -> TokenStream {
+    let name = names.structs.iter_name(kind);
+    let vis = &names.vis;
+    let lifetime = &names.structs.lifetime;
+    let kinds_type = kinds_iter_type(names, kind);
+    let generics = names.structs.iter_generics(kind);
+    let where_clause = &generics.where_clause;
+
+    let columns =
+        variants
+            .iter()
+            .flat_map(|variant| &variant.columns)
+            .map(|Column { name, ty, .. }| {
+                let ty = kind.column_iter_type(ty, lifetime, &names.structs.krate);
+                quote!(#name: #ty)
+            });
+
+    quote! {
+        #vis struct #name #generics #where_clause {
+            kinds: #kinds_type,
+            #(#columns,)*
+        }
+    }
+}
+
+fn kinds_iter_type(names: &EnumNames, kind: IterKind) -> TokenStream {
+    let kind_name = &names.kind;
+    let krate = &names.structs.krate;
+    let lifetime = &names.structs.lifetime;
+
+    match kind {
+        IterKind::Shared | IterKind::Mutable => {
+            quote!(::core::iter::Copied<::core::slice::Iter<#lifetime, #kind_name>>)
+        }
+        IterKind::Owned => quote!(#krate::__private::VecIntoIter<#kind_name>),
+    }
+}
+
+/// Create an iterator from `self`, which is `&self`, `&mut self` or `self` depending on `kind`
+pub(super) fn construct_iter(
+    names: &EnumNames,
+    variants: &[VariantColumns],
+    kind: IterKind,
+) -> TokenStream {
+    let name = names.structs.iter_name(kind);
+    let fn_name = kind.column_iter_fn();
+    let columns = variants
+        .iter()
+        .flat_map(|variant| &variant.columns)
+        .map(|Column { name, .. }| name);
+
+    let kinds = match kind {
+        IterKind::Shared | IterKind::Mutable => quote!(self.kinds.iter().copied()),
+        IterKind::Owned => quote!(self.kinds.into_iter()),
+    };
+
+    quote! {
+        #name {
+            kinds: #kinds,
+            #(#columns: self.#columns.#fn_name(),)*
+        }
+    }
+}
+
+fn iterator_impls(names: &EnumNames, variants: &[VariantColumns], kind: IterKind) -> TokenStream {
+    let iter_type = names.structs.iter_type(kind);
+    let item_type = names.structs.iter_item_type(kind);
+    let generics = names.structs.iter_generics(kind);
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let kind_name = &names.kind;
+
+    let item_name = match kind {
+        IterKind::Shared => &names.structs.shared_ref,
+        IterKind::Mutable => &names.structs.mut_ref,
+        IterKind::Owned => &names.structs.original,
+    };
+
+    // take the next value from the front or back of each of the variant's columns
+    let rows = |method: TokenStream| {
+        let arms = variants.iter().map(|variant| {
+            let ident = variant.ident();
+            let fields = variant.with_fields(
+                variant
+                    .columns
+                    .iter()
+                    .map(|Column { name, .. }| quote!(self.#name.#method?)),
+            );
+
+            quote!(#kind_name::#ident => #item_name::#ident #fields)
+        });
+
+        quote!(#(#arms,)*)
+    };
+
+    // skip a value in each of the variant's columns, without building the row
+    let skips = |method: TokenStream| {
+        let arms = variants.iter().map(|variant| {
+            let ident = variant.ident();
+            let skips = variant
+                .columns
+                .iter()
+                .map(|Column { name, .. }| quote!(self.#name.#method;));
+
+            quote!(#kind_name::#ident => { #(#skips)* })
+        });
+
+        quote!(#(#arms,)*)
+    };
+
+    let next_rows = rows(quote!(next()));
+    let next_back_rows = rows(quote!(next_back()));
+    let next_skips = skips(quote!(next()));
+    let next_back_skips = skips(quote!(next_back()));
+
+    quote! {
+        impl #impl_generics ::core::iter::Iterator for #iter_type #where_clause {
+            type Item = #item_type;
+
+            #[inline]
+            fn next(&mut self) -> ::core::option::Option<Self::Item> {
+                ::core::option::Option::Some(match self.kinds.next()? {
+                    #next_rows
+                })
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (::core::primitive::usize, ::core::option::Option<::core::primitive::usize>) {
+                self.kinds.size_hint()
+            }
+
+            #[inline]
+            fn nth(&mut self, n: ::core::primitive::usize) -> ::core::option::Option<Self::Item> {
+                for _ in 0..n {
+                    match self.kinds.next()? {
+                        #next_skips
+                    }
+                }
+
+                self.next()
+            }
+        }
+
+        impl #impl_generics ::core::iter::DoubleEndedIterator for #iter_type #where_clause {
+            #[inline]
+            fn next_back(&mut self) -> ::core::option::Option<Self::Item> {
+                ::core::option::Option::Some(match self.kinds.next_back()? {
+                    #next_back_rows
+                })
+            }
+
+            #[inline]
+            fn nth_back(&mut self, n: ::core::primitive::usize) -> ::core::option::Option<Self::Item> {
+                for _ in 0..n {
+                    match self.kinds.next_back()? {
+                        #next_back_skips
+                    }
+                }
+
+                self.next_back()
+            }
+        }
+
+        impl #impl_generics ::core::iter::ExactSizeIterator for #iter_type #where_clause {}
+
+        impl #impl_generics ::core::iter::FusedIterator for #iter_type #where_clause {}
+    }
+}
+
+fn into_iterator_impl(
+    names: &EnumNames,
+    variants: &[VariantColumns],
+    kind: IterKind,
+) -> TokenStream {
+    let vec = &names.structs.vec;
+    let lifetime = &names.structs.lifetime;
+    let iter_type = names.structs.iter_type(kind);
+    let item_type = names.structs.iter_item_type(kind);
+    let generics = names.structs.iter_generics(kind);
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, vec_ty_generics, _) = names.structs.generics.split_for_impl();
+
+    let (self_type, body) = match kind {
+        IterKind::Shared => (
+            quote!(& #lifetime #vec #vec_ty_generics),
+            quote!(self.iter()),
+        ),
+        IterKind::Mutable => (
+            quote!(& #lifetime mut #vec #vec_ty_generics),
+            quote!(self.iter_mut()),
+        ),
+        IterKind::Owned => (
+            quote!(#vec #vec_ty_generics),
+            construct_iter(names, variants, kind),
+        ),
+    };
+
+    quote! {
+        impl #impl_generics ::core::iter::IntoIterator for #self_type #where_clause {
+            type Item = #item_type;
+            type IntoIter = #iter_type;
+
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                #body
+            }
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! The slice views of enums. Each view holds the kinds and slots of the rows in its range, along
+//! with the part of each variant's columns that holds those rows.
+//!
+//! The slots are still relative to the start of the whole vec, so the view also stores the slot of
+//! its first row of each variant. Splitting a view needs to know how many rows of each variant are
+//! before the split, so unlike slices of structs, it's `O(n)`
+use super::*;
+use crate::codegen::views::ViewPrimitives;
+
+pub(super) fn definitions(names: &EnumNames, variants: &[VariantColumns]) -> TokenStream {
+    let shared = definition(names, variants, false);
+    let mutable = definition(names, variants, true);
+
+    quote! {
+        #shared
+        #mutable
+    }
+}
+
+fn definition(names: &EnumNames, variants: &[VariantColumns], mutable: bool) -> TokenStream {
+    let name = match mutable {
+        true => &names.structs.slice_mut,
+        false => &names.structs.slice,
+    };
+
+    let maybe_mut = match mutable {
+        true => quote! { mut },
+        false => quote! {},
+    };
+
+    let vis = &names.vis;
+    let kind = &names.kind;
+    let lifetime = &names.structs.lifetime;
+    let generics = names.structs.ref_generics();
+    let where_clause = &generics.where_clause;
+    let variant_count = variants.len();
+
+    let columns = variants
+        .iter()
+        .flat_map(|variant| &variant.columns)
+        .map(|Column { name, ty, .. }| quote!(#name: & #lifetime #maybe_mut [#ty]));
+
+    // `offsets` is the slot of the view's first row of each variant, indexed by kind. It's only
+    // kept up to date for variants with fields, since it's only needed to index into columns
+    quote! {
+        #vis struct #name #generics #where_clause {
+            kinds: & #lifetime [#kind],
+            slots: & #lifetime [::core::primitive::usize],
+            offsets: [::core::primitive::usize; #variant_count],
+            #(#columns,)*
+        }
+    }
+}
+
+pub(super) fn primitives(names: &EnumNames, variants: &[VariantColumns]) -> ViewPrimitives {
+    let kind = &names.kind;
+    let slice = &names.structs.slice;
+    let slice_mut = &names.structs.slice_mut;
+
+    let columns: Vec<_> = variants
+        .iter()
+        .flat_map(|variant| &variant.columns)
+        .map(|Column { name, .. }| name)
+        .collect();
+
+    // only the columns change between the views, the kinds column is always shared
+    let build = |name: &Ident, column: fn(&Ident) -> TokenStream| {
+        let values = columns.iter().map(|name| column(name));
+
+        quote! {
+            #name {
+                kinds: self.kinds,
+                slots: self.slots,
+                offsets: self.offsets,
+                #(#columns: #values,)*
+            }
+        }
+    };
+
+    let get = |mutable: bool| {
+        let name = match mutable {
+            true => &names.structs.mut_ref,
+            false => &names.structs.shared_ref,
+        };
+
+        let maybe_mut = match mutable {
+            true => quote!(mut),
+            false => quote!(),
+        };
+
+        let arms = variants.iter().enumerate().map(|(index, variant)| {
+            let ident = variant.ident();
+            let fields = variant.with_fields(
+                variant
+                    .columns
+                    .iter()
+                    .map(|Column { name, .. }| quote!(& #maybe_mut self.#name[slot])),
+            );
+
+            match variant.columns.is_empty() {
+                true => quote!(#kind::#ident => #name::#ident #fields),
+                false => quote! {
+                    #kind::#ident => {
+                        let slot = slot - self.offsets[#index];
+                        #name::#ident #fields
+                    }
+                },
+            }
+        });
+
+        quote!({
+            let slot = self.slots[index];
+            match self.kinds[index] {
+                #(#arms,)*
+            }
+        })
+    };
+
+    let split = |name: &Ident, mutable: bool| {
+        let variant_count = variants.len();
+        let fn_name = match mutable {
+            true => quote!(split_at_mut),
+            false => quote!(split_at),
+        };
+
+        // the number of rows before `mid` can be worked out from the number after it, using the
+        // length of any of the variant's columns
+        let with_columns: Vec<_> = variants
+            .iter()
+            .enumerate()
+            .filter_map(|(index, variant)| Some((index, &variant.columns.first()?.name)))
+            .collect();
+        let counts_from_rest = with_columns
+            .iter()
+            .map(|(index, first)| quote!(counts[#index] = self.#first.len() - counts[#index];));
+        let update_offsets = with_columns
+            .iter()
+            .map(|(index, _)| quote!(offsets[#index] += counts[#index];));
+
+        // the halves are bound to `field0`, `field1`, etc. so they can't shadow the locals
+        let split_columns = variants.iter().enumerate().flat_map(|(index, variant)| {
+            let fn_name = &fn_name;
+
+            variant
+                .columns
+                .iter()
+                .map(move |Column { name, .. }| quote!(self.#name.#fn_name(counts[#index])))
+        });
+        let bindings: Vec<_> = (0..columns.len()).map(util::nth_field).collect();
+
+        quote!({
+            let (kinds, rest_kinds) = self.kinds.split_at(mid);
+            let (slots, rest_slots) = self.slots.split_at(mid);
+
+            // count the rows of each variant in whichever half is shorter
+            let mut counts = [0; #variant_count];
+            let count_before = kinds.len() <= rest_kinds.len();
+            let counted = match count_before {
+                true => kinds,
+                false => rest_kinds,
+            };
+
+            for kind in counted {
+                counts[*kind as ::core::primitive::usize] += 1;
+            }
+
+            if !count_before {
+                #(#counts_from_rest)*
+            }
+
+            let mut offsets = self.offsets;
+            #(#update_offsets)*
+            #(let #bindings = #split_columns;)*
+
+            (
+                #name {
+                    kinds,
+                    slots,
+                    offsets: self.offsets,
+                    #(#columns: #bindings.0,)*
+                },
+                #name {
+                    kinds: rest_kinds,
+                    slots: rest_slots,
+                    offsets,
+                    #(#columns: #bindings.1,)*
+                },
+            )
+        })
+    };
+
+    let into_iter = |kind: IterKind| {
+        let iter = names.structs.iter_name(kind);
+
+        quote! {
+            #iter {
+                kinds: self.kinds.iter().copied(),
+                #(#columns: ::core::iter::IntoIterator::into_iter(self.#columns),)*
+            }
+        }
+    };
+
+    let slices = &names.structs.slices;
+    let slices_mut = &names.structs.slices_mut;
+
+    ViewPrimitives {
+        len: quote!(self.kinds.len()),
+        get: get(false),
+        get_mut: get(true),
+        as_slice: build(slice, |name| quote!(&*self.#name)),
+        reborrow: build(slice_mut, |name| quote!(&mut *self.#name)),
+        split_at: split(slice, false),
+        split_at_mut: split(slice_mut, true),
+        into_iter: into_iter(IterKind::Shared),
+        into_iter_mut: into_iter(IterKind::Mutable),
+        as_slices: quote! {
+            #slices {
+                kinds: self.kinds,
+                #(#columns: self.#columns,)*
+            }
+        },
+        as_mut_slices: quote! {
+            #slices_mut {
+                kinds: self.kinds,
+                #(#columns: &mut *self.#columns,)*
+            }
+        },
+    }
+}
@@ -0,0 +1,370 @@
+use super::*;
+
+pub(super) fn codegen(input: &Input, struct_names: &StructNames) -> TokenStream {
+    let (new, with_capacity) = match &struct_names.allocator {
+        Some(allocator) => (
+            gen_new_in(input, struct_names, allocator),
+            gen_with_capacity_in(input, struct_names),
+        ),
+        None => (
+            gen_new(input, struct_names),
+            gen_with_capacity(input, struct_names),
+        ),
+    };
+    let capacity = gen_capacity(input, struct_names);
+    let resize = gen_resize(input, struct_names);
+
+    quote! {
+        #new
+        #with_capacity
+        #capacity
+        #resize
+    }
+}
+
+fn gen_new(input: &Input, struct_names: &StructNames) -> TokenStream {
+    // the columns start without any memory, so the allocation is empty too
+    if let Some(allocation) = input.allocation_member() {
+        let members = input.fields.members();
+        let column_types = input
+            .fields
+            .iter()
+            .map(|field| struct_names.column_type(field));
+        let allocation_type = struct_names.allocation_type();
+
+        return quote! {
+            #[inline]
+            pub fn new() -> Self {
+                Self {
+                    #(#members: <#column_types>::new(),)*
+                    #allocation: <#allocation_type>::new(),
+                }
+            }
+        };
+    }
+
+    let fields = input.map_fields_with_delimiters(
+        |field| {
+            let name = &field.ident.as_ref().unwrap();
+            let column_path = struct_names.column_path(field);
+            quote! { #name: #column_path::new()}
+        },
+        |field, _| {
+            let column_path = struct_names.column_path(field);
+            quote! {#column_path::new()}
+        },
+    );
+
+    quote! {
+        #[inline]
+        pub fn new() -> Self {
+            Self #fields
+        }
+    }
+}
+
+fn gen_with_capacity(input: &Input, struct_names: &StructNames) -> TokenStream {
+    if input.single_allocation() {
+        return quote! {
+            #[inline]
+            pub fn with_capacity(capacity: ::core::primitive::usize) -> Self {
+                let mut vec = Self::new();
+                vec.reserve_exact(capacity);
+                vec
+            }
+        };
+    }
+
+    let fields = input.map_fields_with_delimiters(
+        |field| {
+            let name = &field.ident.as_ref().unwrap();
+            let column_path = struct_names.column_path(field);
+            quote! { #name: #column_path::with_capacity(capacity)}
+        },
+        |field, _| {
+            let column_path = struct_names.column_path(field);
+            quote! {#column_path::with_capacity(capacity)}
+        },
+    );
+
+    quote! {
+        #[inline]
+        pub fn with_capacity(capacity: ::core::primitive::usize) -> Self {
+            Self #fields
+        }
+    }
+}
+
+/// With `#[sov(allocator)]`, `new` and `with_capacity` are only available for the global allocator,
+/// in their own impl block, like they are for `Vec`
+pub(super) fn global_constructors(input: &Input, struct_names: &StructNames) -> TokenStream {
+    if struct_names.allocator.is_none() {
+        return TokenStream::new();
+    }
+
+    let krate = &struct_names.krate;
+    let global_vec_type = struct_names.global_vec_type();
+    let (impl_generics, _, where_clause) = input.generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #global_vec_type #where_clause {
+            #[inline]
+            pub fn new() -> Self {
+                Self::new_in(#krate::__private::allocator_api2::alloc::Global)
+            }
+
+            #[inline]
+            pub fn with_capacity(capacity: ::core::primitive::usize) -> Self {
+                Self::with_capacity_in(capacity, #krate::__private::allocator_api2::alloc::Global)
+            }
+        }
+    }
+}
+
+/// `new_in`, which creates every column in the same allocator, and `allocator`
+fn gen_new_in(input: &Input, struct_names: &StructNames, allocator: &Ident) -> TokenStream {
+    let members = input.fields.members();
+    let column_types = input
+        .fields
+        .iter()
+        .map(|field| struct_names.column_type(field));
+    let first = &members[0];
+
+    quote! {
+        /// Create an empty vec whose columns are all allocated in `alloc`
+        #[inline]
+        pub fn new_in(alloc: #allocator) -> Self {
+            Self {
+                #(#members: <#column_types>::new_in(::core::clone::Clone::clone(&alloc)),)*
+            }
+        }
+
+        /// The allocator that every column is allocated in
+        #[inline]
+        pub fn allocator(&self) -> &#allocator {
+            self.#first.allocator()
+        }
+    }
+}
+
+/// `with_capacity_in`, which creates every column in the same allocator
+fn gen_with_capacity_in(input: &Input, struct_names: &StructNames) -> TokenStream {
+    let members = input.fields.members();
+    let column_types = input
+        .fields
+        .iter()
+        .map(|field| struct_names.column_type(field));
+    let allocator = &struct_names.allocator;
+
+    quote! {
+        /// Create an empty vec with space for at least `capacity` rows, whose columns are all
+        /// allocated in `alloc`
+        #[inline]
+        pub fn with_capacity_in(capacity: ::core::primitive::usize, alloc: #allocator) -> Self {
+            Self {
+                #(#members: <#column_types>::with_capacity_in(capacity, ::core::clone::Clone::clone(&alloc)),)*
+            }
+        }
+    }
+}
+
+/// `capacity` and the methods that change it, which apply the same change to every column
+fn gen_capacity(input: &Input, struct_names: &StructNames) -> TokenStream {
+    // calls `method` on every column
+    let each_column = |method: &str, args: TokenStream| {
+        input
+            .fields
+            .iter()
+            .zip(input.fields.members())
+            .map(|(field, member)| {
+                struct_names.column_call(field, quote!(self.#member), method, args.clone())
+            })
+            .collect::<Vec<_>>()
+    };
+    let reserve = each_column("reserve", quote!(additional));
+    let reserve_exact = each_column("reserve_exact", quote!(additional));
+    let shrink_to_fit = each_column("shrink_to_fit", quote!());
+    let shrink_to = each_column("shrink_to", quote!(min_capacity));
+    let capacities = each_column("capacity", quote!());
+    let truncate = each_column("truncate", quote!(len));
+    let clear = each_column("clear", quote!());
+    let resize_columns = match input.allocation_member() {
+        Some(allocation) => gen_set_capacity(input, struct_names, allocation),
+        None => quote! {
+            /// Reserve space for at least `additional` more rows in every column
+            #[inline]
+            pub fn reserve(&mut self, additional: ::core::primitive::usize) {
+                #(#reserve;)*
+            }
+
+            /// Reserve space for exactly `additional` more rows in every column, without
+            /// over-allocating to avoid future reallocations
+            #[inline]
+            pub fn reserve_exact(&mut self, additional: ::core::primitive::usize) {
+                #(#reserve_exact;)*
+            }
+
+            /// Shrink the capacity of every column as much as possible
+            #[inline]
+            pub fn shrink_to_fit(&mut self) {
+                #(#shrink_to_fit;)*
+            }
+
+            /// Shrink the capacity of every column to at least `min_capacity` and the number of rows
+            #[inline]
+            pub fn shrink_to(&mut self, min_capacity: ::core::primitive::usize) {
+                #(#shrink_to;)*
+            }
+        },
+    };
+
+    quote! {
+        /// The number of rows the vec can hold without reallocating, which is the smallest
+        /// capacity of any column
+        #[inline]
+        pub fn capacity(&self) -> ::core::primitive::usize {
+            let capacity = ::core::primitive::usize::MAX;
+            #(let capacity = ::core::cmp::min(capacity, #capacities);)*
+            capacity
+        }
+
+        #resize_columns
+
+        /// Remove every row after the first `len`, doing nothing if there are fewer rows
+        #[inline]
+        pub fn truncate(&mut self, len: ::core::primitive::usize) {
+            #(#truncate;)*
+        }
+
+        /// Remove every row, keeping the capacity of every column
+        #[inline]
+        pub fn clear(&mut self) {
+            #(#clear;)*
+        }
+    }
+}
+
+/// With `#[sov(layout = "single")]`, the columns can't grow by themselves. Instead, the methods
+/// that change the capacity move every column into a new allocation at once
+fn gen_set_capacity(input: &Input, struct_names: &StructNames, allocation: Member) -> TokenStream {
+    let krate = &struct_names.krate;
+    let members = input.fields.members();
+
+    quote! {
+        /// Reserve space for at least `additional` more rows, growing every column at once
+        #[inline]
+        pub fn reserve(&mut self, additional: ::core::primitive::usize) {
+            let required = self.len().checked_add(additional).expect("capacity overflow");
+
+            if required > self.capacity() {
+                // grow exponentially, like a `Vec`, so that pushing is amortized `O(1)`
+                let doubled = self.capacity().saturating_mul(2);
+                self.set_capacity(::core::cmp::max(::core::cmp::max(required, doubled), 4));
+            }
+        }
+
+        /// Reserve space for exactly `additional` more rows, growing every column at once
+        #[inline]
+        pub fn reserve_exact(&mut self, additional: ::core::primitive::usize) {
+            let required = self.len().checked_add(additional).expect("capacity overflow");
+
+            if required > self.capacity() {
+                self.set_capacity(required);
+            }
+        }
+
+        /// Shrink the capacity of every column as much as possible
+        #[inline]
+        pub fn shrink_to_fit(&mut self) {
+            if self.capacity() > self.len() {
+                self.set_capacity(self.len());
+            }
+        }
+
+        /// Shrink the capacity of every column to at least `min_capacity` and the number of rows
+        #[inline]
+        pub fn shrink_to(&mut self, min_capacity: ::core::primitive::usize) {
+            let capacity = ::core::cmp::max(self.len(), min_capacity);
+
+            if self.capacity() > capacity {
+                self.set_capacity(capacity);
+            }
+        }
+
+        /// Move every column into a new allocation with space for exactly `capacity` rows
+        fn set_capacity(&mut self, capacity: ::core::primitive::usize) {
+            // SAFETY: these are all of the columns, which are only ever stored in this allocation,
+            // and always in this order
+            unsafe {
+                self.#allocation.set_capacity(
+                    &mut [#(&mut self.#members as &mut dyn #krate::__private::RawColumn),*],
+                    capacity,
+                );
+            }
+        }
+    }
+}
+
+/// `resize` splits the value into its fields and resizes each column separately, so it needs each
+/// field to be `Clone` rather than the whole struct (and the nested type's fields, for flattened
+/// fields)
+fn gen_resize(input: &Input, struct_names: &StructNames) -> TokenStream {
+    let original = &struct_names.original;
+    let original_type = struct_names.original_type();
+    let members = input.fields.members();
+    let bindings: Vec<_> = (0..members.len()).map(util::nth_field).collect();
+    let resize =
+        input
+            .fields
+            .iter()
+            .zip(&members)
+            .zip(&bindings)
+            .map(|((field, member), binding)| {
+                struct_names.column_call(
+                    field,
+                    quote!(self.#member),
+                    "resize",
+                    quote!(new_len, #binding),
+                )
+            });
+    let bounds = input.fields.iter().flat_map(|field| {
+        let mut bounds = util::nested_bounds(&input.generics, field, quote!(::core::clone::Clone));
+        bounds.push(util::non_trivial_bound(
+            &field.ty,
+            quote!(::core::clone::Clone),
+        ));
+        bounds
+    });
+    // the columns of a single allocation can only grow into space that is already reserved
+    let reserve = input
+        .single_allocation()
+        .then(|| quote!(self.reserve(new_len.saturating_sub(self.len()));));
+
+    quote! {
+        /// Resize the vec to `new_len` rows, either by removing rows from the end or by adding
+        /// clones of `value`
+        pub fn resize(&mut self, new_len: ::core::primitive::usize, value: #original_type)
+        where
+            #(#bounds,)*
+        {
+            #reserve
+            let #original { #(#members: #bindings,)* } = value;
+            #(#resize;)*
+        }
+
+        /// Resize the vec to `new_len` rows, either by removing rows from the end or by adding
+        /// rows returned by `f`
+        pub fn resize_with<F>(&mut self, new_len: ::core::primitive::usize, f: F)
+        where
+            F: ::core::ops::FnMut() -> #original_type,
+        {
+            match new_len.checked_sub(self.len()) {
+                ::core::option::Option::Some(additional) => ::core::iter::Extend::extend(
+                    self,
+                    ::core::iter::repeat_with(f).take(additional),
+                ),
+                ::core::option::Option::None => self.truncate(new_len),
+            }
+        }
+    }
+}
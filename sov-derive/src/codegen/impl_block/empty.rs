@@ -0,0 +1,166 @@
+//! Structs without any fields have no columns, so the number of rows is stored in a counter
+//! instead, and each row is created from scratch when it's removed
+use super::*;
+
+pub(super) fn codegen(input: &Input, struct_names: &StructNames) -> TokenStream {
+    let original = &struct_names.original;
+    let original_type = struct_names.original_type();
+    let lifetime = &struct_names.lifetime;
+    let shared_ref = &struct_names.shared_ref;
+    let mut_ref = &struct_names.mut_ref;
+    let shared_ref_type = struct_names.ref_type(false);
+    let mut_ref_type = struct_names.ref_type(true);
+    let iter = &struct_names.iter;
+    let iter_mut = &struct_names.iter_mut;
+    let iter_type = struct_names.iter_type(IterKind::Shared);
+    let iter_mut_type = struct_names.iter_type(IterKind::Mutable);
+
+    // `{}`, `()` or nothing, depending on the kind of struct
+    let fields = input.map_fields_with_delimiters(|_| quote!(), |_, _| quote!());
+    let value = quote!(#original #fields);
+
+    quote! {
+        #[inline]
+        pub fn new() -> Self {
+            Self { len: 0 }
+        }
+
+        #[inline]
+        pub fn with_capacity(_capacity: ::core::primitive::usize) -> Self {
+            Self::new()
+        }
+
+        /// Rows without any fields don't take up any space, so the capacity is unlimited
+        #[inline]
+        pub fn capacity(&self) -> ::core::primitive::usize {
+            ::core::primitive::usize::MAX
+        }
+
+        #[inline]
+        pub fn reserve(&mut self, _additional: ::core::primitive::usize) {}
+
+        #[inline]
+        pub fn reserve_exact(&mut self, _additional: ::core::primitive::usize) {}
+
+        #[inline]
+        pub fn shrink_to_fit(&mut self) {}
+
+        #[inline]
+        pub fn shrink_to(&mut self, _min_capacity: ::core::primitive::usize) {}
+
+        #[inline]
+        pub fn truncate(&mut self, len: ::core::primitive::usize) {
+            self.len = ::core::cmp::min(self.len, len);
+        }
+
+        #[inline]
+        pub fn clear(&mut self) {
+            self.len = 0;
+        }
+
+        #[inline]
+        pub fn resize(&mut self, new_len: ::core::primitive::usize, _value: #original_type) {
+            self.len = new_len;
+        }
+
+        #[inline]
+        pub fn resize_with<F>(&mut self, new_len: ::core::primitive::usize, f: F)
+        where
+            F: ::core::ops::FnMut() -> #original_type,
+        {
+            match new_len.checked_sub(self.len) {
+                ::core::option::Option::Some(additional) => {
+                    ::core::iter::Iterator::for_each(
+                        ::core::iter::repeat_with(f).take(additional),
+                        |value| self.push(value),
+                    );
+                }
+                ::core::option::Option::None => self.truncate(new_len),
+            }
+        }
+
+        #[inline]
+        pub fn get<#lifetime>(& #lifetime self, index: ::core::primitive::usize) -> #shared_ref_type {
+            self.check_index(index);
+            #shared_ref { _marker: ::core::marker::PhantomData }
+        }
+
+        #[inline]
+        pub fn get_mut<#lifetime>(& #lifetime mut self, index: ::core::primitive::usize) -> #mut_ref_type {
+            self.check_index(index);
+            #mut_ref { _marker: ::core::marker::PhantomData }
+        }
+
+        #[inline]
+        pub fn iter<#lifetime>(& #lifetime self) -> #iter_type {
+            #iter {
+                range: 0..self.len,
+                _marker: ::core::marker::PhantomData,
+            }
+        }
+
+        #[inline]
+        pub fn iter_mut<#lifetime>(& #lifetime mut self) -> #iter_mut_type {
+            #iter_mut {
+                range: 0..self.len,
+                _marker: ::core::marker::PhantomData,
+            }
+        }
+
+        #[inline]
+        pub fn push(&mut self, _value: #original_type) {
+            self.len += 1;
+        }
+
+        #[inline]
+        pub fn insert(&mut self, index: usize, _value: #original_type) {
+            assert!(
+                index <= self.len,
+                "insertion index (is {index}) should be <= len (is {})",
+                self.len,
+            );
+            self.len += 1;
+        }
+
+        #[inline]
+        pub fn len(&self) -> usize {
+            self.len
+        }
+
+        #[inline]
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        #[inline]
+        pub fn remove(&mut self, index: usize) -> #original_type {
+            self.check_index(index);
+            self.len -= 1;
+            #value
+        }
+
+        #[inline]
+        pub fn swap_remove(&mut self, index: usize) -> #original_type {
+            self.remove(index)
+        }
+
+        #[inline]
+        pub fn pop(&mut self) -> Option<#original_type> {
+            if self.len == 0 {
+                return None;
+            }
+
+            self.len -= 1;
+            Some(#value)
+        }
+
+        #[inline]
+        fn check_index(&self, index: usize) {
+            assert!(
+                index < self.len,
+                "index out of bounds: the len is {} but the index is {index}",
+                self.len,
+            );
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use super::*;
+
+pub(super) fn codegen(input: &Input, struct_names: &StructNames) -> TokenStream {
+    let get = get(input, struct_names);
+    let field_accessors = field_accessors(input, struct_names);
+
+    quote! {
+        #get
+        #field_accessors
+    }
+}
+
+fn get(input: &Input, struct_names: &StructNames) -> TokenStream {
+    fn get_impl(input: &Input, struct_names: &StructNames, mutable: bool) -> TokenStream {
+        let name = match mutable {
+            true => &struct_names.mut_ref,
+            false => &struct_names.shared_ref,
+        };
+        let ref_type = struct_names.ref_type(mutable);
+        let lifetime = &struct_names.lifetime;
+
+        let fn_name = match mutable {
+            true => quote!(get_mut),
+            false => quote!(get),
+        };
+
+        let maybe_mut = match mutable {
+            true => quote!(mut),
+            false => quote!(),
+        };
+
+        let fields = input.map_fields_with_delimiters(
+            |field| {
+                let name = &field.ident.as_ref().unwrap();
+
+                match field.config.flatten {
+                    true => quote! { #name: self.#name.#fn_name(index) },
+                    false => {
+                        let column = struct_names.column_slice(field, quote!(self.#name), mutable);
+                        quote! { #name: & #maybe_mut #column[index] }
+                    }
+                }
+            },
+            |field, field_index| {
+                let field_index = Index::from(field_index);
+
+                match field.config.flatten {
+                    true => quote! { self.#field_index.#fn_name(index) },
+                    false => {
+                        let column =
+                            struct_names.column_slice(field, quote!(self.#field_index), mutable);
+                        quote! { & #maybe_mut #column[index] }
+                    }
+                }
+            },
+        );
+        quote! {
+            #[inline]
+            pub fn #fn_name<#lifetime>(& #lifetime #maybe_mut self, index: ::core::primitive::usize) -> #ref_type {
+                #name #fields
+            }
+        }
+    }
+
+    let shared = get_impl(input, struct_names, false);
+    let mutable = get_impl(input, struct_names, true);
+
+    quote! {
+        #shared
+        #mutable
+    }
+}
+
+fn field_accessors(input: &Input, struct_names: &StructNames) -> TokenStream {
+    let accessors = input
+        .fields
+        .iter()
+        .zip(input.fields.members())
+        .zip(accessor_names(input))
+        .map(|((field, member), names)| field_accessor(field, struct_names, member, names));
+
+    quote!(#(#accessors)*)
+}
+
+/// The visibility and names of the accessors for the column of every field, e.g. `xs()` and
+/// `xs_mut()` for a field `x`, or `field0s()` and `field0s_mut()` for the first field of a tuple
+/// struct
+pub(in crate::codegen) fn accessor_names(input: &Input) -> Vec<(Visibility, Ident, Ident)> {
+    input
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let vis = field
+                .config
+                .vis
+                .clone()
+                .unwrap_or_else(|| util::hidden_module_vis(&field.vis));
+            let fn_name = field
+                .config
+                .accessor
+                .clone()
+                .unwrap_or_else(|| match &field.ident {
+                    Some(ident) => pluralize(ident),
+                    None => pluralize(&util::nth_field(index)),
+                });
+            let fn_name_mut = format_ident!("{fn_name}_mut");
+
+            (vis, fn_name, fn_name_mut)
+        })
+        .collect()
+}
+
+/// The shared and mutable accessors for the column of a single field
+fn field_accessor(
+    field: &Field,
+    struct_names: &StructNames,
+    member: Member,
+    (vis, fn_name, fn_name_mut): (Visibility, Ident, Ident),
+) -> TokenStream {
+    let ty = &field.ty;
+
+    // bit-packed, nullable and dictionary columns can't be borrowed as a slice of the field's
+    // type, so their accessors borrow them as a view instead, which can't change their length
+    // either
+    let krate = &struct_names.krate;
+    let views = match (field.config.bitpacked, field.nullable_type()) {
+        (true, _) => Some((
+            quote!(#krate::BitSlice<'_>),
+            quote!(#krate::BitSliceMut<'_>),
+        )),
+        (false, Some(ty)) => Some((
+            quote!(#krate::NullableSlice<'_, #ty>),
+            quote!(#krate::NullableSliceMut<'_, #ty>),
+        )),
+        (false, None) if field.config.dictionary => Some((
+            quote!(#krate::DictionarySlice<'_, #ty>),
+            quote!(#krate::DictionarySliceMut<'_, #ty>),
+        )),
+        (false, None) => None,
+    };
+
+    if let Some((slice_type, slice_mut_type)) = views {
+        return quote! {
+            #vis fn #fn_name(&self) -> #slice_type {
+                self.#member.as_slice()
+            }
+
+            #vis fn #fn_name_mut(&mut self) -> #slice_mut_type {
+                self.#member.as_mut_slice()
+            }
+        };
+    }
+
+    // a mutable reference to a flattened column would allow changing its length, so only
+    // shared access is given
+    if field.config.flatten {
+        let column_type = struct_names.column_type(field);
+
+        return quote! {
+            #vis fn #fn_name(&self) -> &#column_type {
+                &self.#member
+            }
+        };
+    }
+
+    let column = struct_names.column_call(field, quote!(self.#member), "as_slice", quote!());
+    let column_mut =
+        struct_names.column_call(field, quote!(self.#member), "as_mut_slice", quote!());
+
+    quote! {
+        #vis fn #fn_name(&self) -> &[#ty] {
+            #column
+        }
+
+        #vis fn #fn_name_mut(&mut self) -> &mut [#ty] {
+            #column_mut
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use super::*;
+
+pub(super) fn codegen(input: &Input, struct_names: &StructNames) -> TokenStream {
+    let push = push(input, struct_names);
+    let insert = insert(input, struct_names);
+
+    quote! {
+        #push
+        #insert
+    }
+}
+
+fn insert(input: &Input, struct_names: &StructNames) -> TokenStream {
+    let original = struct_names.original_type();
+    let reserve = reserve_one(input);
+    let insert_fields = input.map_fields(
+        |field| {
+            let name = field.ident.as_ref().unwrap();
+            let insert = struct_names.column_call(
+                field,
+                quote!(self.#name),
+                "insert",
+                quote!(index, value.#name),
+            );
+            quote! {
+                #insert;
+            }
+        },
+        |field, field_index| {
+            let field_index = Index::from(field_index);
+            let insert = struct_names.column_call(
+                field,
+                quote!(self.#field_index),
+                "insert",
+                quote!(index, value.#field_index),
+            );
+
+            quote! {
+                #insert;
+            }
+        },
+    );
+
+    quote! {
+        #[inline]
+        pub fn insert(&mut self, index: usize, value: #original) {
+            self.debug_assert_consistent();
+            #reserve
+            #insert_fields
+        }
+    }
+}
+
+fn push(input: &Input, struct_names: &StructNames) -> TokenStream {
+    let original = struct_names.original_type();
+    let reserve = reserve_one(input);
+
+    let push_fields = input.map_fields(
+        |field| {
+            let name = field.ident.as_ref().unwrap();
+            let push =
+                struct_names.column_call(field, quote!(self.#name), "push", quote!(value.#name));
+            quote! {
+                #push;
+            }
+        },
+        |field, index| {
+            let index = Index::from(index);
+            let push =
+                struct_names.column_call(field, quote!(self.#index), "push", quote!(value.#index));
+
+            quote! {
+                #push;
+            }
+        },
+    );
+
+    quote! {
+        #[inline]
+        pub fn push(&mut self, value: #original) {
+            self.debug_assert_consistent();
+            #reserve
+            #push_fields
+        }
+    }
+}
+
+/// The columns of a single allocation can't grow by themselves, so space for the new row is
+/// reserved in all of them first
+fn reserve_one(input: &Input) -> Option<TokenStream> {
+    input.single_allocation().then(|| quote!(self.reserve(1);))
+}
@@ -0,0 +1,65 @@
+use super::*;
+
+pub(super) fn codegen(input: &Input, struct_names: &StructNames) -> TokenStream {
+    let shared = iter_impl(input, struct_names, false);
+    let mutable = iter_impl(input, struct_names, true);
+
+    quote! {
+        #shared
+        #mutable
+    }
+}
+
+fn iter_impl(input: &Input, struct_names: &StructNames, mutable: bool) -> TokenStream {
+    let kind = match mutable {
+        true => IterKind::Mutable,
+        false => IterKind::Shared,
+    };
+    let fn_name = kind.column_iter_fn();
+
+    let maybe_mut = match mutable {
+        true => quote!(mut),
+        false => quote!(),
+    };
+
+    let name = struct_names.iter_name(kind);
+    let iter_type = struct_names.iter_type(kind);
+    let lifetime = &struct_names.lifetime;
+
+    let fields = input.map_fields_with_delimiters(
+        |field| {
+            let name = field.ident.as_ref().unwrap();
+            let column = column_iter(field, struct_names, quote!(#name), &fn_name, mutable);
+            quote! { #name: #column }
+        },
+        |field, index| {
+            let index = Index::from(index);
+            column_iter(field, struct_names, quote!(#index), &fn_name, mutable)
+        },
+    );
+
+    quote! {
+        #[inline]
+        pub fn #fn_name<#lifetime>(& #lifetime #maybe_mut self) -> #iter_type {
+            #name #fields
+        }
+    }
+}
+
+/// An iterator over a single column. Flattened columns are iterated through the `SovVec` trait,
+/// since they may have been renamed
+fn column_iter(
+    field: &Field,
+    struct_names: &StructNames,
+    member: TokenStream,
+    fn_name: &Ident,
+    mutable: bool,
+) -> TokenStream {
+    match field.config.flatten {
+        true => struct_names.nested_call(field, quote!(self.#member), &fn_name.to_string()),
+        false => {
+            let column = struct_names.column_slice(field, quote!(self.#member), mutable);
+            quote!(#column.#fn_name())
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, Ident, Index, Member, Visibility};
+
+use crate::{
+    parse::{Field, Input},
+    util::{self, pluralize},
+};
+
+use super::structs::{IterKind, StructNames};
+
+mod constructors;
+mod empty;
+mod get;
+mod insert;
+mod iter;
+#[cfg(feature = "rayon")]
+pub(super) mod rayon;
+mod remove;
+mod slices;
+mod sort;
+
+pub(super) use get::accessor_names;
+
+pub(super) fn generate_impl_block(input: &Input, struct_names: &StructNames) -> TokenStream {
+    let vec_type = struct_names.vec_type();
+    let generics = struct_names.vec_generics();
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let slices = slices::codegen(input, struct_names);
+    let sort = sort::codegen(input, struct_names);
+    let (par_methods, par_impls) = parallel(input, struct_names);
+
+    if input.fields.is_empty() {
+        let body = empty::codegen(input, struct_names);
+
+        return quote! {
+            impl #impl_generics #vec_type #where_clause {
+                #body
+                #slices
+                #sort
+                #par_methods
+            }
+
+            #par_impls
+        };
+    }
+
+    let constructors = constructors::codegen(input, struct_names);
+    let global_constructors = constructors::global_constructors(input, struct_names);
+    let insert = insert::codegen(input, struct_names);
+    let remove = remove::codegen(input, struct_names);
+    let get = get::codegen(input, struct_names);
+    let iter = iter::codegen(input, struct_names);
+
+    let len_is_empty = len_is_empty(input, struct_names);
+    let debug_assert_consistent = debug_assert_consistent(input, struct_names);
+
+    quote! {
+        impl #impl_generics #vec_type #where_clause {
+            #constructors
+            #get
+            #slices
+            #iter
+            #insert
+            #len_is_empty
+            #remove
+            #sort
+            #debug_assert_consistent
+            #par_methods
+        }
+
+        #global_constructors
+        #par_impls
+    }
+}
+
+/// Methods and trait impls for parallel iteration, which are empty without the `rayon` feature
+#[cfg(feature = "rayon")]
+fn parallel(input: &Input, struct_names: &StructNames) -> (TokenStream, TokenStream) {
+    let methods = match input.fields.is_empty() {
+        true => rayon::empty_methods(struct_names),
+        false => rayon::methods(input, struct_names),
+    };
+
+    (methods, rayon::collect_impls(struct_names))
+}
+
+#[cfg(not(feature = "rayon"))]
+fn parallel(_input: &Input, _struct_names: &StructNames) -> (TokenStream, TokenStream) {
+    (TokenStream::new(), TokenStream::new())
+}
+
+fn len_is_empty(input: &Input, struct_names: &StructNames) -> TokenStream {
+    let Some((field, member)) = input.fields.iter().zip(input.fields.members()).next() else {
+        return TokenStream::new();
+    };
+    let len = struct_names.column_call(field, quote!(self.#member), "len", quote!());
+
+    quote! {
+        #[inline]
+        pub fn len(&self) -> usize {
+            #len
+        }
+
+        #[inline]
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+    }
+}
+
+/// A check that every column has the same length, which is called at the start of every method
+/// that changes the length. The methods keep the columns in sync, but `#[sov(expose_columns)]`
+/// allows modifying them directly
+fn debug_assert_consistent(input: &Input, struct_names: &StructNames) -> TokenStream {
+    let checks = input
+        .fields
+        .iter()
+        .zip(input.fields.members())
+        .map(|(field, member)| {
+            let message = format!(
+                "column `{}` has {{}} rows, but the vec has {{}} rows",
+                quote!(#member),
+            );
+            let column_len = struct_names.column_call(field, quote!(self.#member), "len", quote!());
+
+            quote! {
+                ::core::debug_assert_eq!(#column_len, len, #message, #column_len, len);
+            }
+        });
+
+    quote! {
+        #[inline]
+        fn debug_assert_consistent(&self) {
+            let len = self.len();
+            #(#checks)*
+        }
+    }
+}
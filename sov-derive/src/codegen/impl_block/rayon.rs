@@ -0,0 +1,227 @@
+//! Parallel iterators, generated when the `rayon` feature is enabled.
+//!
+//! These zip together a parallel iterator over each column, in the same way as the sequential
+//! iterators. Flattened columns are `Vec`-like types generated by this macro, so they have the same
+//! methods as a `Vec`
+use super::*;
+
+/// Methods for the impl block of the `Vec`-like type
+pub(super) fn methods(input: &Input, struct_names: &StructNames) -> TokenStream {
+    let rayon = rayon_path(struct_names);
+    let lifetime = &struct_names.lifetime;
+    let shared_ref_type = struct_names.ref_type(false);
+    let mut_ref_type = struct_names.ref_type(true);
+    let slices_mut_type = struct_names.slices_type(true);
+
+    let sync_bounds = bounds(input, quote!(::core::marker::Sync));
+    let send_bounds = bounds(input, quote!(::core::marker::Send));
+
+    let par_iter = zip_columns(
+        input,
+        struct_names,
+        &struct_names.shared_ref,
+        false,
+        quote!(par_iter()),
+    );
+    let par_iter_mut = zip_columns(
+        input,
+        struct_names,
+        &struct_names.mut_ref,
+        true,
+        quote!(par_iter_mut()),
+    );
+    let par_chunks_mut = zip_columns(
+        input,
+        struct_names,
+        &struct_names.slices_mut,
+        true,
+        quote!(par_chunks_mut(chunk_size)),
+    );
+
+    quote! {
+        /// A parallel iterator over shared references to each row
+        #[inline]
+        pub fn par_iter<#lifetime>(& #lifetime self)
+            -> impl #rayon::iter::IndexedParallelIterator<Item = #shared_ref_type>
+        where
+            #sync_bounds
+        {
+            use #rayon::prelude::*;
+            #par_iter
+        }
+
+        /// A parallel iterator over mutable references to each row
+        #[inline]
+        pub fn par_iter_mut<#lifetime>(& #lifetime mut self)
+            -> impl #rayon::iter::IndexedParallelIterator<Item = #mut_ref_type>
+        where
+            #send_bounds
+        {
+            use #rayon::prelude::*;
+            #par_iter_mut
+        }
+
+        /// A parallel iterator over chunks of `chunk_size` rows, yielding a mutable slice of each
+        /// column. The last chunk may be shorter.
+        ///
+        /// Panics if `chunk_size` is 0
+        #[inline]
+        pub fn par_chunks_mut<#lifetime>(& #lifetime mut self, chunk_size: ::core::primitive::usize)
+            -> impl #rayon::iter::IndexedParallelIterator<Item = #slices_mut_type>
+        where
+            #send_bounds
+        {
+            use #rayon::prelude::*;
+            #par_chunks_mut
+        }
+    }
+}
+
+/// Methods for the impl block of a `Vec`-like type without any columns
+pub(super) fn empty_methods(struct_names: &StructNames) -> TokenStream {
+    let rayon = rayon_path(struct_names);
+    let lifetime = &struct_names.lifetime;
+    let shared_ref = &struct_names.shared_ref;
+    let mut_ref = &struct_names.mut_ref;
+    let slices_mut = &struct_names.slices_mut;
+    let shared_ref_type = struct_names.ref_type(false);
+    let mut_ref_type = struct_names.ref_type(true);
+    let slices_mut_type = struct_names.slices_type(true);
+
+    quote! {
+        #[inline]
+        pub fn par_iter<#lifetime>(& #lifetime self)
+            -> impl #rayon::iter::IndexedParallelIterator<Item = #shared_ref_type>
+        {
+            use #rayon::prelude::*;
+            (0..self.len).into_par_iter().map(|_| #shared_ref { _marker: ::core::marker::PhantomData })
+        }
+
+        #[inline]
+        pub fn par_iter_mut<#lifetime>(& #lifetime mut self)
+            -> impl #rayon::iter::IndexedParallelIterator<Item = #mut_ref_type>
+        {
+            use #rayon::prelude::*;
+            (0..self.len).into_par_iter().map(|_| #mut_ref { _marker: ::core::marker::PhantomData })
+        }
+
+        #[inline]
+        pub fn par_chunks_mut<#lifetime>(& #lifetime mut self, chunk_size: ::core::primitive::usize)
+            -> impl #rayon::iter::IndexedParallelIterator<Item = #slices_mut_type>
+        {
+            use #rayon::prelude::*;
+            assert!(chunk_size != 0, "chunk_size must not be zero");
+            (0..self.len.div_ceil(chunk_size))
+                .into_par_iter()
+                .map(|_| #slices_mut { _marker: ::core::marker::PhantomData })
+        }
+    }
+}
+
+/// `ParallelExtend` and `FromParallelIterator` impls, which are shared with enums
+pub(crate) fn collect_impls(struct_names: &StructNames) -> TokenStream {
+    let rayon = rayon_path(struct_names);
+    let krate = &struct_names.krate;
+    let original_type = struct_names.original_type();
+    let vec_type = struct_names.vec_type();
+    let global_vec_type = struct_names.global_vec_type();
+    let send_bound = util::non_trivial_bound(&original_type, quote!(::core::marker::Send));
+
+    let generics = util::with_bounds(&struct_names.vec_generics(), [send_bound.clone()]);
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    // collecting creates a new vec, so it can only be done with the global allocator
+    let global_generics = util::with_bounds(&struct_names.generics, [send_bound]);
+    let (global_impl_generics, _, global_where_clause) = global_generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #rayon::iter::ParallelExtend<#original_type> for #vec_type #where_clause {
+            fn par_extend<I>(&mut self, par_iter: I)
+            where
+                I: #rayon::iter::IntoParallelIterator<Item = #original_type>,
+            {
+                // splitting the rows into columns in parallel would need a temporary `Vec` per
+                // column anyway, so the rows are collected in parallel and then pushed, which
+                // `extend` reserves space for up front
+                let values: #krate::__private::Vec<#original_type> =
+                    #rayon::iter::ParallelIterator::collect(par_iter.into_par_iter());
+
+                ::core::iter::Extend::extend(self, values);
+            }
+        }
+
+        impl #global_impl_generics #rayon::iter::FromParallelIterator<#original_type> for #global_vec_type #global_where_clause {
+            fn from_par_iter<I>(par_iter: I) -> Self
+            where
+                I: #rayon::iter::IntoParallelIterator<Item = #original_type>,
+            {
+                let mut vec = Self::new();
+                #rayon::iter::ParallelExtend::par_extend(&mut vec, par_iter);
+                vec
+            }
+        }
+    }
+}
+
+pub(crate) fn rayon_path(struct_names: &StructNames) -> TokenStream {
+    let krate = &struct_names.krate;
+    quote!(#krate::__private::rayon)
+}
+
+/// Call `method` on every column, zip the resulting parallel iterators together, and build a
+/// `name` from each item. `mutable` is whether `method` needs mutable access to the columns
+fn zip_columns(
+    input: &Input,
+    struct_names: &StructNames,
+    name: &Ident,
+    mutable: bool,
+    method: TokenStream,
+) -> TokenStream {
+    let members = input.fields.members();
+    let bindings: Vec<_> = (0..members.len()).map(util::nth_field).collect();
+
+    let mut iter = quote!();
+    let mut pattern = quote!();
+
+    for (index, ((field, member), binding)) in
+        input.fields.iter().zip(&members).zip(&bindings).enumerate()
+    {
+        let column = struct_names.column_slice(field, quote!(self.#member), mutable);
+
+        match index {
+            0 => {
+                iter = quote!(#column.#method);
+                pattern = quote!(#binding);
+            }
+            _ => {
+                iter = quote!(#iter.zip(#column.#method));
+                pattern = quote!((#pattern, #binding));
+            }
+        }
+    }
+
+    quote! {
+        #iter.map(|#pattern| #name { #(#members: #bindings,)* })
+    }
+}
+
+/// `#ty: #bound` for the type of every field, so that generic fields can be used across threads.
+/// Nullable fields are stored as their `T`, so the bound is on that instead. Every row of a
+/// dictionary field borrows the same dictionary, so it needs to be `Sync` as well.
+///
+/// Flattened fields also need the nested type's bounds, see `util::nested_bounds`
+fn bounds(input: &Input, bound: TokenStream) -> TokenStream {
+    let mut bounds = Vec::new();
+
+    for field in input.fields.iter() {
+        let ty = field.nullable_type().unwrap_or(&field.ty);
+
+        bounds.extend(util::nested_bounds(&input.generics, field, &bound));
+        bounds.push(match field.config.dictionary {
+            true => util::non_trivial_bound(ty, quote!(#bound + ::core::marker::Sync)),
+            false => util::non_trivial_bound(ty, &bound),
+        });
+    }
+
+    quote!(#(#bounds,)*)
+}
@@ -0,0 +1,78 @@
+use super::*;
+
+pub(super) fn codegen(input: &Input, struct_names: &StructNames) -> TokenStream {
+    let remove = remove_impl(input, struct_names, parse_quote!(remove));
+    let swap_remove = remove_impl(input, struct_names, parse_quote!(swap_remove));
+    let pop = pop(input, struct_names);
+
+    quote! {
+        #remove
+        #swap_remove
+        #pop
+    }
+}
+
+fn pop(input: &Input, struct_names: &StructNames) -> TokenStream {
+    let original = &struct_names.original;
+    let original_type = struct_names.original_type();
+
+    let fields = input.map_fields_with_delimiters(
+        |field| {
+            let name = field.ident.as_ref().unwrap();
+            let pop = struct_names.column_call(field, quote!(self.#name), "pop", quote!());
+
+            quote! {
+                #name: #pop?
+            }
+        },
+        |field, index| {
+            let index = Index::from(index);
+            let pop = struct_names.column_call(field, quote!(self.#index), "pop", quote!());
+
+            quote! {
+                #pop?
+            }
+        },
+    );
+
+    quote! {
+        #[inline]
+        pub fn pop(&mut self) -> Option<#original_type> {
+            self.debug_assert_consistent();
+            Some(#original #fields)
+        }
+    }
+}
+
+fn remove_impl(input: &Input, struct_names: &StructNames, remove_fn: Ident) -> TokenStream {
+    let original = &struct_names.original;
+    let original_type = struct_names.original_type();
+    let method = remove_fn.to_string();
+
+    let fields = input.map_fields_with_delimiters(
+        |field| {
+            let name = field.ident.as_ref().unwrap();
+            let remove =
+                struct_names.column_call(field, quote!(self.#name), &method, quote!(index));
+
+            quote! {
+                #name: #remove
+            }
+        },
+        |field, field_index| {
+            let field_index = Index::from(field_index);
+            let remove =
+                struct_names.column_call(field, quote!(self.#field_index), &method, quote!(index));
+
+            quote! {
+                #remove
+            }
+        },
+    );
+    quote! {
+        pub fn #remove_fn(&mut self, index: usize) -> #original_type {
+            self.debug_assert_consistent();
+            #original #fields
+        }
+    }
+}
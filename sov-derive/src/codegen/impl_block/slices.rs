@@ -0,0 +1,136 @@
+use super::*;
+
+pub(super) fn codegen(input: &Input, struct_names: &StructNames) -> TokenStream {
+    let shared = slices_impl(input, struct_names, false);
+    let mutable = slices_impl(input, struct_names, true);
+    let shared_view = slice_impl(input, struct_names, false);
+    let mutable_view = slice_impl(input, struct_names, true);
+
+    quote! {
+        #shared
+        #mutable
+        #shared_view
+        #mutable_view
+    }
+}
+
+/// `as_slice` and `as_mut_slice`, which borrow every row as a `FooSlice` or `FooSliceMut`
+fn slice_impl(input: &Input, struct_names: &StructNames, mutable: bool) -> TokenStream {
+    let name = match mutable {
+        true => &struct_names.slice_mut,
+        false => &struct_names.slice,
+    };
+    let slice_type = struct_names.slice_type(mutable);
+    let lifetime = &struct_names.lifetime;
+
+    let fn_name = match mutable {
+        true => quote!(as_mut_slice),
+        false => quote!(as_slice),
+    };
+
+    let maybe_mut = match mutable {
+        true => quote!(mut),
+        false => quote!(),
+    };
+
+    let column = |field: &Field, member: TokenStream| match field.config.flatten {
+        true => struct_names.nested_call(field, quote!(self.#member), &fn_name.to_string()),
+        false => {
+            struct_names.column_call(field, quote!(self.#member), &fn_name.to_string(), quote!())
+        }
+    };
+
+    let fields = match input.fields.is_empty() {
+        true => quote!({ len: self.len, _marker: ::core::marker::PhantomData }),
+        false => {
+            let members = input.fields.members();
+            let columns = input
+                .fields
+                .iter()
+                .zip(&members)
+                .map(|(field, member)| column(field, quote!(#member)));
+
+            quote!({ #(#members: #columns,)* })
+        }
+    };
+
+    let doc = match mutable {
+        true => quote! {
+            /// Mutably borrow every row as a view, which can be sliced, split and chunked like a
+            /// `&mut [T]`
+        },
+        false => quote! {
+            /// Borrow every row as a view, which can be sliced, split and chunked like a `&[T]`
+        },
+    };
+
+    quote! {
+        #doc
+        #[inline]
+        pub fn #fn_name<#lifetime>(& #lifetime #maybe_mut self) -> #slice_type {
+            #name #fields
+        }
+    }
+}
+
+fn slices_impl(input: &Input, struct_names: &StructNames, mutable: bool) -> TokenStream {
+    let name = match mutable {
+        true => &struct_names.slices_mut,
+        false => &struct_names.slices,
+    };
+    let slices_type = struct_names.slices_type(mutable);
+    let lifetime = &struct_names.lifetime;
+
+    let (fn_name, column_fn_name) = match mutable {
+        true => (quote!(as_mut_slices), quote!(as_mut_slice)),
+        false => (quote!(as_slices), quote!(as_slice)),
+    };
+
+    let maybe_mut = match mutable {
+        true => quote!(mut),
+        false => quote!(),
+    };
+
+    let column = |field: &Field, member: TokenStream| match field.config.flatten {
+        true => struct_names.nested_call(field, quote!(self.#member), &fn_name.to_string()),
+        false => struct_names.column_call(
+            field,
+            quote!(self.#member),
+            &column_fn_name.to_string(),
+            quote!(),
+        ),
+    };
+
+    let fields = match input.fields.is_empty() {
+        true => quote!({ _marker: ::core::marker::PhantomData }),
+        false => input.map_fields_with_delimiters(
+            |field| {
+                let name = field.ident.as_ref().unwrap();
+                let column = column(field, quote!(#name));
+                quote! { #name: #column }
+            },
+            |field, index| {
+                let index = Index::from(index);
+                column(field, quote!(#index))
+            },
+        ),
+    };
+
+    let doc = match mutable {
+        true => quote! {
+            /// Mutably borrow every column at once, so that different columns can be modified at
+            /// the same time
+        },
+        false => quote! {
+            /// Borrow every column at once
+        },
+    };
+
+    quote! {
+        #doc
+        #[inline]
+        pub fn #fn_name<#lifetime>(& #lifetime #maybe_mut self) -> #slices_type {
+            #name #fields
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use syn::ext::IdentExt;
+
+use super::*;
+
+pub(super) fn codegen(input: &Input, struct_names: &StructNames) -> TokenStream {
+    let apply_permutation = apply_permutation(input, struct_names);
+    let field_sorts = input.map_fields(
+        |field| {
+            let name = field.ident.as_ref().unwrap();
+            field_sort(field, struct_names, quote!(#name), name.unraw())
+        },
+        |field, index| {
+            let field_index = Index::from(index);
+            field_sort(
+                field,
+                struct_names,
+                quote!(#field_index),
+                util::nth_field(index),
+            )
+        },
+    );
+
+    quote! {
+        #apply_permutation
+        #field_sorts
+    }
+}
+
+fn apply_permutation(input: &Input, struct_names: &StructNames) -> TokenStream {
+    let krate = &struct_names.krate;
+
+    // structs without fields have nothing to reorder
+    if input.fields.is_empty() {
+        return quote! {
+            #[doc(hidden)]
+            #[inline]
+            pub fn apply_permutation(&mut self, _permutation: &#krate::__private::Permutation) {}
+        };
+    }
+
+    let columns =
+        input
+            .fields
+            .iter()
+            .zip(input.fields.members())
+            .map(|(field, member)| match field.config.flatten {
+                true => quote!(self.#member.apply_permutation(permutation);),
+                false => {
+                    let column = struct_names.column_call(
+                        field,
+                        quote!(self.#member),
+                        "as_mut_slice",
+                        quote!(),
+                    );
+                    quote!(permutation.apply(#column);)
+                }
+            });
+
+    quote! {
+        #[doc(hidden)]
+        #[inline]
+        pub fn apply_permutation(&mut self, permutation: &#krate::__private::Permutation) {
+            #(#columns)*
+        }
+    }
+}
+
+/// `sort_by_x()` and `sort_unstable_by_x()` for a field `x`, which compare the values in its column
+/// directly. Flattened fields can't be compared, and a field called `key` would clash with
+/// `sort_by_key`, so they are skipped
+fn field_sort(
+    field: &Field,
+    struct_names: &StructNames,
+    member: TokenStream,
+    name: Ident,
+) -> TokenStream {
+    if field.config.flatten || name == "key" {
+        return TokenStream::new();
+    }
+
+    let krate = &struct_names.krate;
+    let vis = field
+        .config
+        .vis
+        .clone()
+        .unwrap_or_else(|| util::hidden_module_vis(&field.vis));
+    let ty = &field.ty;
+    let bound = util::non_trivial_bound(ty, quote!(::core::cmp::Ord));
+    let column = struct_names.column_slice(field, quote!(self.#member), false);
+
+    let sort_by = format_ident!("sort_by_{name}");
+    let sort_unstable_by = format_ident!("sort_unstable_by_{name}");
+    let doc = format!(
+        "Sort the rows by `{name}`, keeping rows with equal values in their original order"
+    );
+    let unstable_doc = format!(
+        "Sort the rows by `{name}`, without keeping rows with equal values in their original order"
+    );
+
+    quote! {
+        #[doc = #doc]
+        #vis fn #sort_by(&mut self)
+        where
+            #bound
+        {
+            let mut order: #krate::__private::Vec<_> = (0..self.len()).collect();
+            order.sort_by(|&a, &b| ::core::cmp::Ord::cmp(&#column[a], &#column[b]));
+            self.apply_permutation(&#krate::__private::Permutation::new(order));
+        }
+
+        #[doc = #unstable_doc]
+        #vis fn #sort_unstable_by(&mut self)
+        where
+            #bound
+        {
+            let mut order: #krate::__private::Vec<_> = (0..self.len()).collect();
+            order.sort_unstable_by(|&a, &b| ::core::cmp::Ord::cmp(&#column[a], &#column[b]));
+            self.apply_permutation(&#krate::__private::Permutation::new(order));
+        }
+    }
+}
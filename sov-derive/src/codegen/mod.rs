@@ -0,0 +1,96 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::{
+    parse::{Input, Item},
+    util,
+};
+
+mod array;
+mod conversions;
+mod derives;
+mod drain;
+mod enums;
+mod impl_block;
+mod sort;
+mod structs;
+mod traits;
+mod views;
+
+pub fn codegen(item: Item) -> TokenStream {
+    match item {
+        Item::Struct(input) => codegen_struct(input),
+        Item::Enum(input) => enums::codegen(input),
+    }
+}
+
+fn codegen_struct(input: Input) -> TokenStream {
+    let vis = &input.vis;
+    let structs::GeneratedStructs {
+        vec,
+        ref_and_ref_mut,
+        slices,
+        slice,
+        iters,
+        names: struct_names,
+    } = structs::codegen_structs(&input);
+
+    let impl_block = impl_block::generate_impl_block(&input, &struct_names);
+    let traits = traits::codegen(&struct_names);
+    let derives = derives::codegen(&input, &struct_names);
+    let conversions = conversions::codegen(
+        &struct_names,
+        conversions::RefToOwned::for_struct(&input, &struct_names),
+    );
+    let views = views::codegen(
+        &struct_names,
+        views::ViewPrimitives::for_struct(&input, &struct_names),
+    );
+    let sort = sort::codegen(&struct_names);
+    let drain = drain::codegen(&struct_names, &util::hidden_module_vis(&input.vis));
+    let array = array::codegen(&input, &struct_names);
+    let mod_name = &struct_names.module;
+
+    let vec_name = &struct_names.vec;
+    let iter_name = &struct_names.iter;
+    let iter_mut_name = &struct_names.iter_mut;
+    let into_iter_name = &struct_names.into_iter;
+    let drain_name = &struct_names.drain;
+    let slice_name = &struct_names.slice;
+    let slice_mut_name = &struct_names.slice_mut;
+    let array_names = match input.config.array {
+        true => {
+            let array_name = &struct_names.array;
+            let array_into_iter_name = &struct_names.array_into_iter;
+            quote!(#array_name, #array_into_iter_name,)
+        }
+        false => quote!(),
+    };
+
+    quote! {
+        #vis use #mod_name::{
+            #vec_name, #iter_name, #iter_mut_name, #into_iter_name, #drain_name, #slice_name,
+            #slice_mut_name, #array_names
+        };
+
+        #ref_and_ref_mut
+        #slices
+
+        #[doc(hidden)]
+        mod #mod_name {
+            use super::*;
+
+            #vec
+            #iters
+            #slice
+            #views
+            #impl_block
+            #traits
+            #derives
+            #conversions
+            #sort
+            #drain
+            #array
+        }
+    }
+}
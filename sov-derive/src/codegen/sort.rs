@@ -0,0 +1,104 @@
+//! Sorting and deduplicating the rows of the `Vec`-like type.
+//!
+//! Rather than moving whole rows around while sorting, the indices of the rows are sorted, and the
+//! resulting permutation is applied to each column once. Only `apply_permutation` depends on how
+//! the rows are stored, so the rest is shared by structs and enums
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Lifetime;
+
+use super::structs::StructNames;
+
+pub(super) fn codegen(names: &StructNames) -> TokenStream {
+    let vec_type = names.vec_type();
+    let krate = &names.krate;
+    let generics = names.vec_generics();
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let ref_type = names.ref_type_with_lifetime(false, &Lifetime::new("'_", names.lifetime.span()));
+
+    quote! {
+        impl #impl_generics #vec_type #where_clause {
+            /// Sort the rows with a comparison function, keeping rows that compare equal in their
+            /// original order.
+            ///
+            /// The indices of the rows are sorted first, so each column is only reordered once
+            pub fn sort_by<F>(&mut self, mut compare: F)
+            where
+                F: ::core::ops::FnMut(#ref_type, #ref_type) -> ::core::cmp::Ordering,
+            {
+                let mut order: #krate::__private::Vec<_> = (0..self.len()).collect();
+                order.sort_by(|&a, &b| compare(self.get(a), self.get(b)));
+                self.apply_permutation(&#krate::__private::Permutation::new(order));
+            }
+
+            /// Sort the rows with a comparison function, without keeping rows that compare equal
+            /// in their original order
+            pub fn sort_unstable_by<F>(&mut self, mut compare: F)
+            where
+                F: ::core::ops::FnMut(#ref_type, #ref_type) -> ::core::cmp::Ordering,
+            {
+                let mut order: #krate::__private::Vec<_> = (0..self.len()).collect();
+                order.sort_unstable_by(|&a, &b| compare(self.get(a), self.get(b)));
+                self.apply_permutation(&#krate::__private::Permutation::new(order));
+            }
+
+            /// Sort the rows by a key, keeping rows with equal keys in their original order
+            pub fn sort_by_key<K, F>(&mut self, mut key: F)
+            where
+                K: ::core::cmp::Ord,
+                F: ::core::ops::FnMut(#ref_type) -> K,
+            {
+                let mut order: #krate::__private::Vec<_> = (0..self.len()).collect();
+                order.sort_by_key(|&index| key(self.get(index)));
+                self.apply_permutation(&#krate::__private::Permutation::new(order));
+            }
+
+            /// Sort the rows by a key, without keeping rows with equal keys in their original
+            /// order
+            pub fn sort_unstable_by_key<K, F>(&mut self, mut key: F)
+            where
+                K: ::core::cmp::Ord,
+                F: ::core::ops::FnMut(#ref_type) -> K,
+            {
+                let mut order: #krate::__private::Vec<_> = (0..self.len()).collect();
+                order.sort_unstable_by_key(|&index| key(self.get(index)));
+                self.apply_permutation(&#krate::__private::Permutation::new(order));
+            }
+
+            /// Remove consecutive rows with the same key, keeping the first row of each run
+            pub fn dedup_by_key<K, F>(&mut self, mut key: F)
+            where
+                K: ::core::cmp::PartialEq,
+                F: ::core::ops::FnMut(#ref_type) -> K,
+            {
+                let mut kept = #krate::__private::Vec::with_capacity(self.len());
+                let mut duplicates = #krate::__private::Vec::new();
+                let mut last_key = ::core::option::Option::None;
+
+                for index in 0..self.len() {
+                    let current = key(self.get(index));
+
+                    if last_key.as_ref() == ::core::option::Option::Some(&current) {
+                        duplicates.push(index);
+                    } else {
+                        kept.push(index);
+                        last_key = ::core::option::Option::Some(current);
+                    }
+                }
+
+                if duplicates.is_empty() {
+                    return;
+                }
+
+                // move the duplicates to the end, keeping the other rows in order, and drop them
+                let count = duplicates.len();
+                kept.extend(duplicates);
+                self.apply_permutation(&#krate::__private::Permutation::new(kept));
+
+                for _ in 0..count {
+                    self.pop();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! Iterators over the rows of the `Vec`-like type.
+//!
+//! Each iterator zips together an iterator over every column, so iterating doesn't need any bounds
+//! checks, and `nth` can skip ahead in every column at once
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Ident, Lifetime, Path, Type};
+
+use crate::{
+    parse::{Field, Input},
+    util,
+};
+
+use super::{define_struct, fields_with_allocation, StructNames};
+
+/// The three ways of iterating over a `Vec`-like type
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum IterKind {
+    /// Yields `FooRef`
+    Shared,
+    /// Yields `FooRefMut`
+    Mutable,
+    /// Yields `Foo`, consuming the vec
+    Owned,
+}
+
+impl IterKind {
+    pub const ALL: [Self; 3] = [Self::Shared, Self::Mutable, Self::Owned];
+
+    /// The type of the iterator over a `Vec<#ty>` column
+    pub fn column_iter_type(self, ty: &Type, lifetime: &Lifetime, krate: &Path) -> Type {
+        match self {
+            IterKind::Shared => parse_quote!(::core::slice::Iter<#lifetime, #ty>),
+            IterKind::Mutable => parse_quote!(::core::slice::IterMut<#lifetime, #ty>),
+            IterKind::Owned => parse_quote!(#krate::__private::VecIntoIter<#ty>),
+        }
+    }
+
+    /// The method that creates the iterator over a column, e.g. `iter_mut`
+    pub fn column_iter_fn(self) -> Ident {
+        match self {
+            IterKind::Shared => parse_quote!(iter),
+            IterKind::Mutable => parse_quote!(iter_mut),
+            IterKind::Owned => parse_quote!(into_iter),
+        }
+    }
+}
+
+pub(super) fn generate_iters(input: &Input, names: &StructNames) -> TokenStream {
+    let iters = IterKind::ALL.map(|kind| {
+        let definition = define_iter(input, names, kind);
+        let iterator = iterator_impls(input, names, kind);
+        let into_iterator = into_iterator_impl(input, names, kind);
+
+        quote! {
+            #definition
+            #iterator
+            #into_iterator
+        }
+    });
+
+    quote!(#(#iters)*)
+}
+
+fn define_iter(input: &Input, names: &StructNames, kind: IterKind) -> TokenStream {
+    let name = names.iter_name(kind);
+    let vis = util::hidden_module_vis(&input.vis);
+    let generics = match kind {
+        IterKind::Owned => names.vec_definition_generics(),
+        IterKind::Shared | IterKind::Mutable => names.iter_generics(kind),
+    };
+    let lifetime = &names.lifetime;
+
+    // there are no columns to iterate over, so only the remaining rows are counted
+    if input.fields.is_empty() {
+        let where_clause = &generics.where_clause;
+        let marker = match kind {
+            IterKind::Owned => quote!(),
+            IterKind::Shared | IterKind::Mutable => {
+                quote!(_marker: ::core::marker::PhantomData<& #lifetime ()>,)
+            }
+        };
+
+        return quote! {
+            #vis struct #name #generics #where_clause {
+                range: ::core::ops::Range<::core::primitive::usize>,
+                #marker
+            }
+        };
+    }
+
+    let map_field = |field: &Field| {
+        let field = syn::Field {
+            vis: syn::Visibility::Inherited,
+            ty: names.column_iter_type(field, kind),
+            ..field.field.clone()
+        };
+
+        quote!(#field)
+    };
+
+    // the iterator over the owned rows takes ownership of the allocation that they're stored in
+    let fields = match kind {
+        IterKind::Owned => fields_with_allocation(input, names, input.fields.iter().map(map_field)),
+        IterKind::Shared | IterKind::Mutable => {
+            input.map_fields_with_delimiters(map_field, |field, _index| map_field(field))
+        }
+    };
+
+    define_struct(input, &vis, name, &generics, fields)
+}
+
+fn iterator_impls(input: &Input, names: &StructNames, kind: IterKind) -> TokenStream {
+    let iter_type = names.iter_type(kind);
+    let item_type = names.iter_item_type(kind);
+    let generics = names.iter_generics(kind);
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let next = advance(input, names, kind, quote!(next()));
+    let nth = advance(input, names, kind, quote!(nth(n)));
+    let next_back = advance(input, names, kind, quote!(next_back()));
+    let nth_back = advance(input, names, kind, quote!(nth_back(n)));
+
+    // all columns have the same length, so any of them gives the size hint
+    let size_hint = match input.fields.members().first() {
+        Some(member) => quote!(self.#member.size_hint()),
+        None => quote!(self.range.size_hint()),
+    };
+
+    quote! {
+        impl #impl_generics ::core::iter::Iterator for #iter_type #where_clause {
+            type Item = #item_type;
+
+            #[inline]
+            fn next(&mut self) -> ::core::option::Option<Self::Item> {
+                #next
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (::core::primitive::usize, ::core::option::Option<::core::primitive::usize>) {
+                #size_hint
+            }
+
+            #[inline]
+            fn nth(&mut self, n: ::core::primitive::usize) -> ::core::option::Option<Self::Item> {
+                #nth
+            }
+        }
+
+        impl #impl_generics ::core::iter::DoubleEndedIterator for #iter_type #where_clause {
+            #[inline]
+            fn next_back(&mut self) -> ::core::option::Option<Self::Item> {
+                #next_back
+            }
+
+            #[inline]
+            fn nth_back(&mut self, n: ::core::primitive::usize) -> ::core::option::Option<Self::Item> {
+                #nth_back
+            }
+        }
+
+        impl #impl_generics ::core::iter::ExactSizeIterator for #iter_type #where_clause {}
+
+        impl #impl_generics ::core::iter::FusedIterator for #iter_type #where_clause {}
+    }
+}
+
+/// Call `method` on the iterator of every column, and build a row from the results.
+///
+/// Every column is advanced even if an earlier one is exhausted, so that they all stay in step
+pub(in crate::codegen) fn advance(
+    input: &Input,
+    names: &StructNames,
+    kind: IterKind,
+    method: TokenStream,
+) -> TokenStream {
+    let name = match kind {
+        IterKind::Shared => &names.shared_ref,
+        IterKind::Mutable => &names.mut_ref,
+        IterKind::Owned => &names.original,
+    };
+
+    if input.fields.is_empty() {
+        let row = match kind {
+            IterKind::Shared | IterKind::Mutable => {
+                quote!(#name { _marker: ::core::marker::PhantomData })
+            }
+            // `{}`, `()` or nothing, depending on the kind of struct
+            IterKind::Owned => {
+                let fields = input.map_fields_with_delimiters(|_| quote!(), |_, _| quote!());
+                quote!(#name #fields)
+            }
+        };
+
+        return quote!(self.range.#method.map(|_| #row));
+    }
+
+    // the values are bound to `field0`, `field1`, etc. so they can't shadow `n`
+    let members = input.fields.members();
+    let bindings: Vec<_> = (0..members.len()).map(util::nth_field).collect();
+
+    quote! {
+        #(let #bindings = self.#members.#method;)*
+        ::core::option::Option::Some(#name { #(#members: #bindings?,)* })
+    }
+}
+
+fn into_iterator_impl(input: &Input, names: &StructNames, kind: IterKind) -> TokenStream {
+    let lifetime = &names.lifetime;
+    let iter_type = names.iter_type(kind);
+    let item_type = names.iter_item_type(kind);
+    let vec_type = names.vec_type();
+
+    // the iterators over references don't depend on the allocator, but the impls for references to
+    // the vec do
+    let generics = match kind {
+        IterKind::Owned => names.iter_generics(kind),
+        IterKind::Shared | IterKind::Mutable => names.with_allocator(names.iter_generics(kind)),
+    };
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let (self_type, body) = match kind {
+        IterKind::Shared => (quote!(& #lifetime #vec_type), quote!(self.iter())),
+        IterKind::Mutable => (quote!(& #lifetime mut #vec_type), quote!(self.iter_mut())),
+        IterKind::Owned => {
+            let iter = names.iter_name(kind);
+            let members = input.fields.members();
+            let allocation = input
+                .allocation_member()
+                .map(|member| quote!(#member: self.#member,));
+            let body = match input.fields.is_empty() {
+                true => quote!(#iter { range: 0..self.len }),
+                false => quote! {
+                    #iter {
+                        #(#members: ::core::iter::IntoIterator::into_iter(self.#members),)*
+                        #allocation
+                    }
+                },
+            };
+
+            (vec_type.clone(), body)
+        }
+    };
+
+    quote! {
+        impl #impl_generics ::core::iter::IntoIterator for #self_type #where_clause {
+            type Item = #item_type;
+            type IntoIter = #iter_type;
+
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                #body
+            }
+        }
+    }
+}
@@ -0,0 +1,565 @@
+use convert_case::Casing;
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::{
+    parse_quote, parse_quote_spanned, spanned::Spanned, GenericParam, Generics, Ident, Lifetime,
+    LifetimeParam, Member, Path, Type, Visibility,
+};
+
+use crate::{
+    parse::{ContainerConfig, Field, Fields, Input, Layout},
+    util,
+};
+
+mod iter;
+mod ref_and_ref_mut;
+mod slice;
+mod slices;
+mod vec;
+
+pub(super) use iter::advance;
+pub use iter::IterKind;
+
+pub struct StructNames {
+    pub original: Ident,
+    pub vec: Ident,
+    pub shared_ref: Ident,
+    pub mut_ref: Ident,
+    pub iter: Ident,
+    pub iter_mut: Ident,
+    pub into_iter: Ident,
+    pub drain: Ident,
+    pub slices: Ident,
+    pub slices_mut: Ident,
+    pub slice: Ident,
+    pub slice_mut: Ident,
+    /// The fixed-capacity array, with `#[sov(array)]`
+    pub array: Ident,
+    pub array_into_iter: Ident,
+    /// The const parameter for the capacity of the array
+    pub capacity: Ident,
+    pub module: Ident,
+    pub lifetime: Lifetime,
+    /// The generics of the original type
+    pub generics: Generics,
+    /// The path to the `sov` crate
+    pub krate: Path,
+    /// How the columns are stored
+    pub layout: Layout,
+    /// The allocator parameter of the `Vec`-like type and its owning iterator, with
+    /// `#[sov(allocator)]`
+    pub allocator: Option<Ident>,
+}
+
+impl StructNames {
+    pub fn new(name: &Ident, generics: &Generics, config: &ContainerConfig) -> Self {
+        Self {
+            original: name.clone(),
+            vec: config
+                .vec
+                .clone()
+                .unwrap_or_else(|| format_ident!("Vec{name}")),
+            shared_ref: config
+                .shared_ref
+                .clone()
+                .unwrap_or_else(|| format_ident!("{name}Ref")),
+            mut_ref: config
+                .mut_ref
+                .clone()
+                .unwrap_or_else(|| format_ident!("{name}RefMut")),
+            iter: config
+                .iter
+                .clone()
+                .unwrap_or_else(|| format_ident!("{name}Iter")),
+            iter_mut: config
+                .iter_mut
+                .clone()
+                .unwrap_or_else(|| format_ident!("{name}IterMut")),
+            into_iter: config
+                .into_iter
+                .clone()
+                .unwrap_or_else(|| format_ident!("{name}IntoIter")),
+            drain: config
+                .drain
+                .clone()
+                .unwrap_or_else(|| format_ident!("{name}Drain")),
+            slices: config
+                .slices
+                .clone()
+                .unwrap_or_else(|| format_ident!("{name}Slices")),
+            slices_mut: config
+                .slices_mut
+                .clone()
+                .unwrap_or_else(|| format_ident!("{name}SlicesMut")),
+            slice: config
+                .slice
+                .clone()
+                .unwrap_or_else(|| format_ident!("{name}Slice")),
+            slice_mut: config
+                .slice_mut
+                .clone()
+                .unwrap_or_else(|| format_ident!("{name}SliceMut")),
+            array: config
+                .array_name
+                .clone()
+                .unwrap_or_else(|| format_ident!("Array{name}")),
+            array_into_iter: config
+                .array_into_iter
+                .clone()
+                .unwrap_or_else(|| format_ident!("{name}ArrayIntoIter")),
+            capacity: util::fresh_param(generics, "N"),
+            module: config.module.clone().unwrap_or_else(|| {
+                format_ident!(
+                    "__hidden_vec_{}",
+                    name.to_string().to_case(convert_case::Case::Snake),
+                    span = name.span()
+                )
+            }),
+            lifetime: util::fresh_lifetime(generics, "a"),
+            generics: generics.clone(),
+            krate: config.krate.clone().unwrap_or_else(|| parse_quote!(::sov)),
+            layout: config.layout.unwrap_or_default(),
+            allocator: config.allocator.then(|| util::fresh_param(generics, "A")),
+        }
+    }
+
+    /// The generics of the `Vec`-like type and its owning iterator: those of the original type,
+    /// followed by the allocator if there is one
+    pub fn vec_generics(&self) -> Generics {
+        self.with_allocator(self.generics.clone())
+    }
+
+    /// A copy of `generics` with the allocator appended, if there is one
+    pub fn with_allocator(&self, mut generics: Generics) -> Generics {
+        if let Some(allocator) = &self.allocator {
+            let krate = &self.krate;
+            generics.params.push(parse_quote! {
+                #allocator: #krate::__private::allocator_api2::alloc::Allocator + ::core::clone::Clone
+            });
+        }
+
+        generics
+    }
+
+    /// `self.vec_generics()`, with the allocator defaulting to the global allocator, for the
+    /// definitions of the `Vec`-like type and its owning iterator
+    pub fn vec_definition_generics(&self) -> Generics {
+        self.with_default_allocator(self.vec_generics())
+    }
+
+    /// A copy of `generics`, which ends with the allocator if there is one, with the allocator
+    /// defaulting to the global allocator
+    pub fn with_default_allocator(&self, mut generics: Generics) -> Generics {
+        if let Some(GenericParam::Type(allocator)) = generics.params.last_mut() {
+            if self.allocator.as_ref() == Some(&allocator.ident) {
+                let krate = &self.krate;
+                allocator.eq_token = Some(Default::default());
+                allocator.default =
+                    Some(parse_quote!(#krate::__private::allocator_api2::alloc::Global));
+            }
+        }
+
+        generics
+    }
+
+    /// The full type of the `Vec`-like type, including generics
+    pub fn vec_type(&self) -> TokenStream {
+        let name = &self.vec;
+        let generics = self.vec_generics();
+        let (_, ty_generics, _) = generics.split_for_impl();
+
+        quote!(#name #ty_generics)
+    }
+
+    /// The full type of the `Vec`-like type using the global allocator, which is the only one that
+    /// the constructors without an allocator argument, and the traits, are implemented for
+    pub fn global_vec_type(&self) -> TokenStream {
+        let name = &self.vec;
+        let krate = &self.krate;
+        let params = self.generics.params.iter().map(|param| match param {
+            GenericParam::Lifetime(param) => param.lifetime.to_token_stream(),
+            GenericParam::Type(param) => param.ident.to_token_stream(),
+            GenericParam::Const(param) => param.ident.to_token_stream(),
+        });
+        let allocator = self
+            .allocator
+            .as_ref()
+            .map(|_| quote!(#krate::__private::allocator_api2::alloc::Global));
+
+        quote!(#name<#(#params,)* #allocator>)
+    }
+
+    /// The generics of the original type, with `self.lifetime` prepended.
+    ///
+    /// Each type parameter is bounded by the lifetime: a flattened field's borrowing types are
+    /// only defined when the field's type outlives the borrow, which isn't implied for a generic
+    /// field type like `Inner<T>`
+    pub fn ref_generics(&self) -> Generics {
+        let lifetime = &self.lifetime;
+        let outlives = self.generics.type_params().map(|param| {
+            let ident = &param.ident;
+            parse_quote!(#ident: #lifetime)
+        });
+
+        let mut generics = util::with_bounds(&self.generics, outlives);
+        let lifetime = GenericParam::Lifetime(LifetimeParam::new(lifetime.clone()));
+        generics.params.insert(0, lifetime);
+        generics
+    }
+
+    /// The full type of the shared or mutable reference struct, borrowing for `self.lifetime`
+    pub fn ref_type(&self, mutable: bool) -> TokenStream {
+        self.ref_type_with_lifetime(mutable, &self.lifetime)
+    }
+
+    /// The full type of the shared or mutable reference struct, borrowing for `lifetime`
+    pub fn ref_type_with_lifetime(&self, mutable: bool, lifetime: &Lifetime) -> TokenStream {
+        let name = match mutable {
+            true => &self.mut_ref,
+            false => &self.shared_ref,
+        };
+
+        self.with_lifetime(name, lifetime)
+    }
+
+    /// The full type of the shared or mutable slice view, borrowing for `self.lifetime`
+    pub fn slice_type(&self, mutable: bool) -> TokenStream {
+        self.slice_type_with_lifetime(mutable, &self.lifetime)
+    }
+
+    /// The full type of the shared or mutable slice view, borrowing for `lifetime`
+    pub fn slice_type_with_lifetime(&self, mutable: bool, lifetime: &Lifetime) -> TokenStream {
+        let name = match mutable {
+            true => &self.slice_mut,
+            false => &self.slice,
+        };
+
+        self.with_lifetime(name, lifetime)
+    }
+
+    /// `#name<#lifetime, ...>`, for one of the generated types that borrows for `lifetime`
+    pub fn with_lifetime(&self, name: &Ident, lifetime: &Lifetime) -> TokenStream {
+        let mut generics = self.generics.clone();
+        let lifetime = GenericParam::Lifetime(LifetimeParam::new(lifetime.clone()));
+        generics.params.insert(0, lifetime);
+        let (_, ty_generics, _) = generics.split_for_impl();
+
+        quote!(#name #ty_generics)
+    }
+
+    /// The type of a field in the shared or mutable reference struct
+    pub fn ref_field_type(&self, field: &Field, mutable: bool) -> TokenStream {
+        let ty = &field.ty;
+        let lifetime = &self.lifetime;
+
+        match (field.config.flatten, mutable) {
+            (true, true) => self.nested_type(field, "RefMut"),
+            (true, false) => self.nested_type(field, "Ref"),
+            (false, true) => quote!(& #lifetime mut #ty),
+            (false, false) => quote!(& #lifetime #ty),
+        }
+    }
+
+    /// One of the types that store a flattened field or borrow its rows, named after the
+    /// associated type of `StructOfVecs` it comes from, or of `SovVec` for the iterators, and
+    /// borrowing for `self.lifetime`.
+    ///
+    /// Dictionary fields are stored in a `DictionaryVec<T>`, but the field's own type doesn't
+    /// implement `StructOfVecs` with it, so its types are named directly
+    pub fn nested_type(&self, field: &Field, assoc: &str) -> TokenStream {
+        let ty = &field.ty;
+        let krate = &self.krate;
+        let lifetime = &self.lifetime;
+
+        if field.config.dictionary {
+            return match assoc {
+                "Vec" => quote!(#krate::DictionaryVec<#ty>),
+                "Ref" => quote!(& #lifetime #ty),
+                "RefMut" => quote!(#krate::DictionaryMut<#lifetime, #ty>),
+                "Slice" | "Slices" => quote!(#krate::DictionarySlice<#lifetime, #ty>),
+                "SliceMut" | "SlicesMut" => quote!(#krate::DictionarySliceMut<#lifetime, #ty>),
+                "Iter" => quote!(#krate::DictionaryIter<#lifetime, #ty>),
+                "IterMut" => quote!(#krate::DictionaryIterMut<#lifetime, #ty>),
+                "IntoIter" => quote!(#krate::DictionaryIntoIter<#ty>),
+                _ => unreachable!("unknown nested type `{assoc}`"),
+            };
+        }
+
+        let name = Ident::new(assoc, Span::call_site());
+        let vec = quote!(<#ty as #krate::StructOfVecs>::Vec);
+
+        match assoc {
+            "Vec" => vec,
+            "Iter" | "IterMut" => quote!(<#vec as #krate::SovVec>::#name<#lifetime>),
+            "IntoIter" => quote!(<#vec as ::core::iter::IntoIterator>::IntoIter),
+            _ => quote!(<#ty as #krate::StructOfVecs>::#name<#lifetime>),
+        }
+    }
+
+    /// A call to one of the methods of `SovVec` on the column of a flattened field, e.g.
+    /// `SovVec::iter(&self.x)`, which is called through the trait since the nested vec may have
+    /// been renamed. Dictionary columns aren't a `SovVec`, so their inherent method is called
+    pub fn nested_call(&self, field: &Field, column: TokenStream, method: &str) -> TokenStream {
+        let krate = &self.krate;
+        let fn_name = Ident::new(method, Span::call_site());
+        let receiver = match method {
+            "iter_mut" | "as_mut_slice" | "as_mut_slices" => quote!(&mut #column),
+            _ => quote!(&#column),
+        };
+
+        match field.config.dictionary {
+            true => quote!(#column.#fn_name()),
+            false => quote!(#krate::SovVec::#fn_name(#receiver)),
+        }
+    }
+
+    /// The full type of the struct of shared or mutable slices, borrowing for `self.lifetime`
+    pub fn slices_type(&self, mutable: bool) -> TokenStream {
+        let name = match mutable {
+            true => &self.slices_mut,
+            false => &self.slices,
+        };
+        let generics = self.ref_generics();
+        let (_, ty_generics, _) = generics.split_for_impl();
+
+        quote!(#name #ty_generics)
+    }
+
+    /// The type of the column that a field is stored in
+    pub fn column_type(&self, field: &Field) -> Type {
+        let ty = &field.ty;
+        let krate = &self.krate;
+
+        match (field.config.flatten, self.layout) {
+            (true, _) => {
+                let vec = self.nested_type(field, "Vec");
+                parse_quote!(#vec)
+            }
+            (false, Layout::Separate) => match (&field.config.column, &self.allocator) {
+                (Some(column), _) => column.clone(),
+                (None, Some(allocator)) => {
+                    parse_quote!(#krate::__private::allocator_api2::vec::Vec<#ty, #allocator>)
+                }
+                (None, None) => parse_quote!(#krate::__private::Vec<#ty>),
+            },
+            (false, Layout::Single) => parse_quote!(#krate::__private::SingleColumn<#ty>),
+        }
+    }
+
+    /// The type of the column that a field is stored in, as a qualified path that its associated
+    /// functions can be called on. Custom columns are called through the `Column` trait
+    pub fn column_path(&self, field: &Field) -> TokenStream {
+        let column_type = self.column_type(field);
+
+        // spanned to the attribute, so an error about a type that isn't a column points there
+        match &field.config.column {
+            Some(column) => {
+                let ty = &field.ty;
+                let krate = &self.krate;
+                quote_spanned!(column.span() => <#column_type as #krate::Column<#ty>>)
+            }
+            None => quote!(<#column_type>),
+        }
+    }
+
+    /// A call to `method` on the column of a field, e.g. `self.x.push(value)`. Custom columns are
+    /// called through the `Column` trait, rather than any inherent methods with the same names
+    pub fn column_call(
+        &self,
+        field: &Field,
+        column: TokenStream,
+        method: &str,
+        args: TokenStream,
+    ) -> TokenStream {
+        let fn_name = Ident::new(method, Span::call_site());
+
+        if field.config.column.is_none() {
+            return quote!(#column.#fn_name(#args));
+        }
+
+        let path = self.column_path(field);
+        let receiver = match method {
+            "len" | "capacity" | "as_slice" => quote!(&#column),
+            _ => quote!(&mut #column),
+        };
+
+        quote!(#path::#fn_name(#receiver, #args))
+    }
+
+    /// The column of a field as an expression that dereferences to a slice, so it can be indexed
+    /// or iterated
+    pub fn column_slice(&self, field: &Field, column: TokenStream, mutable: bool) -> TokenStream {
+        match (&field.config.column, mutable) {
+            (None, _) => column,
+            (Some(_), true) => self.column_call(field, column, "as_mut_slice", quote!()),
+            (Some(_), false) => self.column_call(field, column, "as_slice", quote!()),
+        }
+    }
+
+    /// The type that owns the memory of every column, with `#[sov(layout = "single")]`
+    pub fn allocation_type(&self) -> Type {
+        let krate = &self.krate;
+        parse_quote!(#krate::__private::Allocation)
+    }
+
+    /// The type of the iterator over a single column, when iterating in the given way
+    pub fn column_iter_type(&self, field: &Field, kind: IterKind) -> Type {
+        let ty = &field.ty;
+        let krate = &self.krate;
+        let lifetime = &self.lifetime;
+
+        match (field.config.flatten, kind) {
+            (true, kind) => {
+                let iter = self.nested_type(
+                    field,
+                    match kind {
+                        IterKind::Shared => "Iter",
+                        IterKind::Mutable => "IterMut",
+                        IterKind::Owned => "IntoIter",
+                    },
+                );
+                parse_quote!(#iter)
+            }
+            (false, IterKind::Owned) if field.config.column.is_some() => {
+                let column_type = self.column_type(field);
+                parse_quote_spanned!(column_type.span() => <#column_type as ::core::iter::IntoIterator>::IntoIter)
+            }
+            (false, IterKind::Owned) if self.layout == Layout::Single => {
+                parse_quote!(#krate::__private::SingleColumnIntoIter<#ty>)
+            }
+            (false, IterKind::Owned) if self.allocator.is_some() => {
+                let allocator = &self.allocator;
+                parse_quote!(#krate::__private::allocator_api2::vec::IntoIter<#ty, #allocator>)
+            }
+            (false, kind) => kind.column_iter_type(ty, lifetime, krate),
+        }
+    }
+
+    /// The name of the iterator type for the given kind of iteration
+    pub fn iter_name(&self, kind: IterKind) -> &Ident {
+        match kind {
+            IterKind::Shared => &self.iter,
+            IterKind::Mutable => &self.iter_mut,
+            IterKind::Owned => &self.into_iter,
+        }
+    }
+
+    /// The generics of the iterator type: iterators over references borrow for `self.lifetime`
+    pub fn iter_generics(&self, kind: IterKind) -> Generics {
+        match kind {
+            IterKind::Shared | IterKind::Mutable => self.ref_generics(),
+            IterKind::Owned => self.vec_generics(),
+        }
+    }
+
+    /// The full type of the iterator, including generics
+    pub fn iter_type(&self, kind: IterKind) -> TokenStream {
+        let name = self.iter_name(kind);
+        let generics = self.iter_generics(kind);
+        let (_, ty_generics, _) = generics.split_for_impl();
+
+        quote!(#name #ty_generics)
+    }
+
+    /// The full type of the items yielded by the iterator
+    pub fn iter_item_type(&self, kind: IterKind) -> TokenStream {
+        match kind {
+            IterKind::Shared => self.ref_type(false),
+            IterKind::Mutable => self.ref_type(true),
+            IterKind::Owned => self.original_type(),
+        }
+    }
+
+    /// The generics of the original type, with the capacity of the array appended
+    pub fn array_generics(&self) -> Generics {
+        let mut generics = self.generics.clone();
+        let capacity = &self.capacity;
+        generics
+            .params
+            .push(parse_quote!(const #capacity: ::core::primitive::usize));
+        generics
+    }
+
+    /// The full type of the original type, including generics
+    pub fn original_type(&self) -> TokenStream {
+        let name = &self.original;
+        let (_, ty_generics, _) = self.generics.split_for_impl();
+
+        quote!(#name #ty_generics)
+    }
+}
+
+pub(super) struct GeneratedStructs {
+    pub vec: TokenStream,
+    pub ref_and_ref_mut: TokenStream,
+    pub slices: TokenStream,
+    pub slice: TokenStream,
+    pub iters: TokenStream,
+    pub names: StructNames,
+}
+
+pub(super) fn codegen_structs(input: &Input) -> GeneratedStructs {
+    let names = StructNames::new(&input.name, &input.generics, &input.config);
+
+    let vec = vec::generate_struct(input, &names);
+    let ref_and_ref_mut = ref_and_ref_mut::generate_structs(input, &names);
+    let slices = slices::generate_structs(input, &names);
+    let slice = slice::generate_structs(input, &names);
+    let iters = iter::generate_iters(input, &names);
+
+    GeneratedStructs {
+        vec,
+        ref_and_ref_mut,
+        slices,
+        slice,
+        iters,
+        names,
+    }
+}
+
+/// The fields of one of the generated structs, followed by the allocation if the columns are stored
+/// in one, wrapped in braces or parentheses to match the original struct
+fn fields_with_allocation(
+    input: &Input,
+    names: &StructNames,
+    fields: impl IntoIterator<Item = TokenStream>,
+) -> TokenStream {
+    let allocation_type = names.allocation_type();
+    let allocation = input.allocation_member().map(|member| match member {
+        Member::Named(name) => quote!(#name: #allocation_type),
+        Member::Unnamed(_) => quote!(#allocation_type),
+    });
+    let fields = fields.into_iter().chain(allocation);
+
+    match &input.fields {
+        Fields::Named(_) => quote!({ #(#fields,)* }),
+        Fields::Unnamed(_) => quote!(( #(#fields,)* )),
+        Fields::Unit => quote!(),
+    }
+}
+
+/// Emit a struct definition with the given generics.
+///
+/// The where clause goes in a different place depending on whether the struct has named fields, so
+/// this can't just be a `quote!`
+pub(super) fn define_struct(
+    input: &Input,
+    vis: &Visibility,
+    name: &Ident,
+    generics: &Generics,
+    fields: TokenStream,
+) -> TokenStream {
+    let where_clause = &generics.where_clause;
+
+    match &input.fields {
+        Fields::Named(_) => quote! {
+            #vis struct #name #generics #where_clause #fields
+        },
+        Fields::Unnamed(_) => quote! {
+            #vis struct #name #generics #fields #where_clause;
+        },
+        Fields::Unit => quote! {
+            #vis struct #name #generics #where_clause;
+        },
+    }
+}
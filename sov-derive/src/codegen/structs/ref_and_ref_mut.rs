@@ -0,0 +1,248 @@
+//! The reference types, which hold a reference to a field in every column, along with the methods
+//! that every reference type has regardless of which traits the fields implement
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse_quote;
+
+use crate::{
+    parse::{Field, Input},
+    util,
+};
+
+use super::{define_struct, StructNames};
+
+pub(super) fn generate_structs(input: &Input, names: &StructNames) -> TokenStream {
+    let shared = generate(input, names, false);
+    let mutable = generate(input, names, true);
+    let copy = copy_impls(names);
+    let mut_methods = mut_methods(input, names);
+
+    quote! {
+        #shared
+        #mutable
+        #copy
+        #mut_methods
+    }
+}
+
+fn generate(input: &Input, names: &StructNames, mutable: bool) -> TokenStream {
+    let name = match mutable {
+        true => &names.mut_ref,
+        false => &names.shared_ref,
+    };
+
+    let lifetime = &names.lifetime;
+
+    if input.fields.is_empty() {
+        let generics = names.ref_generics();
+        let where_clause = &generics.where_clause;
+
+        return quote! {
+            pub struct #name #generics #where_clause {
+                _marker: ::core::marker::PhantomData<& #lifetime ()>,
+            }
+        };
+    }
+
+    let field_type = |field: &Field| names.ref_field_type(field, mutable);
+
+    let fields = input.map_fields_with_delimiters(
+        |field| {
+            let vis = &field.vis;
+            let name = field.ident.as_ref().unwrap();
+            let ty = field_type(field);
+
+            quote! { #vis #name: #ty }
+        },
+        |field, _index| {
+            let vis = &field.vis;
+            let ty = field_type(field);
+
+            quote! { #vis #ty }
+        },
+    );
+
+    let definition = define_struct(
+        input,
+        &parse_quote!(pub),
+        name,
+        &names.ref_generics(),
+        fields,
+    );
+
+    // the user didn't write these structs, so they shouldn't be warned about fields they never read
+    quote! {
+        #[allow(dead_code)]
+        #definition
+    }
+}
+
+/// `FooRef` only holds shared references, so it's `Copy` just like a `&Foo` would be. Flattened
+/// fields are the nested type's `FooRef`, which `StructOfVecs` requires to be `Copy` too
+fn copy_impls(names: &StructNames) -> TokenStream {
+    let name = &names.shared_ref;
+    let generics = names.ref_generics();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics ::core::clone::Clone for #name #ty_generics #where_clause {
+            #[inline]
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl #impl_generics ::core::marker::Copy for #name #ty_generics #where_clause {}
+    }
+}
+
+/// Methods for `FooRefMut`: reborrowing it, and overwriting the whole row it points to
+fn mut_methods(input: &Input, names: &StructNames) -> TokenStream {
+    let name = &names.mut_ref;
+    let shared_ref = &names.shared_ref;
+    let original = &names.original;
+    let original_type = names.original_type();
+    let generics = names.ref_generics();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let reborrow_lifetime = util::fresh_lifetime(&generics, "b");
+    let shared_ref_type = names.ref_type_with_lifetime(false, &reborrow_lifetime);
+    let mut_ref_type = names.ref_type_with_lifetime(true, &reborrow_lifetime);
+
+    let members = input.fields.members();
+    let flattened: Vec<_> = input
+        .fields
+        .iter()
+        .map(|field| field.config.flatten)
+        .collect();
+
+    // a dictionary field can only be pointed at a value that is already in its dictionary, so that
+    // is checked for every field, including those of flattened structs, before any are written.
+    // Bit-packed and nullable fields can always be set
+    let checks: Vec<_> = input
+        .fields
+        .iter()
+        .zip(&members)
+        .filter_map(|(field, member)| {
+            let config = &field.config;
+
+            if config.dictionary {
+                Some(quote!(self.#member.contains(&value.#member)))
+            } else if config.flatten && !config.bitpacked && !config.nullable {
+                Some(quote!(self.#member.can_set(&value.#member)))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let (can_set, check, panics) = match checks.is_empty() {
+        true => (quote!(let _ = value; true), quote!(), quote!()),
+        false => (
+            quote!(#(#checks)&&*),
+            quote! {
+                ::core::assert!(
+                    self.can_set(&value),
+                    "a dictionary field's value is not in its dictionary, so it must be interned first",
+                );
+            },
+            quote! {
+                ///
+                /// Panics if the value of a dictionary field isn't in its dictionary yet, without
+                /// changing any field of the row
+            },
+        ),
+    };
+
+    // flattened fields are the nested type's `FooRefMut`, which has the same methods
+    let (as_ref, reborrow, set, replace) = match input.fields.is_empty() {
+        true => (
+            quote!(#shared_ref { _marker: ::core::marker::PhantomData }),
+            quote!(#name { _marker: ::core::marker::PhantomData }),
+            quote!(let _ = value;),
+            quote!(value),
+        ),
+        false => {
+            let field_as_ref =
+                members
+                    .iter()
+                    .zip(&flattened)
+                    .map(|(member, flatten)| match flatten {
+                        true => quote!(self.#member.as_ref()),
+                        false => quote!(&*self.#member),
+                    });
+            let field_reborrow =
+                members
+                    .iter()
+                    .zip(&flattened)
+                    .map(|(member, flatten)| match flatten {
+                        true => quote!(self.#member.reborrow()),
+                        false => quote!(&mut *self.#member),
+                    });
+            let field_set = members
+                .iter()
+                .zip(&flattened)
+                .map(|(member, flatten)| match flatten {
+                    true => quote!(self.#member.set(value.#member);),
+                    false => quote!(*self.#member = value.#member;),
+                });
+            let field_replace =
+                members
+                    .iter()
+                    .zip(&flattened)
+                    .map(|(member, flatten)| match flatten {
+                        true => quote!(self.#member.replace(value.#member)),
+                        false => quote!(::core::mem::replace(self.#member, value.#member)),
+                    });
+
+            (
+                quote!(#shared_ref { #(#members: #field_as_ref,)* }),
+                quote!(#name { #(#members: #field_reborrow,)* }),
+                quote!(#(#field_set)*),
+                quote!(#original { #(#members: #field_replace,)* }),
+            )
+        }
+    };
+
+    quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Borrow the row immutably, for as long as this reference is borrowed
+            #[inline]
+            pub fn as_ref<#reborrow_lifetime>(& #reborrow_lifetime self) -> #shared_ref_type {
+                #as_ref
+            }
+
+            /// Borrow the row mutably for a shorter lifetime, so that this reference can be used
+            /// again afterwards
+            #[inline]
+            pub fn reborrow<#reborrow_lifetime>(& #reborrow_lifetime mut self) -> #mut_ref_type {
+                #reborrow
+            }
+
+            /// Overwrite every field of the row with the fields of `value`
+            #panics
+            #[inline]
+            pub fn set(&mut self, value: #original_type) {
+                #check
+                #set
+            }
+
+            /// Overwrite every field of the row with the fields of `value`, and return the old
+            /// value of the row
+            #panics
+            #[inline]
+            pub fn replace(&mut self, value: #original_type) -> #original_type {
+                #check
+                #replace
+            }
+
+            /// Whether `set` and `replace` can overwrite the row with `value`, which they can't
+            /// if the value of a dictionary field isn't in its dictionary
+            #[doc(hidden)]
+            #[inline]
+            pub fn can_set(&self, value: &#original_type) -> ::core::primitive::bool {
+                #can_set
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! The slice views, `FooSlice` and `FooSliceMut`, which hold a slice of every column (or the nested
+//! view, for flattened fields). The columns all have the same length, so slicing or splitting a
+//! view slices every column in the same place
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Type, Visibility};
+
+use crate::{
+    parse::{Field, Input},
+    util,
+};
+
+use super::{define_struct, StructNames};
+
+pub(super) fn generate_structs(input: &Input, names: &StructNames) -> TokenStream {
+    let shared = generate(input, names, false);
+    let mutable = generate(input, names, true);
+
+    quote! {
+        #shared
+        #mutable
+    }
+}
+
+fn generate(input: &Input, names: &StructNames, mutable: bool) -> TokenStream {
+    let name = match mutable {
+        true => &names.slice_mut,
+        false => &names.slice,
+    };
+    // this is defined in the hidden module, but is only as visible as the original struct
+    let vis = util::hidden_module_vis(&input.vis);
+    let generics = names.ref_generics();
+    let lifetime = &names.lifetime;
+
+    // there are no columns to get the length from, so it has to be stored separately
+    if input.fields.is_empty() {
+        let where_clause = &generics.where_clause;
+        let marker = match mutable {
+            true => quote!(& #lifetime mut ()),
+            false => quote!(& #lifetime ()),
+        };
+
+        return quote! {
+            #vis struct #name #generics #where_clause {
+                len: ::core::primitive::usize,
+                _marker: ::core::marker::PhantomData<#marker>,
+            }
+        };
+    }
+
+    let map_field = |field: &Field| {
+        let field = syn::Field {
+            vis: Visibility::Inherited,
+            ty: column_type(names, field, mutable),
+            ..field.field.clone()
+        };
+
+        quote!(#field)
+    };
+
+    let fields = input.map_fields_with_delimiters(map_field, |field, _index| map_field(field));
+
+    define_struct(input, &vis, name, &generics, fields)
+}
+
+/// The type of a single column in the view
+fn column_type(names: &StructNames, field: &Field, mutable: bool) -> Type {
+    let ty = &field.ty;
+    let lifetime = &names.lifetime;
+
+    match (field.config.flatten, mutable) {
+        (true, true) => {
+            let slice = names.nested_type(field, "SliceMut");
+            parse_quote!(#slice)
+        }
+        (true, false) => {
+            let slice = names.nested_type(field, "Slice");
+            parse_quote!(#slice)
+        }
+        (false, true) => parse_quote!(& #lifetime mut [#ty]),
+        (false, false) => parse_quote!(& #lifetime [#ty]),
+    }
+}
@@ -0,0 +1,82 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse_quote;
+
+use crate::parse::{Field, Input};
+
+use super::{define_struct, StructNames};
+
+pub(super) fn generate_structs(input: &Input, names: &StructNames) -> TokenStream {
+    let shared = generate(input, names, false);
+    let mutable = generate(input, names, true);
+
+    quote! {
+        #shared
+        #mutable
+    }
+}
+
+fn generate(input: &Input, names: &StructNames, mutable: bool) -> TokenStream {
+    let name = match mutable {
+        true => &names.slices_mut,
+        false => &names.slices,
+    };
+
+    let maybe_mut = match mutable {
+        true => quote! { mut },
+        false => quote! {},
+    };
+
+    let lifetime = &names.lifetime;
+
+    if input.fields.is_empty() {
+        let generics = names.ref_generics();
+        let where_clause = &generics.where_clause;
+
+        return quote! {
+            pub struct #name #generics #where_clause {
+                _marker: ::core::marker::PhantomData<& #lifetime ()>,
+            }
+        };
+    }
+
+    let field_type = |field: &Field| {
+        let ty = &field.ty;
+
+        match (field.config.flatten, mutable) {
+            (true, true) => names.nested_type(field, "SlicesMut"),
+            (true, false) => names.nested_type(field, "Slices"),
+            (false, _) => quote!(& #lifetime #maybe_mut [#ty]),
+        }
+    };
+
+    let fields = input.map_fields_with_delimiters(
+        |field| {
+            let vis = &field.vis;
+            let name = field.ident.as_ref().unwrap();
+            let ty = field_type(field);
+
+            quote! { #vis #name: #ty }
+        },
+        |field, _index| {
+            let vis = &field.vis;
+            let ty = field_type(field);
+
+            quote! { #vis #ty }
+        },
+    );
+
+    let definition = define_struct(
+        input,
+        &parse_quote!(pub),
+        name,
+        &names.ref_generics(),
+        fields,
+    );
+
+    // the user didn't write these structs, so they shouldn't be warned about fields they never read
+    quote! {
+        #[allow(dead_code)]
+        #definition
+    }
+}
@@ -0,0 +1,55 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Visibility;
+
+use crate::{
+    parse::{Field, Input},
+    util,
+};
+
+use super::{define_struct, fields_with_allocation, StructNames};
+
+pub(super) fn generate_struct(input: &Input, names: &StructNames) -> TokenStream {
+    let name = &names.vec;
+    // this is defined in the hidden module, but is only as visible as the original struct
+    let vis = util::hidden_module_vis(&input.vis);
+
+    // there are no columns to get the length from, so it has to be stored separately
+    if input.fields.is_empty() {
+        let generics = &input.generics;
+        let where_clause = &generics.where_clause;
+
+        return quote! {
+            #vis struct #name #generics #where_clause {
+                len: ::core::primitive::usize,
+            }
+        };
+    }
+
+    // the columns must all have the same length, so by default they can only be modified through
+    // the generated methods
+    let map_field = |field: &Field| {
+        let vis = match input.config.expose_columns {
+            true => util::hidden_module_vis(&field.vis),
+            false => Visibility::Inherited,
+        };
+
+        let field = syn::Field {
+            vis,
+            ty: names.column_type(field),
+            ..field.field.clone()
+        };
+
+        quote!(#field)
+    };
+
+    let struct_fields = fields_with_allocation(input, names, input.fields.iter().map(map_field));
+
+    define_struct(
+        input,
+        &vis,
+        name,
+        &names.vec_definition_generics(),
+        struct_fields,
+    )
+}
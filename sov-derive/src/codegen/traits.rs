@@ -0,0 +1,162 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use super::structs::{IterKind, StructNames};
+
+/// Implement `StructOfVecs` for the original type, and `SovVec` for the `Vec`-like type, by
+/// forwarding to the inherent methods
+pub(super) fn codegen(names: &StructNames) -> TokenStream {
+    let krate = &names.krate;
+    let vec_type = names.global_vec_type();
+    let lifetime = &names.lifetime;
+    let original_type = names.original_type();
+    let shared_ref_type = names.ref_type(false);
+    let mut_ref_type = names.ref_type(true);
+    let slices_type = names.slices_type(false);
+    let slices_mut_type = names.slices_type(true);
+    let slice_type = names.slice_type(false);
+    let slice_mut_type = names.slice_type(true);
+    let iter_type = names.iter_type(IterKind::Shared);
+    let iter_mut_type = names.iter_type(IterKind::Mutable);
+    let (impl_generics, _, where_clause) = names.generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #krate::StructOfVecs for #original_type #where_clause {
+            type Vec = #vec_type;
+            type Ref<#lifetime> = #shared_ref_type where Self: #lifetime;
+            type RefMut<#lifetime> = #mut_ref_type where Self: #lifetime;
+            type Slices<#lifetime> = #slices_type where Self: #lifetime;
+            type SlicesMut<#lifetime> = #slices_mut_type where Self: #lifetime;
+            type Slice<#lifetime> = #slice_type where Self: #lifetime;
+            type SliceMut<#lifetime> = #slice_mut_type where Self: #lifetime;
+        }
+
+        impl #impl_generics #krate::SovVec for #vec_type #where_clause {
+            type Iter<#lifetime> = #iter_type where Self: #lifetime;
+            type IterMut<#lifetime> = #iter_mut_type where Self: #lifetime;
+
+            #[inline]
+            fn new() -> Self {
+                Self::new()
+            }
+
+            #[inline]
+            fn with_capacity(capacity: ::core::primitive::usize) -> Self {
+                Self::with_capacity(capacity)
+            }
+
+            #[inline]
+            fn len(&self) -> ::core::primitive::usize {
+                Self::len(self)
+            }
+
+            #[inline]
+            fn capacity(&self) -> ::core::primitive::usize {
+                Self::capacity(self)
+            }
+
+            #[inline]
+            fn reserve(&mut self, additional: ::core::primitive::usize) {
+                Self::reserve(self, additional)
+            }
+
+            #[inline]
+            fn reserve_exact(&mut self, additional: ::core::primitive::usize) {
+                Self::reserve_exact(self, additional)
+            }
+
+            #[inline]
+            fn shrink_to_fit(&mut self) {
+                Self::shrink_to_fit(self)
+            }
+
+            #[inline]
+            fn shrink_to(&mut self, min_capacity: ::core::primitive::usize) {
+                Self::shrink_to(self, min_capacity)
+            }
+
+            #[inline]
+            fn truncate(&mut self, len: ::core::primitive::usize) {
+                Self::truncate(self, len)
+            }
+
+            #[inline]
+            fn clear(&mut self) {
+                Self::clear(self)
+            }
+
+            #[inline]
+            fn resize_with<F>(&mut self, new_len: ::core::primitive::usize, f: F)
+            where
+                F: ::core::ops::FnMut() -> Self::Item,
+            {
+                Self::resize_with(self, new_len, f)
+            }
+
+            #[inline]
+            fn get(&self, index: ::core::primitive::usize) -> <Self::Item as #krate::StructOfVecs>::Ref<'_> {
+                Self::get(self, index)
+            }
+
+            #[inline]
+            fn get_mut(&mut self, index: ::core::primitive::usize) -> <Self::Item as #krate::StructOfVecs>::RefMut<'_> {
+                Self::get_mut(self, index)
+            }
+
+            #[inline]
+            fn as_slices(&self) -> <Self::Item as #krate::StructOfVecs>::Slices<'_> {
+                Self::as_slices(self)
+            }
+
+            #[inline]
+            fn as_mut_slices(&mut self) -> <Self::Item as #krate::StructOfVecs>::SlicesMut<'_> {
+                Self::as_mut_slices(self)
+            }
+
+            #[inline]
+            fn as_slice(&self) -> <Self::Item as #krate::StructOfVecs>::Slice<'_> {
+                Self::as_slice(self)
+            }
+
+            #[inline]
+            fn as_mut_slice(&mut self) -> <Self::Item as #krate::StructOfVecs>::SliceMut<'_> {
+                Self::as_mut_slice(self)
+            }
+
+            #[inline]
+            fn iter(&self) -> Self::Iter<'_> {
+                Self::iter(self)
+            }
+
+            #[inline]
+            fn iter_mut(&mut self) -> Self::IterMut<'_> {
+                Self::iter_mut(self)
+            }
+
+            #[inline]
+            fn push(&mut self, value: Self::Item) {
+                Self::push(self, value)
+            }
+
+            #[inline]
+            fn insert(&mut self, index: ::core::primitive::usize, value: Self::Item) {
+                Self::insert(self, index, value)
+            }
+
+            #[inline]
+            fn pop(&mut self) -> ::core::option::Option<Self::Item> {
+                Self::pop(self)
+            }
+
+            #[inline]
+            fn remove(&mut self, index: ::core::primitive::usize) -> Self::Item {
+                Self::remove(self, index)
+            }
+
+            #[inline]
+            fn swap_remove(&mut self, index: ::core::primitive::usize) -> Self::Item {
+                Self::swap_remove(self, index)
+            }
+        }
+    }
+}
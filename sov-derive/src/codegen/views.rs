@@ -0,0 +1,591 @@
+//! Methods and trait impls for the slice views, `FooSlice` and `FooSliceMut`, which borrow a range
+//! of rows like a `&[Foo]` or `&mut [Foo]` would.
+//!
+//! Most methods can be written in terms of a handful of primitives (`len`, `get`, `split_at`,
+//! etc.), so they are shared by structs and enums, and only the primitives depend on how the rows
+//! are stored
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Ident, Member};
+
+use crate::{parse::Input, util};
+
+use super::structs::{IterKind, StructNames};
+
+/// Expressions for the methods that depend on how the rows are stored
+pub(super) struct ViewPrimitives {
+    /// The number of rows in `self`, which is either view
+    pub len: TokenStream,
+    /// The `FooRef` to the row at `index` in the `FooSlice` in `self`, panicking if it is out of
+    /// bounds
+    pub get: TokenStream,
+    /// The `FooRefMut` to the row at `index` in the `FooSliceMut` in `self`, panicking if it is out
+    /// of bounds
+    pub get_mut: TokenStream,
+    /// A `FooSlice` borrowing the `FooSliceMut` in `self`
+    pub as_slice: TokenStream,
+    /// A shorter-lived `FooSliceMut` borrowing the `FooSliceMut` in `self`
+    pub reborrow: TokenStream,
+    /// The two halves of the `FooSlice` in `self` before and after `mid`, panicking if `mid > len`
+    pub split_at: TokenStream,
+    /// The two halves of the `FooSliceMut` in `self` before and after `mid`, panicking if
+    /// `mid > len`
+    pub split_at_mut: TokenStream,
+    /// The `FooIter` over the `FooSlice` in `self`
+    pub into_iter: TokenStream,
+    /// The `FooIterMut` over the `FooSliceMut` in `self`
+    pub into_iter_mut: TokenStream,
+    /// The `FooSlices` of the `FooSlice` in `self`
+    pub as_slices: TokenStream,
+    /// The `FooSlicesMut` of the `FooSliceMut` in `self`
+    pub as_mut_slices: TokenStream,
+}
+
+impl ViewPrimitives {
+    /// Slice every column in the same way, or the nested view for flattened fields. Structs
+    /// without fields only have a length, which is all the primitives need to update
+    pub(super) fn for_struct(input: &Input, names: &StructNames) -> Self {
+        let krate = &names.krate;
+        let slice = &names.slice;
+        let slice_mut = &names.slice_mut;
+        let marker = quote!(_marker: ::core::marker::PhantomData);
+
+        // `#name { #member: #column, ... }`, or `#name { #empty }` without any fields
+        let build =
+            |name: &Ident, column: &dyn Fn(&Member, bool) -> TokenStream, empty: TokenStream| {
+                if input.fields.is_empty() {
+                    return quote!(#name { #empty });
+                }
+
+                let members = input.fields.members();
+                let columns = input
+                    .fields
+                    .iter()
+                    .zip(&members)
+                    .map(|(field, member)| column(member, field.config.flatten));
+
+                quote!(#name { #(#members: #columns,)* })
+            };
+
+        let len = match input.fields.iter().zip(input.fields.members()).next() {
+            Some((field, member)) if field.config.flatten => {
+                quote!(#krate::SovSlice::len(&self.#member))
+            }
+            Some((_, member)) => quote!(self.#member.len()),
+            None => quote!(self.len),
+        };
+
+        let (get, get_mut) = match input.fields.is_empty() {
+            true => {
+                let shared_ref = &names.shared_ref;
+                let mut_ref = &names.mut_ref;
+
+                (
+                    quote!({
+                        ::core::assert!(
+                            index < self.len,
+                            "index out of bounds: the len is {} but the index is {index}",
+                            self.len,
+                        );
+                        #shared_ref { #marker }
+                    }),
+                    quote!({
+                        let _ = self.get(index);
+                        #mut_ref { #marker }
+                    }),
+                )
+            }
+            false => (
+                build(
+                    &names.shared_ref,
+                    &|member, flatten| match flatten {
+                        true => quote!(self.#member.get(index)),
+                        false => quote!(&self.#member[index]),
+                    },
+                    quote!(),
+                ),
+                build(
+                    &names.mut_ref,
+                    &|member, flatten| match flatten {
+                        true => quote!(self.#member.get_mut(index)),
+                        false => quote!(&mut self.#member[index]),
+                    },
+                    quote!(),
+                ),
+            ),
+        };
+
+        let split = |name: &Ident, mutable: bool| {
+            if input.fields.is_empty() {
+                return quote!({
+                    ::core::assert!(mid <= self.len, "mid > len");
+
+                    (
+                        #name { len: mid, #marker },
+                        #name { len: self.len - mid, #marker },
+                    )
+                });
+            }
+
+            let members = input.fields.members();
+            let bindings: Vec<_> = (0..members.len()).map(util::nth_field).collect();
+            let splits = input.fields.iter().zip(&members).map(|(field, member)| {
+                match (field.config.flatten, mutable) {
+                    (true, _) => quote!(#krate::SovSlice::split_at(self.#member, mid)),
+                    (false, true) => quote!(self.#member.split_at_mut(mid)),
+                    (false, false) => quote!(self.#member.split_at(mid)),
+                }
+            });
+
+            quote!({
+                #(let #bindings = #splits;)*
+
+                (
+                    #name { #(#members: #bindings.0,)* },
+                    #name { #(#members: #bindings.1,)* },
+                )
+            })
+        };
+
+        let into_iter = |kind: IterKind| {
+            build(
+                names.iter_name(kind),
+                &|member, _| quote!(::core::iter::IntoIterator::into_iter(self.#member)),
+                quote!(range: 0..self.len, #marker),
+            )
+        };
+
+        Self {
+            len,
+            get,
+            get_mut,
+            as_slice: build(
+                slice,
+                &|member, flatten| match flatten {
+                    true => quote!(self.#member.as_slice()),
+                    false => quote!(&*self.#member),
+                },
+                quote!(len: self.len, #marker),
+            ),
+            reborrow: build(
+                slice_mut,
+                &|member, flatten| match flatten {
+                    true => quote!(self.#member.reborrow()),
+                    false => quote!(&mut *self.#member),
+                },
+                quote!(len: self.len, #marker),
+            ),
+            split_at: split(slice, false),
+            split_at_mut: split(slice_mut, true),
+            into_iter: into_iter(IterKind::Shared),
+            into_iter_mut: into_iter(IterKind::Mutable),
+            as_slices: build(
+                &names.slices,
+                &|member, flatten| match flatten {
+                    true => quote!(self.#member.as_slices()),
+                    false => quote!(self.#member),
+                },
+                marker.clone(),
+            ),
+            as_mut_slices: build(
+                &names.slices_mut,
+                &|member, flatten| match flatten {
+                    true => quote!(self.#member.as_mut_slices()),
+                    false => quote!(&mut *self.#member),
+                },
+                marker.clone(),
+            ),
+        }
+    }
+}
+
+pub(super) fn codegen(names: &StructNames, primitives: ViewPrimitives) -> TokenStream {
+    let shared = shared_methods(names, &primitives);
+    let mutable = mut_methods(names, &primitives);
+    let trait_impls = trait_impls(names, &primitives);
+
+    quote! {
+        #shared
+        #mutable
+        #trait_impls
+    }
+}
+
+fn shared_methods(names: &StructNames, primitives: &ViewPrimitives) -> TokenStream {
+    let name = &names.slice;
+    let krate = &names.krate;
+    let generics = names.ref_generics();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let shared_ref_type = names.ref_type(false);
+    let iter_type = names.iter_type(IterKind::Shared);
+    let slices_type = names.slices_type(false);
+
+    let ViewPrimitives {
+        len,
+        get,
+        as_slices,
+        ..
+    } = primitives;
+
+    quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The number of rows
+            #[inline]
+            pub fn len(&self) -> ::core::primitive::usize {
+                #len
+            }
+
+            /// Whether there are no rows
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            /// A reference to the row at `index`, panicking if it is out of bounds
+            #[inline]
+            pub fn get(&self, index: ::core::primitive::usize) -> #shared_ref_type {
+                #get
+            }
+
+            /// A reference to the first row, or `None` if the view is empty
+            #[inline]
+            pub fn first(&self) -> ::core::option::Option<#shared_ref_type> {
+                match self.is_empty() {
+                    true => ::core::option::Option::None,
+                    false => ::core::option::Option::Some(self.get(0)),
+                }
+            }
+
+            /// A reference to the last row, or `None` if the view is empty
+            #[inline]
+            pub fn last(&self) -> ::core::option::Option<#shared_ref_type> {
+                match self.is_empty() {
+                    true => ::core::option::Option::None,
+                    false => ::core::option::Option::Some(self.get(self.len() - 1)),
+                }
+            }
+
+            /// A view of the rows in `range`, panicking if it is out of bounds
+            #[inline]
+            pub fn slice(&self, range: impl ::core::ops::RangeBounds<::core::primitive::usize>) -> Self {
+                let ::core::ops::Range { start, end } = #krate::__private::range(range, self.len());
+                let (_, rest) = #krate::SovSlice::split_at(*self, start);
+                #krate::SovSlice::split_at(rest, end - start).0
+            }
+
+            /// Split the view into the rows before `mid` and the rows from `mid` onwards,
+            /// panicking if `mid > len`
+            #[inline]
+            pub fn split_at(&self, mid: ::core::primitive::usize) -> (Self, Self) {
+                #krate::SovSlice::split_at(*self, mid)
+            }
+
+            /// Iterate over references to each row
+            #[inline]
+            pub fn iter(&self) -> #iter_type {
+                ::core::iter::IntoIterator::into_iter(*self)
+            }
+
+            /// Iterate over views of `size` rows at a time, with a shorter view at the end if the
+            /// rows don't divide evenly
+            #[inline]
+            pub fn chunks(&self, size: ::core::primitive::usize) -> #krate::Chunks<Self> {
+                #krate::Chunks::new(*self, size)
+            }
+
+            /// Iterate over views of exactly `size` rows at a time, leaving any rows at the end in
+            /// the iterator's `remainder`
+            #[inline]
+            pub fn chunks_exact(&self, size: ::core::primitive::usize) -> #krate::ChunksExact<Self> {
+                #krate::ChunksExact::new(*self, size)
+            }
+
+            /// Iterate over every overlapping view of `size` consecutive rows
+            #[inline]
+            pub fn windows(&self, size: ::core::primitive::usize) -> #krate::Windows<Self> {
+                #krate::Windows::new(*self, size)
+            }
+
+            /// The slice of each column in the view
+            #[inline]
+            pub fn as_slices(&self) -> #slices_type {
+                #as_slices
+            }
+        }
+    }
+}
+
+fn mut_methods(names: &StructNames, primitives: &ViewPrimitives) -> TokenStream {
+    let name = &names.slice_mut;
+    let krate = &names.krate;
+    let generics = names.ref_generics();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let lifetime = util::fresh_lifetime(&generics, "b");
+    let shared_ref_type = names.ref_type_with_lifetime(false, &lifetime);
+    let mut_ref_type = names.ref_type_with_lifetime(true, &lifetime);
+    let slice_type = names.slice_type_with_lifetime(false, &lifetime);
+    let slice_mut_type = names.slice_type_with_lifetime(true, &lifetime);
+    let iter_type = names.with_lifetime(&names.iter, &lifetime);
+    let iter_mut_type = names.with_lifetime(&names.iter_mut, &lifetime);
+    let slices_type = names.with_lifetime(&names.slices, &lifetime);
+    let slices_mut_type = names.with_lifetime(&names.slices_mut, &lifetime);
+
+    let ViewPrimitives {
+        len,
+        get_mut,
+        as_slice,
+        reborrow,
+        as_mut_slices,
+        ..
+    } = primitives;
+
+    quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The number of rows
+            #[inline]
+            pub fn len(&self) -> ::core::primitive::usize {
+                #len
+            }
+
+            /// Whether there are no rows
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            /// Borrow the rows immutably, for as long as this view is borrowed
+            #[inline]
+            pub fn as_slice<#lifetime>(& #lifetime self) -> #slice_type {
+                #as_slice
+            }
+
+            /// Borrow the rows mutably for a shorter lifetime, so that this view can be used again
+            /// afterwards
+            #[inline]
+            pub fn reborrow<#lifetime>(& #lifetime mut self) -> #slice_mut_type {
+                #reborrow
+            }
+
+            /// A reference to the row at `index`, panicking if it is out of bounds
+            #[inline]
+            pub fn get<#lifetime>(& #lifetime self, index: ::core::primitive::usize) -> #shared_ref_type {
+                self.as_slice().get(index)
+            }
+
+            /// A mutable reference to the row at `index`, panicking if it is out of bounds
+            #[inline]
+            pub fn get_mut<#lifetime>(& #lifetime mut self, index: ::core::primitive::usize) -> #mut_ref_type {
+                #get_mut
+            }
+
+            /// A reference to the first row, or `None` if the view is empty
+            #[inline]
+            pub fn first<#lifetime>(& #lifetime self) -> ::core::option::Option<#shared_ref_type> {
+                self.as_slice().first()
+            }
+
+            /// A reference to the last row, or `None` if the view is empty
+            #[inline]
+            pub fn last<#lifetime>(& #lifetime self) -> ::core::option::Option<#shared_ref_type> {
+                self.as_slice().last()
+            }
+
+            /// A mutable reference to the first row, or `None` if the view is empty
+            #[inline]
+            pub fn first_mut<#lifetime>(& #lifetime mut self) -> ::core::option::Option<#mut_ref_type> {
+                match self.is_empty() {
+                    true => ::core::option::Option::None,
+                    false => ::core::option::Option::Some(self.get_mut(0)),
+                }
+            }
+
+            /// A mutable reference to the last row, or `None` if the view is empty
+            #[inline]
+            pub fn last_mut<#lifetime>(& #lifetime mut self) -> ::core::option::Option<#mut_ref_type> {
+                match self.len() {
+                    0 => ::core::option::Option::None,
+                    len => ::core::option::Option::Some(self.get_mut(len - 1)),
+                }
+            }
+
+            /// A view of the rows in `range`, panicking if it is out of bounds
+            #[inline]
+            pub fn slice<#lifetime>(
+                & #lifetime self,
+                range: impl ::core::ops::RangeBounds<::core::primitive::usize>,
+            ) -> #slice_type {
+                self.as_slice().slice(range)
+            }
+
+            /// A mutable view of the rows in `range`, panicking if it is out of bounds
+            #[inline]
+            pub fn slice_mut<#lifetime>(
+                & #lifetime mut self,
+                range: impl ::core::ops::RangeBounds<::core::primitive::usize>,
+            ) -> #slice_mut_type {
+                let ::core::ops::Range { start, end } = #krate::__private::range(range, self.len());
+                let (_, rest) = #krate::SovSlice::split_at(self.reborrow(), start);
+                #krate::SovSlice::split_at(rest, end - start).0
+            }
+
+            /// Split the view into the rows before `mid` and the rows from `mid` onwards,
+            /// panicking if `mid > len`
+            #[inline]
+            pub fn split_at<#lifetime>(
+                & #lifetime self,
+                mid: ::core::primitive::usize,
+            ) -> (#slice_type, #slice_type) {
+                #krate::SovSlice::split_at(self.as_slice(), mid)
+            }
+
+            /// Split the view into two mutable views of the rows before `mid` and the rows from
+            /// `mid` onwards, panicking if `mid > len`
+            #[inline]
+            pub fn split_at_mut<#lifetime>(
+                & #lifetime mut self,
+                mid: ::core::primitive::usize,
+            ) -> (#slice_mut_type, #slice_mut_type) {
+                #krate::SovSlice::split_at(self.reborrow(), mid)
+            }
+
+            /// Iterate over references to each row
+            #[inline]
+            pub fn iter<#lifetime>(& #lifetime self) -> #iter_type {
+                ::core::iter::IntoIterator::into_iter(self.as_slice())
+            }
+
+            /// Iterate over mutable references to each row
+            #[inline]
+            pub fn iter_mut<#lifetime>(& #lifetime mut self) -> #iter_mut_type {
+                ::core::iter::IntoIterator::into_iter(self.reborrow())
+            }
+
+            /// Iterate over views of `size` rows at a time, with a shorter view at the end if the
+            /// rows don't divide evenly
+            #[inline]
+            pub fn chunks<#lifetime>(
+                & #lifetime self,
+                size: ::core::primitive::usize,
+            ) -> #krate::Chunks<#slice_type> {
+                #krate::Chunks::new(self.as_slice(), size)
+            }
+
+            /// Iterate over mutable views of `size` rows at a time, with a shorter view at the end
+            /// if the rows don't divide evenly
+            #[inline]
+            pub fn chunks_mut<#lifetime>(
+                & #lifetime mut self,
+                size: ::core::primitive::usize,
+            ) -> #krate::Chunks<#slice_mut_type> {
+                #krate::Chunks::new(self.reborrow(), size)
+            }
+
+            /// Iterate over views of exactly `size` rows at a time, leaving any rows at the end in
+            /// the iterator's `remainder`
+            #[inline]
+            pub fn chunks_exact<#lifetime>(
+                & #lifetime self,
+                size: ::core::primitive::usize,
+            ) -> #krate::ChunksExact<#slice_type> {
+                #krate::ChunksExact::new(self.as_slice(), size)
+            }
+
+            /// Iterate over mutable views of exactly `size` rows at a time, leaving any rows at the
+            /// end in the iterator's `remainder`
+            #[inline]
+            pub fn chunks_exact_mut<#lifetime>(
+                & #lifetime mut self,
+                size: ::core::primitive::usize,
+            ) -> #krate::ChunksExact<#slice_mut_type> {
+                #krate::ChunksExact::new(self.reborrow(), size)
+            }
+
+            /// Iterate over every overlapping view of `size` consecutive rows
+            #[inline]
+            pub fn windows<#lifetime>(
+                & #lifetime self,
+                size: ::core::primitive::usize,
+            ) -> #krate::Windows<#slice_type> {
+                #krate::Windows::new(self.as_slice(), size)
+            }
+
+            /// The slice of each column in the view
+            #[inline]
+            pub fn as_slices<#lifetime>(& #lifetime self) -> #slices_type {
+                self.as_slice().as_slices()
+            }
+
+            /// The mutable slice of each column in the view, so that different columns can be
+            /// modified at the same time
+            #[inline]
+            pub fn as_mut_slices<#lifetime>(& #lifetime mut self) -> #slices_mut_type {
+                #as_mut_slices
+            }
+        }
+    }
+}
+
+/// `Copy` for the shared view, like a `&[Foo]`, and `SovSlice` and `IntoIterator` for both
+fn trait_impls(names: &StructNames, primitives: &ViewPrimitives) -> TokenStream {
+    let krate = &names.krate;
+    let shared = &names.slice;
+    let generics = names.ref_generics();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let impls = [false, true].map(|mutable| {
+        let (name, kind, split_at, into_iter) = match mutable {
+            true => (
+                &names.slice_mut,
+                IterKind::Mutable,
+                &primitives.split_at_mut,
+                &primitives.into_iter_mut,
+            ),
+            false => (
+                &names.slice,
+                IterKind::Shared,
+                &primitives.split_at,
+                &primitives.into_iter,
+            ),
+        };
+        let iter_type = names.iter_type(kind);
+        let item_type = names.iter_item_type(kind);
+
+        quote! {
+            impl #impl_generics #krate::SovSlice for #name #ty_generics #where_clause {
+                #[inline]
+                fn len(&self) -> ::core::primitive::usize {
+                    Self::len(self)
+                }
+
+                #[inline]
+                fn split_at(self, mid: ::core::primitive::usize) -> (Self, Self) {
+                    #split_at
+                }
+            }
+
+            impl #impl_generics ::core::iter::IntoIterator for #name #ty_generics #where_clause {
+                type Item = #item_type;
+                type IntoIter = #iter_type;
+
+                #[inline]
+                fn into_iter(self) -> Self::IntoIter {
+                    #into_iter
+                }
+            }
+        }
+    });
+
+    quote! {
+        impl #impl_generics ::core::clone::Clone for #shared #ty_generics #where_clause {
+            #[inline]
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl #impl_generics ::core::marker::Copy for #shared #ty_generics #where_clause {}
+
+        #(#impls)*
+    }
+}
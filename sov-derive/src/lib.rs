@@ -0,0 +1,32 @@
+//! The derive macro for [`sov`](https://docs.rs/sov).
+//!
+//! This crate is an implementation detail of `sov`, and should not be used directly. The generated
+//! code refers to items in `sov`, so use the re-export `sov::StructOfVecs` instead.
+use codegen::codegen;
+use proc_macro2::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+macro_rules! bail {
+    ($span:expr => $($t:tt)*) => {
+        return Err(quote::quote_spanned! { $span.span() => compile_error! { $($t)* } })
+    };
+    ($($t:tt)*) => {
+        return Err(quote::quote! { compile_error! { $($t)* } })
+    };
+}
+
+mod codegen;
+mod parse;
+mod util;
+
+pub(crate) type Result<T> = core::result::Result<T, TokenStream>;
+
+/// Generate a struct of `Vec`s. See the crate-level docs for more detail
+#[proc_macro_derive(StructOfVecs, attributes(sov))]
+pub fn struct_of_vecs(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(tokens as DeriveInput);
+    match parse::Item::from_derive_input(input) {
+        Ok(item) => codegen(item).into(),
+        Err(tokens) => tokens.into(),
+    }
+}
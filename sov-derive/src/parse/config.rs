@@ -0,0 +1,292 @@
+use syn::{meta::ParseNestedMeta, Attribute, Ident, LitStr, Path, Token, Type, Visibility};
+
+/// Options that apply to the whole struct, set with `#[sov(...)]` on the struct itself
+#[derive(Default)]
+pub(crate) struct ContainerConfig {
+    /// `#[sov(vec = "...")]`, the name of the generated `Vec`-like type
+    pub vec: Option<Ident>,
+    /// `#[sov(ref = "...")]`, the name of the generated shared reference type
+    pub shared_ref: Option<Ident>,
+    /// `#[sov(ref_mut = "...")]`, the name of the generated mutable reference type
+    pub mut_ref: Option<Ident>,
+    /// `#[sov(iter = "...")]`, the name of the generated shared iterator
+    pub iter: Option<Ident>,
+    /// `#[sov(iter_mut = "...")]`, the name of the generated mutable iterator
+    pub iter_mut: Option<Ident>,
+    /// `#[sov(into_iter = "...")]`, the name of the generated owning iterator
+    pub into_iter: Option<Ident>,
+    /// `#[sov(drain = "...")]`, the name of the iterator returned by `drain`
+    pub drain: Option<Ident>,
+    /// `#[sov(slices = "...")]`, the name of the generated struct of shared column slices
+    pub slices: Option<Ident>,
+    /// `#[sov(slices_mut = "...")]`, the name of the generated struct of mutable column slices
+    pub slices_mut: Option<Ident>,
+    /// `#[sov(slice = "...")]`, the name of the generated shared view
+    pub slice: Option<Ident>,
+    /// `#[sov(slice_mut = "...")]`, the name of the generated mutable view
+    pub slice_mut: Option<Ident>,
+    /// `#[sov(module = "...")]`, the name of the hidden module the `Vec`-like type is defined in
+    pub module: Option<Ident>,
+    /// `#[sov(kind = "...")]`, the name of the generated discriminant enum (only valid on enums)
+    pub kind: Option<Ident>,
+    /// `#[sov(crate = "...")]`, the path to the `sov` crate, for crates that re-export it
+    pub krate: Option<Path>,
+    /// `#[sov(expose_columns)]`, give the columns of the `Vec`-like type the visibility of their
+    /// fields, rather than keeping them private
+    pub expose_columns: bool,
+    /// `#[sov(derive(...))]`, standard traits to implement for the generated types
+    pub derives: Vec<Derive>,
+    /// `#[sov(layout = "...")]`, how the columns are stored (only valid on structs)
+    pub layout: Option<Layout>,
+    /// `#[sov(array)]`, also generate a fixed-capacity `ArrayFoo<N>` with inline storage (only
+    /// valid on structs)
+    pub array: bool,
+    /// `#[sov(array = "...")]`, which also sets `array`, the name of the fixed-capacity array
+    pub array_name: Option<Ident>,
+    /// `#[sov(array_into_iter = "...")]`, the name of the array's owning iterator
+    pub array_into_iter: Option<Ident>,
+    /// `#[sov(allocator)]`, give the `Vec`-like type an allocator parameter (only valid on structs,
+    /// and needs the `allocator-api2` feature)
+    pub allocator: bool,
+}
+
+/// How the columns of the `Vec`-like type are stored, set with `#[sov(layout = "...")]`
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum Layout {
+    /// `"separate"`, each column is its own `Vec`
+    #[default]
+    Separate,
+    /// `"single"`, every column is stored in one allocation, and they all grow together
+    Single,
+}
+
+impl Layout {
+    fn from_meta(meta: &ParseNestedMeta) -> syn::Result<Self> {
+        let value = meta.value()?.parse::<LitStr>()?;
+
+        match value.value().as_str() {
+            "separate" => Ok(Self::Separate),
+            "single" => Ok(Self::Single),
+            _ => Err(syn::Error::new(
+                value.span(),
+                "unknown layout, expected `\"separate\"` or `\"single\"`",
+            )),
+        }
+    }
+}
+
+/// A standard trait that can be implemented for the generated types with `#[sov(derive(...))]`
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Derive {
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+}
+
+impl Derive {
+    fn from_meta(meta: &ParseNestedMeta) -> syn::Result<Self> {
+        let derive = match meta.path.get_ident() {
+            Some(ident) if ident == "Clone" => Self::Clone,
+            Some(ident) if ident == "Debug" => Self::Debug,
+            Some(ident) if ident == "Default" => Self::Default,
+            Some(ident) if ident == "PartialEq" => Self::PartialEq,
+            Some(ident) if ident == "Eq" => Self::Eq,
+            Some(ident) if ident == "PartialOrd" => Self::PartialOrd,
+            Some(ident) if ident == "Ord" => Self::Ord,
+            Some(ident) if ident == "Hash" => Self::Hash,
+            _ => {
+                return Err(meta.error(
+                    "unsupported derive, expected one of `Clone`, `Debug`, `Default`, \
+                     `PartialEq`, `Eq`, `PartialOrd`, `Ord`, `Hash`",
+                ))
+            }
+        };
+
+        Ok(derive)
+    }
+}
+
+/// Options that apply to a single field, set with `#[sov(...)]` on the field
+#[derive(Default)]
+pub(crate) struct FieldConfig {
+    /// `#[sov(accessor = "...")]`, the name of the slice accessor for this field's column
+    pub accessor: Option<Ident>,
+    /// `#[sov(vis = "...")]`, the visibility of the accessors for this field's column
+    pub vis: Option<Visibility>,
+    /// `#[sov(flatten)]`, store this field in the `Vec`-like type generated for its own type,
+    /// rather than a `Vec`
+    pub flatten: bool,
+    /// `#[sov(bitpacked)]`, store this `bool` field one bit per row. This also sets `flatten`,
+    /// since `bool` implements `StructOfVecs` with `sov::BitVec` as its vec
+    pub bitpacked: bool,
+    /// `#[sov(nullable)]`, store this `Option<T>` field as a column of `T` and a bitmap of which
+    /// rows are `Some`. This also sets `flatten`, since `Option<T>` implements `StructOfVecs` with
+    /// `sov::NullableVec<T>` as its vec
+    pub nullable: bool,
+    /// `#[sov(dictionary)]`, store this field as a column of `u32` codes into a table of its
+    /// distinct values. This also sets `flatten`, although the field is stored in a
+    /// `sov::DictionaryVec<T>` directly rather than through `StructOfVecs`, since its rows are
+    /// still the field's own type
+    pub dictionary: bool,
+    /// `#[sov(column = "...")]`, the type this field's column is stored in, which implements
+    /// `sov::Column`, rather than a `Vec`
+    pub column: Option<Type>,
+}
+
+impl ContainerConfig {
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut config = Self::default();
+
+        for attr in sov_attrs(attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("vec") {
+                    set_once(&mut config.vec, &meta, parse_ident(&meta)?)
+                } else if meta.path.is_ident("ref") {
+                    set_once(&mut config.shared_ref, &meta, parse_ident(&meta)?)
+                } else if meta.path.is_ident("ref_mut") {
+                    set_once(&mut config.mut_ref, &meta, parse_ident(&meta)?)
+                } else if meta.path.is_ident("iter") {
+                    set_once(&mut config.iter, &meta, parse_ident(&meta)?)
+                } else if meta.path.is_ident("iter_mut") {
+                    set_once(&mut config.iter_mut, &meta, parse_ident(&meta)?)
+                } else if meta.path.is_ident("into_iter") {
+                    set_once(&mut config.into_iter, &meta, parse_ident(&meta)?)
+                } else if meta.path.is_ident("drain") {
+                    set_once(&mut config.drain, &meta, parse_ident(&meta)?)
+                } else if meta.path.is_ident("slices") {
+                    set_once(&mut config.slices, &meta, parse_ident(&meta)?)
+                } else if meta.path.is_ident("slices_mut") {
+                    set_once(&mut config.slices_mut, &meta, parse_ident(&meta)?)
+                } else if meta.path.is_ident("slice") {
+                    set_once(&mut config.slice, &meta, parse_ident(&meta)?)
+                } else if meta.path.is_ident("slice_mut") {
+                    set_once(&mut config.slice_mut, &meta, parse_ident(&meta)?)
+                } else if meta.path.is_ident("array_into_iter") {
+                    set_once(&mut config.array_into_iter, &meta, parse_ident(&meta)?)
+                } else if meta.path.is_ident("module") {
+                    set_once(&mut config.module, &meta, parse_ident(&meta)?)
+                } else if meta.path.is_ident("kind") {
+                    set_once(&mut config.kind, &meta, parse_ident(&meta)?)
+                } else if meta.path.is_ident("crate") {
+                    let krate = meta.value()?.parse::<LitStr>()?.parse()?;
+                    set_once(&mut config.krate, &meta, krate)
+                } else if meta.path.is_ident("expose_columns") {
+                    set_flag(&mut config.expose_columns, &meta)
+                } else if meta.path.is_ident("layout") {
+                    set_once(&mut config.layout, &meta, Layout::from_meta(&meta)?)
+                } else if meta.path.is_ident("array") {
+                    // either a flag, or the name of the array, which implies the flag
+                    if meta.input.peek(Token![=]) {
+                        config.array_name = Some(parse_ident(&meta)?);
+                    }
+
+                    set_flag(&mut config.array, &meta)
+                } else if meta.path.is_ident("allocator") {
+                    set_flag(&mut config.allocator, &meta)
+                } else if meta.path.is_ident("derive") {
+                    meta.parse_nested_meta(|meta| {
+                        let derive = Derive::from_meta(&meta)?;
+
+                        if config.derives.contains(&derive) {
+                            return Err(meta.error("duplicate derive"));
+                        }
+
+                        config.derives.push(derive);
+                        Ok(())
+                    })
+                } else {
+                    Err(meta.error(
+                        "unknown `sov` option, expected one of `vec`, `ref`, `ref_mut`, `iter`, \
+                         `iter_mut`, `into_iter`, `drain`, `slices`, `slices_mut`, `slice`, \
+                         `slice_mut`, `array_into_iter`, `module`, `kind`, `crate`, \
+                         `expose_columns`, `layout`, `array`, `allocator`, `derive`",
+                    ))
+                }
+            })?;
+        }
+
+        Ok(config)
+    }
+}
+
+impl FieldConfig {
+    /// The option that stores the field in a nested vec, for error messages: `bitpacked`,
+    /// `nullable` or `dictionary` if one was set, since they imply `flatten`
+    pub fn nested_option(&self) -> &'static str {
+        match (self.bitpacked, self.nullable, self.dictionary) {
+            (true, _, _) => "bitpacked",
+            (false, true, _) => "nullable",
+            (false, false, true) => "dictionary",
+            (false, false, false) => "flatten",
+        }
+    }
+
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut config = Self::default();
+
+        for attr in sov_attrs(attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("accessor") {
+                    set_once(&mut config.accessor, &meta, parse_ident(&meta)?)
+                } else if meta.path.is_ident("vis") {
+                    let vis = meta.value()?.parse::<LitStr>()?.parse()?;
+                    set_once(&mut config.vis, &meta, vis)
+                } else if meta.path.is_ident("flatten") {
+                    set_flag(&mut config.flatten, &meta)
+                } else if meta.path.is_ident("bitpacked") {
+                    set_flag(&mut config.bitpacked, &meta)
+                } else if meta.path.is_ident("nullable") {
+                    set_flag(&mut config.nullable, &meta)
+                } else if meta.path.is_ident("dictionary") {
+                    set_flag(&mut config.dictionary, &meta)
+                } else if meta.path.is_ident("column") {
+                    let column = meta.value()?.parse::<LitStr>()?.parse()?;
+                    set_once(&mut config.column, &meta, column)
+                } else {
+                    Err(meta.error(
+                        "unknown `sov` field option, expected one of `accessor`, `vis`, `flatten`, \
+                         `bitpacked`, `nullable`, `dictionary`, `column`",
+                    ))
+                }
+            })?;
+        }
+
+        Ok(config)
+    }
+}
+
+/// Whether this attribute is one of ours, i.e. `#[sov(...)]`
+pub(crate) fn is_sov_attr(attr: &Attribute) -> bool {
+    attr.path().is_ident("sov")
+}
+
+fn sov_attrs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|attr| is_sov_attr(attr))
+}
+
+fn parse_ident(meta: &ParseNestedMeta) -> syn::Result<Ident> {
+    meta.value()?.parse::<LitStr>()?.parse()
+}
+
+fn set_once<T>(slot: &mut Option<T>, meta: &ParseNestedMeta, value: T) -> syn::Result<()> {
+    if slot.is_some() {
+        return Err(meta.error("duplicate `sov` option"));
+    }
+
+    *slot = Some(value);
+    Ok(())
+}
+
+fn set_flag(flag: &mut bool, meta: &ParseNestedMeta) -> syn::Result<()> {
+    if *flag {
+        return Err(meta.error("duplicate `sov` option"));
+    }
+
+    *flag = true;
+    Ok(())
+}
@@ -0,0 +1,481 @@
+use std::ops::Deref;
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+    ext::IdentExt, spanned::Spanned, Data, DeriveInput, GenericArgument, Generics, Ident, Index,
+    Member, PathArguments, Type, Visibility,
+};
+
+pub(crate) use config::{ContainerConfig, Derive, FieldConfig, Layout};
+
+mod config;
+
+/// The type that `StructOfVecs` was derived on
+pub(crate) enum Item {
+    Struct(Input),
+    Enum(EnumInput),
+}
+
+pub(crate) struct Input {
+    pub vis: Visibility,
+    pub name: Ident,
+    pub generics: Generics,
+    pub fields: Fields,
+    pub config: ContainerConfig,
+}
+
+pub(crate) struct EnumInput {
+    pub vis: Visibility,
+    pub name: Ident,
+    pub generics: Generics,
+    pub variants: Vec<Variant>,
+    pub config: ContainerConfig,
+}
+
+pub(crate) struct Variant {
+    pub ident: Ident,
+    pub fields: Fields,
+}
+
+pub(crate) enum Fields {
+    Named(Vec<Field>),
+    Unnamed(Vec<Field>),
+    Unit,
+}
+
+/// A field of the original struct, along with any `#[sov(...)]` options set on it.
+///
+/// The `#[sov(...)]` attributes are removed from the underlying [`syn::Field`], so it can be
+/// copied into the generated structs as-is
+pub(crate) struct Field {
+    pub field: syn::Field,
+    pub config: FieldConfig,
+}
+
+impl Deref for Field {
+    type Target = syn::Field;
+
+    fn deref(&self) -> &Self::Target {
+        &self.field
+    }
+}
+
+impl Field {
+    /// The `T` of a `#[sov(nullable)]` field of type `Option<T>`
+    pub fn nullable_type(&self) -> Option<&Type> {
+        match self.config.nullable {
+            true => option_inner(&self.ty),
+            false => None,
+        }
+    }
+
+    /// The type that a row of the field is cloned and compared as, when that isn't a nested
+    /// reference type: the `T` of a nullable `Option<T>`, or the field's own type for dictionary
+    /// fields and fields that aren't flattened
+    pub fn row_type(&self) -> Option<&Type> {
+        match (self.config.flatten, self.config.dictionary) {
+            (true, true) | (false, _) => Some(&self.ty),
+            (true, false) => self.nullable_type(),
+        }
+    }
+}
+
+impl Fields {
+    pub fn iter(&self) -> std::slice::Iter<'_, Field> {
+        match self {
+            Fields::Named(fields) | Fields::Unnamed(fields) => fields.iter(),
+            Fields::Unit => [].iter(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iter().len() == 0
+    }
+
+    /// How each field is accessed, i.e. its name, or its index for tuple structs
+    pub fn members(&self) -> Vec<Member> {
+        self.iter()
+            .enumerate()
+            .map(|(index, field)| match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(Index::from(index)),
+            })
+            .collect()
+    }
+}
+
+impl Fields {
+    pub fn map_fields(
+        &self,
+        map_named: impl FnMut(&Field) -> TokenStream,
+        mut map_unnamed: impl FnMut(&Field, usize) -> TokenStream,
+    ) -> TokenStream {
+        match self {
+            Fields::Unit => quote! {},
+            Fields::Named(fields) => {
+                let fields = fields.iter().map(map_named);
+                quote!(#(#fields)*)
+            }
+            Fields::Unnamed(fields) => {
+                let fields = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(index, field)| map_unnamed(field, index));
+                quote!(#(#fields)*)
+            }
+        }
+    }
+    pub fn map_fields_with_delimiters_and_separator(
+        &self,
+        separator: TokenStream,
+        map_named: impl FnMut(&Field) -> TokenStream,
+        mut map_unnamed: impl FnMut(&Field, usize) -> TokenStream,
+    ) -> TokenStream {
+        match self {
+            Fields::Unit => quote! {},
+            Fields::Named(fields) => {
+                let fields = fields.iter().map(map_named);
+                quote!({ #(#fields #separator)* })
+            }
+            Fields::Unnamed(fields) => {
+                let fields = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(index, field)| map_unnamed(field, index));
+                quote!(( #(#fields #separator)* ))
+            }
+        }
+    }
+
+    pub fn map_fields_with_delimiters(
+        &self,
+        map_named: impl FnMut(&Field) -> TokenStream,
+        map_unnamed: impl FnMut(&Field, usize) -> TokenStream,
+    ) -> TokenStream {
+        self.map_fields_with_delimiters_and_separator(quote!(,), map_named, map_unnamed)
+    }
+}
+
+impl Input {
+    /// Whether every column is stored in one allocation, with `#[sov(layout = "single")]`. Structs
+    /// without any fields have no columns, so they are never stored this way
+    pub fn single_allocation(&self) -> bool {
+        self.config.layout == Some(Layout::Single) && !self.fields.is_empty()
+    }
+
+    /// The member of the `Vec`-like type that owns the memory of every column, when they are
+    /// stored in one allocation. It comes after the columns, so that they are dropped first
+    pub fn allocation_member(&self) -> Option<Member> {
+        if !self.single_allocation() {
+            return None;
+        }
+
+        Some(self.extra_member("allocation"))
+    }
+
+    /// A member for a field that the generated types store alongside the columns, named `name`
+    /// (with underscores prepended if a field already has that name), or numbered after the
+    /// fields of a tuple struct
+    pub fn extra_member(&self, name: &str) -> Member {
+        match &self.fields {
+            Fields::Named(fields) => {
+                let mut name = name.to_string();
+
+                while fields
+                    .iter()
+                    .any(|field| field.ident.as_ref().unwrap().unraw() == name)
+                {
+                    name.insert(0, '_');
+                }
+
+                Member::Named(Ident::new(&name, Span::call_site()))
+            }
+            Fields::Unnamed(_) | Fields::Unit => {
+                Member::Unnamed(Index::from(self.fields.iter().len()))
+            }
+        }
+    }
+
+    pub fn map_fields(
+        &self,
+        map_named: impl FnMut(&Field) -> TokenStream,
+        map_unnamed: impl FnMut(&Field, usize) -> TokenStream,
+    ) -> TokenStream {
+        self.fields.map_fields(map_named, map_unnamed)
+    }
+
+    pub fn map_fields_with_delimiters(
+        &self,
+        map_named: impl FnMut(&Field) -> TokenStream,
+        map_unnamed: impl FnMut(&Field, usize) -> TokenStream,
+    ) -> TokenStream {
+        self.fields
+            .map_fields_with_delimiters(map_named, map_unnamed)
+    }
+}
+
+impl Item {
+    pub fn from_derive_input(input: DeriveInput) -> crate::Result<Self> {
+        let config =
+            ContainerConfig::from_attrs(&input.attrs).map_err(syn::Error::into_compile_error)?;
+
+        match input.data {
+            Data::Struct(s) => {
+                if let Some(kind) = &config.kind {
+                    bail!(kind => "`kind` can only be set on enums");
+                }
+
+                let fields = Fields::parse(s.fields)?;
+
+                // with a single allocation, the columns aren't `Vec`s, so they can't be exposed or
+                // hold another generated type
+                if config.layout == Some(Layout::Single) {
+                    if config.expose_columns {
+                        bail!(input.ident => "`expose_columns` can't be used with `layout = \"single\"`");
+                    }
+
+                    if let Some(field) = fields.iter().find(|field| field.config.flatten) {
+                        let message = format!(
+                            "`{}` can't be used with `layout = \"single\"`",
+                            field.config.nested_option()
+                        );
+                        bail!(field.ty => #message);
+                    }
+
+                    if let Some(column) =
+                        fields.iter().find_map(|field| field.config.column.as_ref())
+                    {
+                        bail!(column => "`column` can't be used with `layout = \"single\"`");
+                    }
+                }
+
+                if let (false, Some(name)) = (config.array, &config.array_into_iter) {
+                    bail!(name => "`array_into_iter` needs `array`");
+                }
+
+                // the array stores the rows of each column inline, and has nowhere to put a flattened
+                // field's columns
+                if config.array {
+                    if fields.is_empty() {
+                        bail!(input.ident => "`array` needs a struct with at least one field");
+                    }
+
+                    if let Some(field) = fields.iter().find(|field| field.config.flatten) {
+                        let message = format!(
+                            "`{}` can't be used with `array`",
+                            field.config.nested_option()
+                        );
+                        bail!(field.ty => #message);
+                    }
+                }
+
+                // every column is a `Vec` from `allocator-api2`, and they all share the allocator
+                if config.allocator {
+                    if !cfg!(feature = "allocator-api2") {
+                        bail!(input.ident => "`allocator` needs the `allocator-api2` feature of `sov`");
+                    }
+
+                    if fields.is_empty() {
+                        bail!(input.ident => "`allocator` needs a struct with at least one field");
+                    }
+
+                    if config.layout == Some(Layout::Single) {
+                        bail!(input.ident => "`allocator` can't be used with `layout = \"single\"`");
+                    }
+
+                    if let Some(field) = fields.iter().find(|field| field.config.flatten) {
+                        let message = format!(
+                            "`{}` can't be used with `allocator`",
+                            field.config.nested_option()
+                        );
+                        bail!(field.ty => #message);
+                    }
+
+                    if let Some(column) =
+                        fields.iter().find_map(|field| field.config.column.as_ref())
+                    {
+                        bail!(column => "`column` can't be used with `allocator`");
+                    }
+                }
+
+                Ok(Item::Struct(Input {
+                    vis: input.vis,
+                    fields,
+                    name: input.ident,
+                    generics: input.generics,
+                    config,
+                }))
+            }
+            Data::Enum(e) => {
+                if config.layout.is_some() {
+                    bail!(input.ident => "`layout` can only be set on structs");
+                }
+
+                if config.array || config.array_into_iter.is_some() {
+                    bail!(input.ident => "`array` can only be set on structs");
+                }
+
+                if config.allocator {
+                    bail!(input.ident => "`allocator` can only be set on structs");
+                }
+
+                let variants = e
+                    .variants
+                    .into_iter()
+                    .map(|variant| {
+                        let fields = Fields::parse(variant.fields)?;
+
+                        if let Some(field) = fields.iter().find(|field| field.config.flatten) {
+                            let message = format!(
+                                "`{}` is not supported on enum variants",
+                                field.config.nested_option()
+                            );
+                            bail!(field.ty => #message);
+                        }
+
+                        if let Some(column) =
+                            fields.iter().find_map(|field| field.config.column.as_ref())
+                        {
+                            bail!(column => "`column` is not supported on enum variants");
+                        }
+
+                        Ok(Variant {
+                            ident: variant.ident,
+                            fields,
+                        })
+                    })
+                    .collect::<crate::Result<_>>()?;
+
+                Ok(Item::Enum(EnumInput {
+                    vis: input.vis,
+                    name: input.ident,
+                    generics: input.generics,
+                    variants,
+                    config,
+                }))
+            }
+            Data::Union(u) => bail!(u.union_token => "only structs and enums are supported"),
+        }
+    }
+}
+
+impl Fields {
+    fn parse(fields: syn::Fields) -> crate::Result<Self> {
+        match fields {
+            syn::Fields::Named(fields) => Ok(Fields::Named(parse_fields(fields.named)?)),
+            syn::Fields::Unnamed(fields) => Ok(Fields::Unnamed(parse_fields(fields.unnamed)?)),
+            syn::Fields::Unit => Ok(Fields::Unit),
+        }
+    }
+}
+
+fn parse_fields(fields: impl IntoIterator<Item = syn::Field>) -> crate::Result<Vec<Field>> {
+    fields
+        .into_iter()
+        .map(|mut field| {
+            let mut config =
+                FieldConfig::from_attrs(&field.attrs).map_err(syn::Error::into_compile_error)?;
+            field.attrs.retain(|attr| !config::is_sov_attr(attr));
+
+            // a bit-packed field is a flattened `bool`, which is stored in a `BitVec`
+            if config.bitpacked {
+                if !is_bool(&field.ty) {
+                    bail!(field.ty => "`bitpacked` can only be used on `bool` fields");
+                }
+
+                if config.flatten {
+                    bail!(field.ty => "`bitpacked` can't be used with `flatten`");
+                }
+
+                if let Some(column) = &config.column {
+                    bail!(column => "`column` can't be used with `bitpacked`");
+                }
+
+                config.flatten = true;
+            }
+
+            // a nullable field is a flattened `Option<T>`, which is stored in a `NullableVec<T>`
+            if config.nullable {
+                if option_inner(&field.ty).is_none() {
+                    bail!(field.ty => "`nullable` can only be used on `Option` fields");
+                }
+
+                if config.flatten {
+                    bail!(field.ty => "`nullable` can't be used with `flatten`");
+                }
+
+                if let Some(column) = &config.column {
+                    bail!(column => "`column` can't be used with `nullable`");
+                }
+
+                config.flatten = true;
+            }
+
+            // a dictionary field is stored in a `DictionaryVec<T>`, which the codegen names
+            // directly rather than through `StructOfVecs`, but otherwise treats like a flattened
+            // field
+            if config.dictionary {
+                // checked before `flatten`, which they set
+                if config.bitpacked {
+                    bail!(field.ty => "`dictionary` can't be used with `bitpacked`");
+                }
+
+                if config.nullable {
+                    bail!(field.ty => "`dictionary` can't be used with `nullable`");
+                }
+
+                if config.flatten {
+                    bail!(field.ty => "`dictionary` can't be used with `flatten`");
+                }
+
+                if let Some(column) = &config.column {
+                    bail!(column => "`column` can't be used with `dictionary`");
+                }
+
+                config.flatten = true;
+            }
+
+            // a flattened field is stored in the vec of its own type, not in a column
+            if let (true, Some(column)) = (config.flatten, &config.column) {
+                bail!(column => "`column` can't be used with `flatten`");
+            }
+
+            Ok(Field { field, config })
+        })
+        .collect()
+}
+
+/// Whether a type is written as `bool`, which is the only type that can be bit-packed
+fn is_bool(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path.qself.is_none() && path.path.is_ident("bool"),
+        Type::Group(group) => is_bool(&group.elem),
+        Type::Paren(paren) => is_bool(&paren.elem),
+        _ => false,
+    }
+}
+
+/// The `T` of a type written as `Option<T>`, which is the only type that can be nullable
+fn option_inner(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => {
+            let segment = path.path.segments.last()?;
+
+            match &segment.arguments {
+                PathArguments::AngleBracketed(arguments)
+                    if segment.ident == "Option" && arguments.args.len() == 1 =>
+                {
+                    match &arguments.args[0] {
+                        GenericArgument::Type(ty) => Some(ty),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }
+        }
+        Type::Group(group) => option_inner(&group.elem),
+        Type::Paren(paren) => option_inner(&paren.elem),
+        _ => None,
+    }
+}
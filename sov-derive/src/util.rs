@@ -0,0 +1,145 @@
+use std::collections::HashSet;
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{format_ident, ToTokens};
+use syn::{parse_quote, Generics, Ident, Lifetime, Visibility, WherePredicate};
+
+use crate::parse::Field;
+
+pub fn nth_field(n: usize) -> Ident {
+    format_ident!("field{n}")
+}
+
+/// Best effort attempt to pluralize an English word
+pub fn pluralize(ident: &Ident) -> Ident {
+    match ident.to_string().chars().last().unwrap() {
+        's' => format_ident!("{ident}es"),
+        _ => format_ident!("{ident}s"),
+    }
+}
+
+/// Find a lifetime named `'{name}` (or `'{name}1`, `'{name}2`, etc.) that isn't already declared by
+/// the user's generics, so we can add our own lifetime parameter without clashing
+pub fn fresh_lifetime(generics: &Generics, name: &str) -> Lifetime {
+    let is_taken = |candidate: &str| {
+        generics
+            .lifetimes()
+            .any(|param| param.lifetime.ident == candidate)
+    };
+
+    let mut candidate = name.to_string();
+    let mut suffix = 0;
+
+    while is_taken(&candidate) {
+        suffix += 1;
+        candidate = format!("{name}{suffix}");
+    }
+
+    Lifetime::new(&format!("'{candidate}"), proc_macro2::Span::call_site())
+}
+
+/// Find a name for a type or const parameter, `{name}` (or `{name}1`, `{name}2`, etc.), that isn't
+/// already declared by the user's generics
+pub fn fresh_param(generics: &Generics, name: &str) -> Ident {
+    let is_taken = |candidate: &str| {
+        generics.type_params().any(|param| param.ident == candidate)
+            || generics
+                .const_params()
+                .any(|param| param.ident == candidate)
+    };
+
+    let mut candidate = name.to_string();
+    let mut suffix = 0;
+
+    while is_taken(&candidate) {
+        suffix += 1;
+        candidate = format!("{name}{suffix}");
+    }
+
+    Ident::new(&candidate, proc_macro2::Span::call_site())
+}
+
+/// A copy of `generics` with extra predicates added to its where clause
+pub fn with_bounds(
+    generics: &Generics,
+    predicates: impl IntoIterator<Item = WherePredicate>,
+) -> Generics {
+    let mut generics = generics.clone();
+    generics.make_where_clause().predicates.extend(predicates);
+    generics
+}
+
+/// `#param: #bound` for each type parameter of `generics` that a flattened field's type mentions.
+///
+/// A flattened field's rows are the nested type's reference types, and its columns are the nested
+/// type's `Vec`-like type, so the nested methods and impls are bounded by the nested type's fields.
+/// That doesn't follow from a bound on the field's type, so the parameters it uses are bounded
+/// instead, like `#[derive]` does. Other fields don't need any extra bounds
+pub fn nested_bounds(
+    generics: &Generics,
+    field: &Field,
+    bound: impl ToTokens,
+) -> Vec<WherePredicate> {
+    fn collect(tokens: TokenStream, idents: &mut HashSet<Ident>) {
+        for token in tokens {
+            match token {
+                TokenTree::Ident(ident) => {
+                    idents.insert(ident);
+                }
+                TokenTree::Group(group) => collect(group.stream(), idents),
+                TokenTree::Punct(_) | TokenTree::Literal(_) => {}
+            }
+        }
+    }
+
+    if field.row_type().is_some() {
+        return Vec::new();
+    }
+
+    let mut idents = HashSet::new();
+    collect(field.ty.to_token_stream(), &mut idents);
+
+    generics
+        .type_params()
+        .map(|param| &param.ident)
+        .filter(|ident| idents.contains(*ident))
+        .map(|ident| parse_quote!(#ident: #bound))
+        .collect()
+}
+
+/// `#ty: #bound`, but with a higher-ranked lifetime so that it isn't a trivial bound.
+///
+/// A trivial bound (one that doesn't mention any generic parameters) that doesn't hold is an error,
+/// so `u8: Default` is fine but `Cell<u8>: Sync` would stop the generated code compiling. With the
+/// lifetime, the bound is only checked where the method or impl is used
+pub fn non_trivial_bound(ty: impl ToTokens, bound: impl ToTokens) -> WherePredicate {
+    parse_quote!(for<'__sov> #ty: #bound)
+}
+
+/// Generated impls live in the hidden module, so we need to make private items `pub(super)`, so
+/// they are visible in the parent module (which is the module that the original type is defined
+/// in).
+///
+/// Similarly, relative paths in `pub(in ...)` need to be adjusted to start from the parent module
+pub fn hidden_module_vis(vis: &Visibility) -> Visibility {
+    let Visibility::Restricted(restricted) = vis else {
+        return match vis {
+            Visibility::Inherited => parse_quote! { pub(super) },
+            other => other.clone(),
+        };
+    };
+
+    let mut path = (*restricted.path).clone();
+
+    match path.segments.first_mut() {
+        Some(first) if path.leading_colon.is_none() && first.ident == "self" => {
+            first.ident = Ident::new("super", first.ident.span());
+        }
+        Some(first) if path.leading_colon.is_none() && first.ident == "super" => {
+            path.segments.insert(0, parse_quote!(super));
+        }
+        _ => return vis.clone(),
+    }
+
+    parse_quote! { pub(in #path) }
+}
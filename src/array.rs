@@ -0,0 +1,212 @@
+//! Inline storage for the `ArrayFoo<N>` types generated with `#[sov(array)]`.
+//!
+//! Every column of an array has the same length, so it's stored once in the generated type rather
+//! than in each [`ArrayColumn`]. That means the columns can't know which of their rows are
+//! initialized, and most of their methods are `unsafe`, with the length passed in
+use core::{iter::FusedIterator, mem::MaybeUninit, ptr, slice};
+
+/// Space for `N` rows of one column, of which the first `len` are initialized
+#[doc(hidden)]
+pub struct ArrayColumn<T, const N: usize> {
+    rows: [MaybeUninit<T>; N],
+}
+
+impl<T, const N: usize> ArrayColumn<T, N> {
+    /// A column without any initialized rows
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            rows: [const { MaybeUninit::uninit() }; N],
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The first `len` rows must be initialized
+    #[inline]
+    pub unsafe fn as_slice(&self, len: usize) -> &[T] {
+        debug_assert!(len <= N);
+        // SAFETY: the caller guarantees that the first `len` rows are initialized
+        unsafe { slice::from_raw_parts(self.rows.as_ptr().cast(), len) }
+    }
+
+    /// # Safety
+    ///
+    /// The first `len` rows must be initialized
+    #[inline]
+    pub unsafe fn as_mut_slice(&mut self, len: usize) -> &mut [T] {
+        debug_assert!(len <= N);
+        // SAFETY: the caller guarantees that the first `len` rows are initialized
+        unsafe { slice::from_raw_parts_mut(self.rows.as_mut_ptr().cast(), len) }
+    }
+
+    /// Write the row at `index`, shifting the rows in `index..len` up by one.
+    ///
+    /// # Safety
+    ///
+    /// The first `len` rows must be initialized, `index` must be at most `len`, and `len` must be
+    /// less than `N`
+    #[inline]
+    pub unsafe fn insert(&mut self, len: usize, index: usize, value: T) {
+        debug_assert!(index <= len && len < N);
+
+        // SAFETY: the caller guarantees that there is space for one more row after `len`
+        unsafe {
+            let slot = self.rows.as_mut_ptr().add(index);
+            ptr::copy(slot, slot.add(1), len - index);
+            slot.write(MaybeUninit::new(value));
+        }
+    }
+
+    /// Move the row at `index` out, shifting the rows in `index + 1..len` down by one.
+    ///
+    /// # Safety
+    ///
+    /// The first `len` rows must be initialized, and `index` must be less than `len`
+    #[inline]
+    pub unsafe fn remove(&mut self, len: usize, index: usize) -> T {
+        debug_assert!(index < len && len <= N);
+
+        // SAFETY: the caller guarantees that the row is initialized, and the rows after it are
+        // moved over it
+        unsafe {
+            let slot = self.rows.as_mut_ptr().add(index);
+            let value = slot.read().assume_init();
+            ptr::copy(slot.add(1), slot, len - index - 1);
+            value
+        }
+    }
+
+    /// Move the row at `index` out, replacing it with the last row.
+    ///
+    /// # Safety
+    ///
+    /// The first `len` rows must be initialized, and `index` must be less than `len`
+    #[inline]
+    pub unsafe fn swap_remove(&mut self, len: usize, index: usize) -> T {
+        debug_assert!(index < len && len <= N);
+        self.rows.swap(index, len - 1);
+
+        // SAFETY: the caller guarantees that the last row is initialized
+        unsafe { self.rows[len - 1].assume_init_read() }
+    }
+
+    /// Drop the rows in `start..end`, which are uninitialized afterwards.
+    ///
+    /// # Safety
+    ///
+    /// The rows in `start..end` must be initialized
+    #[inline]
+    pub unsafe fn drop_rows(&mut self, start: usize, end: usize) {
+        debug_assert!(start <= end && end <= N);
+
+        // SAFETY: the caller guarantees that these rows are initialized
+        unsafe {
+            let rows = self.rows.as_mut_ptr().add(start).cast::<T>();
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(rows, end - start));
+        }
+    }
+
+    /// Move the rows out of the column, leaving it without any initialized rows.
+    ///
+    /// # Safety
+    ///
+    /// The first `len` rows must be initialized
+    #[inline]
+    pub unsafe fn take(&mut self, len: usize) -> ArrayColumnIntoIter<T, N> {
+        debug_assert!(len <= N);
+
+        ArrayColumnIntoIter {
+            rows: core::mem::take(self).rows,
+            start: 0,
+            end: len,
+        }
+    }
+}
+
+impl<T, const N: usize> Default for ArrayColumn<T, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An iterator that moves the rows out of an [`ArrayColumn`]
+#[doc(hidden)]
+pub struct ArrayColumnIntoIter<T, const N: usize> {
+    rows: [MaybeUninit<T>; N],
+    /// The rows in `start..end` are initialized, and haven't been yielded yet
+    start: usize,
+    end: usize,
+}
+
+impl<T, const N: usize> ArrayColumnIntoIter<T, N> {
+    /// Drop the rows in `start..end`, which must have already been removed from the remaining rows
+    fn drop_rows(&mut self, start: usize, end: usize) {
+        // SAFETY: the rows were initialized, and won't be read or dropped again
+        unsafe {
+            let rows = self.rows.as_mut_ptr().add(start).cast::<T>();
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(rows, end - start));
+        }
+    }
+}
+
+impl<T, const N: usize> Iterator for ArrayColumnIntoIter<T, N> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+
+        self.start += 1;
+        // SAFETY: the row is initialized, and is no longer in the remaining rows
+        Some(unsafe { self.rows[self.start - 1].assume_init_read() })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len, Some(len))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<T> {
+        let start = self.start;
+        self.start += n.min(self.end - start);
+        self.drop_rows(start, self.start);
+        self.next()
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for ArrayColumnIntoIter<T, N> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+
+        self.end -= 1;
+        // SAFETY: the row is initialized, and is no longer in the remaining rows
+        Some(unsafe { self.rows[self.end].assume_init_read() })
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<T> {
+        let end = self.end;
+        self.end -= n.min(end - self.start);
+        self.drop_rows(self.end, end);
+        self.next_back()
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for ArrayColumnIntoIter<T, N> {}
+
+impl<T, const N: usize> FusedIterator for ArrayColumnIntoIter<T, N> {}
+
+impl<T, const N: usize> Drop for ArrayColumnIntoIter<T, N> {
+    fn drop(&mut self) {
+        let (start, end) = (self.start, self.end);
+        self.start = end;
+        self.drop_rows(start, end);
+    }
+}
@@ -0,0 +1,1515 @@
+//! Bit-packed storage for `bool` fields, which `#[sov(bitpacked)]` stores one bit per row.
+//!
+//! `bool` implements [`StructOfVecs`] with [`BitVec`] as its vec, so a bit-packed field is handled
+//! by the generated code in the same way as a flattened field. The bits are stored in atomic words
+//! so that a [`BitMut`] can set its bit without a `&mut` to the whole word, which may be shared
+//! with the neighbouring rows of another view. Writes through a whole `&mut BitVec` don't need to
+//! be atomic, so only writes through [`BitMut`] and [`BitSliceMut`] pay for it.
+use alloc::vec::Vec;
+use core::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    iter::FusedIterator,
+    ops::{Range, RangeBounds},
+    sync::atomic::{self, AtomicUsize},
+};
+
+use crate::{permutation::Permutation, slice, SovSlice, SovVec, StructOfVecs};
+
+/// The number of bits in each word
+pub(crate) const BITS: usize = usize::BITS as usize;
+
+/// The ordering of every atomic access. Each bit is only ever written through one [`BitMut`] at a
+/// time, so the accesses don't need to synchronize anything else
+const RELAXED: atomic::Ordering = atomic::Ordering::Relaxed;
+
+/// The number of words needed to store `len` bits
+fn word_count(len: usize) -> usize {
+    len.div_ceil(BITS)
+}
+
+/// The mask of the bits below `offset` in a word
+fn low_mask(offset: usize) -> usize {
+    (1 << offset) - 1
+}
+
+/// A `Vec<bool>` which stores each value in a single bit, used for `#[sov(bitpacked)]` fields.
+///
+/// The rows are borrowed as a [`BitSlice`] or [`BitSliceMut`] rather than a `&[bool]`, and
+/// mutable access to a single row is through a [`BitMut`] proxy rather than a `&mut bool`
+pub struct BitVec {
+    /// The rows, packed into words from the least significant bit. The bits after `len` are
+    /// always zero, so that whole words can be compared and counted
+    words: Vec<AtomicUsize>,
+    len: usize,
+}
+
+impl BitVec {
+    /// Create an empty vec
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            words: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Create an empty vec with space for at least `capacity` rows
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            words: Vec::with_capacity(word_count(capacity)),
+            len: 0,
+        }
+    }
+
+    /// The number of rows
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether there are no rows
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of rows the vec can hold without reallocating
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.words.capacity().saturating_mul(BITS)
+    }
+
+    /// Reserve space for at least `additional` more rows
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        let additional = self.additional_words(additional);
+        self.words.reserve(additional);
+    }
+
+    /// Reserve space for exactly `additional` more rows, rounded up to a whole word
+    #[inline]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        let additional = self.additional_words(additional);
+        self.words.reserve_exact(additional);
+    }
+
+    /// Shrink the capacity as much as possible
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.words.shrink_to_fit();
+    }
+
+    /// Shrink the capacity to at least `min_capacity` and the number of rows
+    #[inline]
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.words.shrink_to(word_count(min_capacity));
+    }
+
+    /// The row at `index`, panicking if it is out of bounds
+    #[inline]
+    pub fn get(&self, index: usize) -> bool {
+        self.as_slice().get(index)
+    }
+
+    /// A proxy for the row at `index`, which can set it, panicking if it is out of bounds
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> BitMut<'_> {
+        self.as_mut_slice().into_mut(index)
+    }
+
+    /// Set the row at `index`, panicking if it is out of bounds
+    #[inline]
+    pub fn set(&mut self, index: usize, value: bool) {
+        let len = self.len;
+        assert!(
+            index < len,
+            "index out of bounds: the len is {len} but the index is {index}"
+        );
+
+        let word = self.words[index / BITS].get_mut();
+        let mask = 1 << (index % BITS);
+
+        match value {
+            true => *word |= mask,
+            false => *word &= !mask,
+        }
+    }
+
+    /// Swap the rows at `a` and `b`, panicking if either is out of bounds
+    #[inline]
+    pub fn swap(&mut self, a: usize, b: usize) {
+        let (first, second) = (self.get(a), self.get(b));
+        self.set(a, second);
+        self.set(b, first);
+    }
+
+    /// Add a row to the end
+    #[inline]
+    pub fn push(&mut self, value: bool) {
+        if self.len.is_multiple_of(BITS) {
+            self.words.push(AtomicUsize::new(0));
+        }
+
+        self.len += 1;
+        self.set(self.len - 1, value);
+    }
+
+    /// Remove the last row, if there is one
+    #[inline]
+    pub fn pop(&mut self) -> Option<bool> {
+        let last = self.len.checked_sub(1)?;
+        let value = self.get(last);
+        self.truncate(last);
+
+        Some(value)
+    }
+
+    /// Insert a row at `index`, shifting all rows after it
+    pub fn insert(&mut self, index: usize, value: bool) {
+        let len = self.len;
+        assert!(
+            index <= len,
+            "insertion index (is {index}) should be <= len (is {len})"
+        );
+
+        self.push(false);
+
+        let mask = 1 << (index % BITS);
+        let low = low_mask(index % BITS);
+        let mut carry = 0;
+
+        // shift every bit from `index` onwards up by one, carrying the top bit of each word into
+        // the next one
+        for (position, word) in self.words[index / BITS..].iter_mut().enumerate() {
+            let word = word.get_mut();
+            let shifted = (*word << 1) | carry;
+            carry = *word >> (BITS - 1);
+
+            *word = match position {
+                0 => (*word & low) | (shifted & !low & !mask) | (usize::from(value) * mask),
+                _ => shifted,
+            };
+        }
+    }
+
+    /// Remove the row at `index`, shifting all rows after it
+    pub fn remove(&mut self, index: usize) -> bool {
+        let len = self.len;
+        assert!(
+            index < len,
+            "removal index (is {index}) should be < len (is {len})"
+        );
+
+        let value = self.get(index);
+        let first = index / BITS;
+        let low = low_mask(index % BITS);
+
+        // shift every bit after `index` down by one, borrowing the bottom bit of the next word
+        for position in first..self.words.len() {
+            let next = match self.words.get_mut(position + 1) {
+                Some(next) => *next.get_mut() & 1,
+                None => 0,
+            };
+
+            let word = self.words[position].get_mut();
+            let shifted = (*word >> 1) | (next << (BITS - 1));
+
+            *word = match position == first {
+                true => (*word & low) | (shifted & !low),
+                false => shifted,
+            };
+        }
+
+        self.truncate(len - 1);
+        value
+    }
+
+    /// Remove the row at `index`, replacing it with the last row
+    #[inline]
+    pub fn swap_remove(&mut self, index: usize) -> bool {
+        let len = self.len;
+        assert!(
+            index < len,
+            "swap_remove index (is {index}) should be < len (is {len})"
+        );
+
+        let value = self.get(index);
+        let last = self.pop().unwrap();
+
+        if index < self.len {
+            self.set(index, last);
+        }
+
+        value
+    }
+
+    /// Remove every row after the first `len`, doing nothing if there are fewer rows
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+
+        self.len = len;
+        self.words.truncate(word_count(len));
+
+        // keep the bits after `len` zeroed
+        if let Some(last) = self.words.last_mut() {
+            if !len.is_multiple_of(BITS) {
+                *last.get_mut() &= low_mask(len % BITS);
+            }
+        }
+    }
+
+    /// Remove every row, keeping the capacity
+    #[inline]
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Resize to `new_len` rows, either by removing rows from the end or by adding copies of
+    /// `value`
+    #[inline]
+    pub fn resize(&mut self, new_len: usize, value: bool) {
+        self.resize_with(new_len, || value);
+    }
+
+    /// Resize to `new_len` rows, either by removing rows from the end or by adding rows returned
+    /// by `f`
+    pub fn resize_with<F>(&mut self, new_len: usize, mut f: F)
+    where
+        F: FnMut() -> bool,
+    {
+        match new_len.checked_sub(self.len) {
+            Some(additional) => {
+                self.reserve(additional);
+                (0..additional).for_each(|_| self.push(f()));
+            }
+            None => self.truncate(new_len),
+        }
+    }
+
+    /// Borrow every row as a view
+    #[inline]
+    pub fn as_slice(&self) -> BitSlice<'_> {
+        BitSlice {
+            words: &self.words,
+            start: 0,
+            len: self.len,
+        }
+    }
+
+    /// Mutably borrow every row as a view
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> BitSliceMut<'_> {
+        BitSliceMut {
+            words: &self.words,
+            start: 0,
+            len: self.len,
+        }
+    }
+
+    /// Borrow every row, which for a single column of bits is the same as [`BitVec::as_slice`]
+    #[inline]
+    pub fn as_slices(&self) -> BitSlice<'_> {
+        self.as_slice()
+    }
+
+    /// Mutably borrow every row, which for a single column of bits is the same as
+    /// [`BitVec::as_mut_slice`]
+    #[inline]
+    pub fn as_mut_slices(&mut self) -> BitSliceMut<'_> {
+        self.as_mut_slice()
+    }
+
+    /// An iterator over each row
+    #[inline]
+    pub fn iter(&self) -> BitIter<'_> {
+        self.as_slice().iter()
+    }
+
+    /// An iterator over a proxy for each row
+    #[inline]
+    pub fn iter_mut(&mut self) -> BitIterMut<'_> {
+        self.as_mut_slice().into_iter()
+    }
+
+    /// The number of rows that are `true`
+    #[inline]
+    pub fn count_ones(&self) -> usize {
+        // the bits after `len` are zero, so whole words can be counted
+        self.words
+            .iter()
+            .map(|word| word.load(RELAXED).count_ones() as usize)
+            .sum()
+    }
+
+    /// The number of rows that are `false`
+    #[inline]
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// An iterator over the index of each row that is `true`, which skips over whole words of
+    /// `false` rows at a time
+    #[inline]
+    pub fn iter_ones(&self) -> Ones<'_> {
+        self.as_slice().iter_ones()
+    }
+
+    /// Reorder the rows, in the same way as the other columns of a vec being sorted
+    #[doc(hidden)]
+    #[inline]
+    pub fn apply_permutation(&mut self, permutation: &Permutation) {
+        debug_assert_eq!(self.len, permutation.column_len());
+
+        for &(a, b) in permutation.swaps() {
+            self.swap(a, b);
+        }
+    }
+
+    /// The number of words to reserve for `additional` more rows
+    fn additional_words(&self, additional: usize) -> usize {
+        let required = self.len.checked_add(additional).expect("capacity overflow");
+        word_count(required) - self.words.len()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl BitVec {
+    /// A parallel iterator over each row
+    #[inline]
+    pub fn par_iter(&self) -> impl rayon::iter::IndexedParallelIterator<Item = bool> + '_ {
+        use rayon::prelude::*;
+
+        let slice = self.as_slice();
+        (0..self.len)
+            .into_par_iter()
+            .map(move |index| slice.get(index))
+    }
+
+    /// A parallel iterator over a proxy for each row
+    #[inline]
+    pub fn par_iter_mut(
+        &mut self,
+    ) -> impl rayon::iter::IndexedParallelIterator<Item = BitMut<'_>> + '_ {
+        use rayon::prelude::*;
+
+        // each proxy sets its bit atomically, so they can share words across threads
+        let words = &self.words[..];
+        (0..self.len).into_par_iter().map(move |index| BitMut {
+            word: &words[index / BITS],
+            mask: 1 << (index % BITS),
+        })
+    }
+
+    /// A parallel iterator over views of `chunk_size` rows. The last chunk may be shorter.
+    ///
+    /// Panics if `chunk_size` is 0
+    #[inline]
+    pub fn par_chunks_mut(
+        &mut self,
+        chunk_size: usize,
+    ) -> impl rayon::iter::IndexedParallelIterator<Item = BitSliceMut<'_>> + '_ {
+        use rayon::prelude::*;
+
+        assert!(chunk_size != 0, "chunk_size must not be zero");
+
+        let (words, len) = (&self.words[..], self.len);
+        (0..len.div_ceil(chunk_size))
+            .into_par_iter()
+            .map(move |chunk| {
+                let start = chunk * chunk_size;
+
+                BitSliceMut {
+                    words,
+                    start,
+                    len: chunk_size.min(len - start),
+                }
+            })
+    }
+}
+
+impl Clone for BitVec {
+    fn clone(&self) -> Self {
+        Self {
+            words: self
+                .words
+                .iter()
+                .map(|word| AtomicUsize::new(word.load(RELAXED)))
+                .collect(),
+            len: self.len,
+        }
+    }
+}
+
+impl fmt::Debug for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.as_slice(), f)
+    }
+}
+
+impl Default for BitVec {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for BitVec {
+    fn eq(&self, other: &Self) -> bool {
+        // the bits after `len` are zero, so whole words can be compared
+        self.len == other.len
+            && self
+                .words
+                .iter()
+                .zip(&other.words)
+                .all(|(a, b)| a.load(RELAXED) == b.load(RELAXED))
+    }
+}
+
+impl Eq for BitVec {}
+
+impl PartialOrd for BitVec {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BitVec {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl Hash for BitVec {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+
+        for word in &self.words {
+            word.load(RELAXED).hash(state);
+        }
+    }
+}
+
+impl Extend<bool> for BitVec {
+    fn extend<I: IntoIterator<Item = bool>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl FromIterator<bool> for BitVec {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl IntoIterator for BitVec {
+    type Item = bool;
+    type IntoIter = BitIntoIter;
+
+    #[inline]
+    fn into_iter(self) -> BitIntoIter {
+        BitIntoIter {
+            range: 0..self.len,
+            vec: self,
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a BitVec {
+    type Item = bool;
+    type IntoIter = BitIter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> BitIter<'a> {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut BitVec {
+    type Item = BitMut<'a>;
+    type IntoIter = BitIterMut<'a>;
+
+    #[inline]
+    fn into_iter(self) -> BitIterMut<'a> {
+        self.iter_mut()
+    }
+}
+
+impl StructOfVecs for bool {
+    type Vec = BitVec;
+    type Ref<'a> = bool;
+    type RefMut<'a> = BitMut<'a>;
+    type Slices<'a> = BitSlice<'a>;
+    type SlicesMut<'a> = BitSliceMut<'a>;
+    type Slice<'a> = BitSlice<'a>;
+    type SliceMut<'a> = BitSliceMut<'a>;
+}
+
+impl SovVec for BitVec {
+    type Iter<'a> = BitIter<'a>;
+    type IterMut<'a> = BitIterMut<'a>;
+
+    #[inline]
+    fn new() -> Self {
+        Self::new()
+    }
+
+    #[inline]
+    fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity(capacity)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.capacity()
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        self.reserve(additional);
+    }
+
+    #[inline]
+    fn reserve_exact(&mut self, additional: usize) {
+        self.reserve_exact(additional);
+    }
+
+    #[inline]
+    fn shrink_to_fit(&mut self) {
+        self.shrink_to_fit();
+    }
+
+    #[inline]
+    fn shrink_to(&mut self, min_capacity: usize) {
+        self.shrink_to(min_capacity);
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        self.truncate(len);
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.clear();
+    }
+
+    #[inline]
+    fn resize_with<F>(&mut self, new_len: usize, f: F)
+    where
+        F: FnMut() -> bool,
+    {
+        self.resize_with(new_len, f);
+    }
+
+    #[inline]
+    fn get(&self, index: usize) -> bool {
+        self.get(index)
+    }
+
+    #[inline]
+    fn get_mut(&mut self, index: usize) -> BitMut<'_> {
+        self.get_mut(index)
+    }
+
+    #[inline]
+    fn as_slices(&self) -> BitSlice<'_> {
+        self.as_slices()
+    }
+
+    #[inline]
+    fn as_mut_slices(&mut self) -> BitSliceMut<'_> {
+        self.as_mut_slices()
+    }
+
+    #[inline]
+    fn as_slice(&self) -> BitSlice<'_> {
+        self.as_slice()
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> BitSliceMut<'_> {
+        self.as_mut_slice()
+    }
+
+    #[inline]
+    fn iter(&self) -> BitIter<'_> {
+        self.iter()
+    }
+
+    #[inline]
+    fn iter_mut(&mut self) -> BitIterMut<'_> {
+        self.iter_mut()
+    }
+
+    #[inline]
+    fn push(&mut self, value: bool) {
+        self.push(value);
+    }
+
+    #[inline]
+    fn insert(&mut self, index: usize, value: bool) {
+        self.insert(index, value);
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<bool> {
+        self.pop()
+    }
+
+    #[inline]
+    fn remove(&mut self, index: usize) -> bool {
+        self.remove(index)
+    }
+
+    #[inline]
+    fn swap_remove(&mut self, index: usize) -> bool {
+        self.swap_remove(index)
+    }
+}
+
+/// A view of a range of rows of a [`BitVec`], like a `&[bool]`
+#[derive(Clone, Copy)]
+pub struct BitSlice<'a> {
+    words: &'a [AtomicUsize],
+    /// The index of the first row in `words`
+    start: usize,
+    len: usize,
+}
+
+impl<'a> BitSlice<'a> {
+    /// The number of rows
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether there are no rows
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The row at `index`, panicking if it is out of bounds
+    #[inline]
+    pub fn get(&self, index: usize) -> bool {
+        let len = self.len;
+        assert!(
+            index < len,
+            "index out of bounds: the len is {len} but the index is {index}"
+        );
+
+        let index = self.start + index;
+        self.words[index / BITS].load(RELAXED) & (1 << (index % BITS)) != 0
+    }
+
+    /// A view of a range of rows, panicking if it is out of bounds
+    #[inline]
+    pub fn slice(self, range: impl RangeBounds<usize>) -> Self {
+        let Range { start, end } = slice::range(range, self.len);
+
+        Self {
+            words: self.words,
+            start: self.start + start,
+            len: end - start,
+        }
+    }
+
+    /// Split the view into the rows before `mid` and the rows from `mid` onwards, panicking if
+    /// `mid > len`
+    #[inline]
+    pub fn split_at(self, mid: usize) -> (Self, Self) {
+        (self.slice(..mid), self.slice(mid..))
+    }
+
+    /// Every row, which for a single column of bits is the view itself
+    #[inline]
+    pub fn as_slices(&self) -> Self {
+        *self
+    }
+
+    /// An iterator over each row
+    #[inline]
+    pub fn iter(&self) -> BitIter<'a> {
+        BitIter {
+            words: self.words,
+            range: self.start..self.start + self.len,
+        }
+    }
+
+    /// The number of rows that are `true`
+    pub fn count_ones(&self) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+
+        let (start, end) = (self.start, self.start + self.len);
+        let (first, last) = (start / BITS, (end - 1) / BITS);
+
+        // mask off the rows before the start of the first word and after the end of the last
+        self.words[first..=last]
+            .iter()
+            .enumerate()
+            .map(|(position, word)| {
+                let mut bits = word.load(RELAXED);
+
+                if position == 0 {
+                    bits &= !low_mask(start % BITS);
+                }
+
+                if first + position == last {
+                    bits &= usize::MAX >> (BITS - 1 - (end - 1) % BITS);
+                }
+
+                bits.count_ones() as usize
+            })
+            .sum()
+    }
+
+    /// The number of rows that are `false`
+    #[inline]
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// An iterator over the index of each row that is `true`, which skips over whole words of
+    /// `false` rows at a time
+    #[inline]
+    pub fn iter_ones(&self) -> Ones<'a> {
+        Ones {
+            slice: *self,
+            next: 0,
+        }
+    }
+}
+
+impl fmt::Debug for BitSlice<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl PartialEq for BitSlice<'_> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for BitSlice<'_> {}
+
+impl<'a> IntoIterator for BitSlice<'a> {
+    type Item = bool;
+    type IntoIter = BitIter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> BitIter<'a> {
+        self.iter()
+    }
+}
+
+impl SovSlice for BitSlice<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn split_at(self, mid: usize) -> (Self, Self) {
+        self.split_at(mid)
+    }
+}
+
+/// A mutable view of a range of rows of a [`BitVec`], like a `&mut [bool]`.
+///
+/// The rows at the edges of the view may share a word with the rows of another view, so each row
+/// is set atomically
+pub struct BitSliceMut<'a> {
+    words: &'a [AtomicUsize],
+    /// The index of the first row in `words`
+    start: usize,
+    len: usize,
+}
+
+impl<'a> BitSliceMut<'a> {
+    /// The number of rows
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether there are no rows
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The row at `index`, panicking if it is out of bounds
+    #[inline]
+    pub fn get(&self, index: usize) -> bool {
+        self.as_slice().get(index)
+    }
+
+    /// A proxy for the row at `index`, which can set it, panicking if it is out of bounds
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> BitMut<'_> {
+        self.reborrow().into_mut(index)
+    }
+
+    /// Set the row at `index`, panicking if it is out of bounds
+    #[inline]
+    pub fn set(&mut self, index: usize, value: bool) {
+        self.get_mut(index).set(value);
+    }
+
+    /// Swap the rows at `a` and `b`, panicking if either is out of bounds
+    #[inline]
+    pub fn swap(&mut self, a: usize, b: usize) {
+        let (first, second) = (self.get(a), self.get(b));
+        self.set(a, second);
+        self.set(b, first);
+    }
+
+    /// Set every row to `value`
+    #[inline]
+    pub fn fill(&mut self, value: bool) {
+        self.iter_mut().for_each(|mut bit| bit.set(value));
+    }
+
+    /// Borrow the view immutably, for as long as it is borrowed
+    #[inline]
+    pub fn as_slice(&self) -> BitSlice<'_> {
+        BitSlice {
+            words: self.words,
+            start: self.start,
+            len: self.len,
+        }
+    }
+
+    /// Borrow the view mutably for a shorter lifetime, so that it can be used again afterwards
+    #[inline]
+    pub fn reborrow(&mut self) -> BitSliceMut<'_> {
+        BitSliceMut {
+            words: self.words,
+            start: self.start,
+            len: self.len,
+        }
+    }
+
+    /// A view of a range of rows, panicking if it is out of bounds
+    #[inline]
+    pub fn slice_mut(self, range: impl RangeBounds<usize>) -> Self {
+        let Range { start, end } = slice::range(range, self.len);
+
+        Self {
+            words: self.words,
+            start: self.start + start,
+            len: end - start,
+        }
+    }
+
+    /// Split the view into the rows before `mid` and the rows from `mid` onwards, panicking if
+    /// `mid > len`
+    #[inline]
+    pub fn split_at(self, mid: usize) -> (Self, Self) {
+        let words = self.words;
+        let (before, after) = self.as_slice().split_at(mid);
+
+        (
+            Self {
+                words,
+                start: before.start,
+                len: before.len,
+            },
+            Self {
+                words,
+                start: after.start,
+                len: after.len,
+            },
+        )
+    }
+
+    /// Every row, which for a single column of bits is the view itself
+    #[inline]
+    pub fn as_slices(&self) -> BitSlice<'_> {
+        self.as_slice()
+    }
+
+    /// Mutably borrow every row, which for a single column of bits is the view itself
+    #[inline]
+    pub fn as_mut_slices(&mut self) -> BitSliceMut<'_> {
+        self.reborrow()
+    }
+
+    /// An iterator over each row
+    #[inline]
+    pub fn iter(&self) -> BitIter<'_> {
+        self.as_slice().iter()
+    }
+
+    /// An iterator over a proxy for each row
+    #[inline]
+    pub fn iter_mut(&mut self) -> BitIterMut<'_> {
+        self.reborrow().into_iter()
+    }
+
+    /// The number of rows that are `true`
+    #[inline]
+    pub fn count_ones(&self) -> usize {
+        self.as_slice().count_ones()
+    }
+
+    /// The number of rows that are `false`
+    #[inline]
+    pub fn count_zeros(&self) -> usize {
+        self.as_slice().count_zeros()
+    }
+
+    /// An iterator over the index of each row that is `true`
+    #[inline]
+    pub fn iter_ones(&self) -> Ones<'_> {
+        self.as_slice().iter_ones()
+    }
+
+    /// A proxy for the row at `index`, borrowing for as long as the view
+    fn into_mut(self, index: usize) -> BitMut<'a> {
+        let len = self.len;
+        assert!(
+            index < len,
+            "index out of bounds: the len is {len} but the index is {index}"
+        );
+
+        let index = self.start + index;
+
+        BitMut {
+            word: &self.words[index / BITS],
+            mask: 1 << (index % BITS),
+        }
+    }
+}
+
+impl fmt::Debug for BitSliceMut<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.as_slice(), f)
+    }
+}
+
+impl<'a> IntoIterator for BitSliceMut<'a> {
+    type Item = BitMut<'a>;
+    type IntoIter = BitIterMut<'a>;
+
+    #[inline]
+    fn into_iter(self) -> BitIterMut<'a> {
+        BitIterMut {
+            words: self.words,
+            range: self.start..self.start + self.len,
+        }
+    }
+}
+
+impl SovSlice for BitSliceMut<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn split_at(self, mid: usize) -> (Self, Self) {
+        self.split_at(mid)
+    }
+}
+
+/// A proxy for a single row of a [`BitVec`], which can read and set it like a `&mut bool`.
+///
+/// It compares, orders and hashes as the `bool` it points to
+pub struct BitMut<'a> {
+    word: &'a AtomicUsize,
+    mask: usize,
+}
+
+impl BitMut<'_> {
+    /// The value of the row
+    #[inline]
+    pub fn get(&self) -> bool {
+        self.word.load(RELAXED) & self.mask != 0
+    }
+
+    /// Set the row to `value`
+    #[inline]
+    pub fn set(&mut self, value: bool) {
+        self.replace(value);
+    }
+
+    /// Set the row to `value`, and return the old value
+    #[inline]
+    pub fn replace(&mut self, value: bool) -> bool {
+        let old = match value {
+            true => self.word.fetch_or(self.mask, RELAXED),
+            false => self.word.fetch_and(!self.mask, RELAXED),
+        };
+
+        old & self.mask != 0
+    }
+
+    /// The value of the row, which is what a `FooRef` holds for a bit-packed field
+    #[inline]
+    pub fn as_ref(&self) -> bool {
+        self.get()
+    }
+
+    /// Borrow the row mutably for a shorter lifetime, so that this proxy can be used again
+    /// afterwards
+    #[inline]
+    pub fn reborrow(&mut self) -> BitMut<'_> {
+        BitMut {
+            word: self.word,
+            mask: self.mask,
+        }
+    }
+
+    /// The value of the row
+    #[inline]
+    pub fn to_owned(&self) -> bool {
+        self.get()
+    }
+}
+
+impl fmt::Debug for BitMut<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.get(), f)
+    }
+}
+
+impl<'b> PartialEq<BitMut<'b>> for BitMut<'_> {
+    #[inline]
+    fn eq(&self, other: &BitMut<'b>) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl Eq for BitMut<'_> {}
+
+impl<'b> PartialOrd<BitMut<'b>> for BitMut<'_> {
+    #[inline]
+    fn partial_cmp(&self, other: &BitMut<'b>) -> Option<Ordering> {
+        Some(self.get().cmp(&other.get()))
+    }
+}
+
+impl Ord for BitMut<'_> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.get().cmp(&other.get())
+    }
+}
+
+impl PartialEq<bool> for BitMut<'_> {
+    #[inline]
+    fn eq(&self, other: &bool) -> bool {
+        self.get() == *other
+    }
+}
+
+impl PartialEq<BitMut<'_>> for bool {
+    #[inline]
+    fn eq(&self, other: &BitMut<'_>) -> bool {
+        *self == other.get()
+    }
+}
+
+impl PartialOrd<bool> for BitMut<'_> {
+    #[inline]
+    fn partial_cmp(&self, other: &bool) -> Option<Ordering> {
+        Some(self.get().cmp(other))
+    }
+}
+
+impl PartialOrd<BitMut<'_>> for bool {
+    #[inline]
+    fn partial_cmp(&self, other: &BitMut<'_>) -> Option<Ordering> {
+        Some(self.cmp(&other.get()))
+    }
+}
+
+impl Hash for BitMut<'_> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.get().hash(state);
+    }
+}
+
+impl From<BitMut<'_>> for bool {
+    #[inline]
+    fn from(bit: BitMut<'_>) -> Self {
+        bit.get()
+    }
+}
+
+/// An iterator over the rows of a [`BitVec`] or [`BitSlice`]
+#[derive(Clone)]
+pub struct BitIter<'a> {
+    words: &'a [AtomicUsize],
+    range: Range<usize>,
+}
+
+impl BitIter<'_> {
+    fn bit(&self, index: usize) -> bool {
+        self.words[index / BITS].load(RELAXED) & (1 << (index % BITS)) != 0
+    }
+}
+
+impl Iterator for BitIter<'_> {
+    type Item = bool;
+
+    #[inline]
+    fn next(&mut self) -> Option<bool> {
+        self.range.next().map(|index| self.bit(index))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for BitIter<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<bool> {
+        self.range.next_back().map(|index| self.bit(index))
+    }
+}
+
+impl ExactSizeIterator for BitIter<'_> {}
+
+impl FusedIterator for BitIter<'_> {}
+
+/// An iterator over a proxy for each row of a [`BitVec`] or [`BitSliceMut`]
+pub struct BitIterMut<'a> {
+    words: &'a [AtomicUsize],
+    range: Range<usize>,
+}
+
+impl<'a> BitIterMut<'a> {
+    fn bit(&self, index: usize) -> BitMut<'a> {
+        BitMut {
+            word: &self.words[index / BITS],
+            mask: 1 << (index % BITS),
+        }
+    }
+}
+
+impl<'a> Iterator for BitIterMut<'a> {
+    type Item = BitMut<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<BitMut<'a>> {
+        self.range.next().map(|index| self.bit(index))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for BitIterMut<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<BitMut<'a>> {
+        self.range.next_back().map(|index| self.bit(index))
+    }
+}
+
+impl ExactSizeIterator for BitIterMut<'_> {}
+
+impl FusedIterator for BitIterMut<'_> {}
+
+/// An iterator over the rows of a [`BitVec`] by value
+#[derive(Clone)]
+pub struct BitIntoIter {
+    vec: BitVec,
+    range: Range<usize>,
+}
+
+impl Iterator for BitIntoIter {
+    type Item = bool;
+
+    #[inline]
+    fn next(&mut self) -> Option<bool> {
+        self.range.next().map(|index| self.vec.get(index))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for BitIntoIter {
+    #[inline]
+    fn next_back(&mut self) -> Option<bool> {
+        self.range.next_back().map(|index| self.vec.get(index))
+    }
+}
+
+impl ExactSizeIterator for BitIntoIter {}
+
+impl FusedIterator for BitIntoIter {}
+
+/// An iterator over the index of each row that is `true`, returned by `iter_ones`
+#[derive(Clone)]
+pub struct Ones<'a> {
+    slice: BitSlice<'a>,
+    /// The index of the next row to look at
+    next: usize,
+}
+
+impl Iterator for Ones<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let BitSlice { words, start, len } = self.slice;
+
+        while self.next < len {
+            let index = start + self.next;
+            let offset = index % BITS;
+            let bits = words[index / BITS].load(RELAXED) >> offset;
+
+            if bits == 0 {
+                self.next += BITS - offset;
+                continue;
+            }
+
+            // the rest of the word may be past the end of the view
+            let one = self.next + bits.trailing_zeros() as usize;
+
+            if one >= len {
+                break;
+            }
+
+            self.next = one + 1;
+            return Some(one);
+        }
+
+        self.next = len;
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.slice.len.saturating_sub(self.next)))
+    }
+}
+
+impl FusedIterator for Ones<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::*;
+
+    /// Lengths on either side of the word boundaries, where bits carry from one word to the next
+    const LENGTHS: [usize; 8] = [
+        0,
+        1,
+        BITS - 1,
+        BITS,
+        BITS + 1,
+        2 * BITS - 1,
+        2 * BITS,
+        2 * BITS + 1,
+    ];
+
+    /// A mix of `true` and `false` rows with no period that lines up with the words
+    fn model(len: usize) -> Vec<bool> {
+        (0..len)
+            .map(|index| index % 3 == 0 || index % 7 == 1)
+            .collect()
+    }
+
+    /// Check `bits` against the model, including that the bits after the end are zeroed
+    fn assert_matches(bits: &BitVec, model: &[bool]) {
+        assert_eq!(bits.len(), model.len());
+        assert!(bits.iter().eq(model.iter().copied()));
+        assert_eq!(
+            bits.count_ones(),
+            model.iter().filter(|value| **value).count()
+        );
+        assert_eq!(bits.words.len(), word_count(model.len()));
+
+        if let Some(last) = bits.words.last() {
+            let used = match model.len() % BITS {
+                0 => usize::MAX,
+                offset => low_mask(offset),
+            };
+            assert_eq!(last.load(RELAXED) & !used, 0);
+        }
+    }
+
+    #[test]
+    fn push_and_pop() {
+        for len in LENGTHS {
+            let mut model = model(len);
+            let mut bits: BitVec = model.iter().copied().collect();
+            assert_matches(&bits, &model);
+
+            while let Some(value) = model.pop() {
+                assert_eq!(bits.pop(), Some(value));
+                assert_matches(&bits, &model);
+            }
+
+            assert_eq!(bits.pop(), None);
+        }
+    }
+
+    #[test]
+    fn insert_across_words() {
+        for len in LENGTHS {
+            for index in 0..=len {
+                for value in [false, true] {
+                    let mut model = model(len);
+                    let mut bits: BitVec = model.iter().copied().collect();
+
+                    model.insert(index, value);
+                    bits.insert(index, value);
+                    assert_matches(&bits, &model);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn remove_across_words() {
+        for len in LENGTHS {
+            for index in 0..len {
+                let mut model = model(len);
+                let mut bits: BitVec = model.iter().copied().collect();
+
+                assert_eq!(bits.remove(index), model.remove(index));
+                assert_matches(&bits, &model);
+            }
+        }
+    }
+
+    #[test]
+    fn swap_remove_across_words() {
+        for len in LENGTHS {
+            for index in 0..len {
+                let mut model = model(len);
+                let mut bits: BitVec = model.iter().copied().collect();
+
+                assert_eq!(bits.swap_remove(index), model.swap_remove(index));
+                assert_matches(&bits, &model);
+            }
+        }
+    }
+
+    #[test]
+    fn truncate_clears_the_rest_of_the_word() {
+        for len in LENGTHS {
+            for new_len in 0..=len {
+                let mut model = vec![true; len];
+                let mut bits: BitVec = model.iter().copied().collect();
+
+                model.truncate(new_len);
+                bits.truncate(new_len);
+                assert_matches(&bits, &model);
+
+                // the cleared bits must not reappear when the vec grows again
+                model.resize(len, false);
+                bits.resize(len, false);
+                assert_matches(&bits, &model);
+            }
+        }
+    }
+
+    #[test]
+    fn count_and_iter_ones() {
+        for len in LENGTHS {
+            for model in [model(len), vec![true; len], vec![false; len]] {
+                let bits: BitVec = model.iter().copied().collect();
+                let ones: Vec<_> = (0..len).filter(|&index| model[index]).collect();
+
+                assert_eq!(bits.count_ones(), ones.len());
+                assert_eq!(bits.count_zeros(), len - ones.len());
+                assert_eq!(bits.iter_ones().collect::<Vec<_>>(), ones);
+            }
+        }
+    }
+
+    #[test]
+    fn slices_at_non_word_offsets() {
+        let len = 3 * BITS + 1;
+        let model = model(len);
+        let bits: BitVec = model.iter().copied().collect();
+        let offsets = [0, 1, BITS - 1, BITS, BITS + 1, 2 * BITS + 1, len];
+
+        for start in offsets {
+            for end in offsets.into_iter().filter(|&end| end >= start) {
+                let model = &model[start..end];
+                let slice = bits.as_slice().slice(start..end);
+                let ones: Vec<_> = (0..model.len()).filter(|&index| model[index]).collect();
+
+                assert_eq!(slice.len(), model.len());
+                assert!(slice.iter().eq(model.iter().copied()));
+                assert!(slice.iter().rev().eq(model.iter().rev().copied()));
+                assert_eq!(slice.count_ones(), ones.len());
+                assert_eq!(slice.iter_ones().collect::<Vec<_>>(), ones);
+
+                for mid in 0..=model.len() {
+                    let (left, right) = slice.split_at(mid);
+                    assert!(left.iter().eq(model[..mid].iter().copied()));
+                    assert!(right.iter().eq(model[mid..].iter().copied()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mutable_slices_only_write_their_own_rows() {
+        let len = 3 * BITS + 1;
+        let offsets = [0, 1, BITS - 1, BITS, BITS + 1, 2 * BITS + 1, len];
+
+        for start in offsets {
+            for end in offsets.into_iter().filter(|&end| end >= start) {
+                for value in [false, true] {
+                    let mut model = model(len);
+                    let mut bits: BitVec = model.iter().copied().collect();
+
+                    bits.as_mut_slice().slice_mut(start..end).fill(value);
+                    model[start..end].fill(value);
+                    assert_matches(&bits, &model);
+
+                    if start < end {
+                        let mut slice = bits.as_mut_slice().slice_mut(start..end);
+                        slice.set(0, !value);
+                        slice.get_mut(end - start - 1).set(!value);
+                        model[start] = !value;
+                        model[end - 1] = !value;
+                        assert_matches(&bits, &model);
+                    }
+                }
+            }
+        }
+    }
+}
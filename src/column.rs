@@ -0,0 +1,518 @@
+//! Storage for `#[sov(layout = "single")]`, where every column lives in one allocation.
+//!
+//! Each column is a [`SingleColumn`], which works like a `Vec` that can't grow by itself. The
+//! generated type grows every column at once by moving them all into a new [`Allocation`], so
+//! pushing only ever writes into space that has already been reserved
+use alloc::{
+    alloc::{alloc, dealloc, handle_alloc_error},
+    vec::Vec,
+};
+use core::{
+    alloc::Layout,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    iter::FusedIterator,
+    marker::PhantomData,
+    mem::{self, ManuallyDrop},
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+    slice,
+};
+
+/// The memory that every column of a vec is stored in, one after the other.
+///
+/// This only owns the memory: the columns drop their own rows, so they must be dropped first
+#[doc(hidden)]
+pub struct Allocation {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+// SAFETY: this is just a block of memory, which is only read and written through the columns, and
+// they are `Send` and `Sync` depending on their rows
+unsafe impl Send for Allocation {}
+unsafe impl Sync for Allocation {}
+
+impl Allocation {
+    /// An allocation without any memory, for columns with no capacity
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            ptr: NonNull::dangling(),
+            layout: Layout::new::<()>(),
+        }
+    }
+
+    /// Move every column into a new allocation with space for exactly `capacity` rows, and free
+    /// the old one. `capacity` must be at least the number of rows.
+    ///
+    /// # Safety
+    ///
+    /// `columns` must be every column stored in this allocation, in the same order each time
+    pub unsafe fn set_capacity(&mut self, columns: &mut [&mut dyn RawColumn], capacity: usize) {
+        assert!(
+            columns.iter().all(|column| column.len() <= capacity),
+            "capacity is less than the number of rows"
+        );
+
+        let mut layout = Layout::new::<()>();
+        let mut offsets = Vec::with_capacity(columns.len());
+
+        for column in columns.iter() {
+            let element = column.element_layout();
+            let array = element
+                .size()
+                .checked_mul(capacity)
+                .and_then(|size| Layout::from_size_align(size, element.align()).ok())
+                .expect("capacity overflow");
+            let (extended, offset) = layout.extend(array).expect("capacity overflow");
+
+            layout = extended;
+            offsets.push(offset);
+        }
+
+        let ptr = match layout.size() {
+            0 => NonNull::dangling(),
+            // SAFETY: the layout has a non-zero size
+            _ => match NonNull::new(unsafe { alloc(layout) }) {
+                Some(ptr) => ptr,
+                None => handle_alloc_error(layout),
+            },
+        };
+
+        for (column, offset) in columns.iter_mut().zip(offsets) {
+            // SAFETY: the offset is within the new allocation, and is followed by enough space for
+            // `capacity` elements of the column, which is at least its length
+            unsafe { column.relocate(ptr.add(offset), capacity) };
+        }
+
+        // every column has been moved out of the old allocation, so it can be freed
+        if self.layout.size() != 0 {
+            // SAFETY: the old memory was allocated with this layout
+            unsafe { dealloc(self.ptr.as_ptr(), self.layout) };
+        }
+
+        self.ptr = ptr;
+        self.layout = layout;
+    }
+}
+
+impl Default for Allocation {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Allocation {
+    fn drop(&mut self) {
+        if self.layout.size() != 0 {
+            // SAFETY: the memory was allocated with this layout
+            unsafe { dealloc(self.ptr.as_ptr(), self.layout) };
+        }
+    }
+}
+
+/// A column of any type, so that the columns of a vec can be moved together
+#[doc(hidden)]
+pub trait RawColumn {
+    /// The layout of one row in this column
+    fn element_layout(&self) -> Layout;
+
+    /// The number of rows
+    fn len(&self) -> usize;
+
+    /// Whether there are no rows
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Move the rows to `ptr`, which now has space for `capacity` rows.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for writes of `capacity` rows, aligned for them, and not overlap the
+    /// rows' current location. `capacity` must be at least the number of rows
+    unsafe fn relocate(&mut self, ptr: NonNull<u8>, capacity: usize);
+}
+
+/// A column that lives in an [`Allocation`], with a fixed capacity.
+///
+/// It dereferences to a slice, and has the methods of `Vec` that don't allocate. The methods that
+/// add rows panic if the column is full
+#[doc(hidden)]
+pub struct SingleColumn<T> {
+    ptr: NonNull<T>,
+    len: usize,
+    capacity: usize,
+    _marker: PhantomData<T>,
+}
+
+// SAFETY: the column owns its rows, like a `Vec`
+unsafe impl<T: Send> Send for SingleColumn<T> {}
+unsafe impl<T: Sync> Sync for SingleColumn<T> {}
+
+impl<T> SingleColumn<T> {
+    /// An empty column without any space
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            ptr: NonNull::dangling(),
+            len: 0,
+            capacity: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The number of rows there is space for
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: the first `len` rows are initialized
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: the first `len` rows are initialized
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// Panic if there isn't space for `additional` more rows
+    #[inline]
+    fn assert_space(&self, additional: usize) {
+        assert!(
+            additional <= self.capacity - self.len,
+            "column is full, the vec must reserve space first"
+        );
+    }
+
+    #[inline]
+    pub fn push(&mut self, value: T) {
+        self.assert_space(1);
+
+        // SAFETY: there is space for at least one more row
+        unsafe { self.ptr.as_ptr().add(self.len).write(value) };
+        self.len += 1;
+    }
+
+    pub fn insert(&mut self, index: usize, value: T) {
+        let len = self.len;
+        assert!(
+            index <= len,
+            "insertion index (is {index}) should be <= len (is {len})"
+        );
+        self.assert_space(1);
+
+        // SAFETY: the index is in bounds, and there is space to shift the rows after it
+        unsafe {
+            let slot = self.ptr.as_ptr().add(index);
+            ptr::copy(slot, slot.add(1), len - index);
+            slot.write(value);
+        }
+        self.len += 1;
+    }
+
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+        // SAFETY: this row was initialized, and is no longer counted in the length
+        Some(unsafe { self.ptr.as_ptr().add(self.len).read() })
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len;
+        assert!(
+            index < len,
+            "removal index (is {index}) should be < len (is {len})"
+        );
+
+        // SAFETY: the index is in bounds, and the rows after it are shifted over the gap
+        unsafe {
+            let slot = self.ptr.as_ptr().add(index);
+            let value = slot.read();
+            ptr::copy(slot.add(1), slot, len - index - 1);
+            self.len -= 1;
+            value
+        }
+    }
+
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let len = self.len;
+        assert!(
+            index < len,
+            "swap_remove index (is {index}) should be < len (is {len})"
+        );
+
+        // SAFETY: the index is in bounds, and the last row is moved into the gap
+        unsafe {
+            let base = self.ptr.as_ptr();
+            let value = base.add(index).read();
+            ptr::copy(base.add(len - 1), base.add(index), 1);
+            self.len -= 1;
+            value
+        }
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+
+        let removed = ptr::slice_from_raw_parts_mut(
+            // SAFETY: `len` is less than the number of rows
+            unsafe { self.ptr.as_ptr().add(len) },
+            self.len - len,
+        );
+
+        // the length is updated first, so that the rows aren't dropped again if one panics
+        self.len = len;
+        // SAFETY: these rows were initialized, and are no longer counted in the length
+        unsafe { ptr::drop_in_place(removed) };
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    pub fn resize(&mut self, new_len: usize, value: T)
+    where
+        T: Clone,
+    {
+        match new_len.checked_sub(self.len) {
+            Some(0) => {}
+            Some(additional) => {
+                self.assert_space(additional);
+
+                for _ in 1..additional {
+                    self.push(value.clone());
+                }
+                self.push(value);
+            }
+            None => self.truncate(new_len),
+        }
+    }
+
+    pub fn extend_from_slice(&mut self, values: &[T])
+    where
+        T: Clone,
+    {
+        self.assert_space(values.len());
+
+        for value in values {
+            self.push(value.clone());
+        }
+    }
+}
+
+impl<T> RawColumn for SingleColumn<T> {
+    #[inline]
+    fn element_layout(&self) -> Layout {
+        Layout::new::<T>()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    unsafe fn relocate(&mut self, ptr: NonNull<u8>, capacity: usize) {
+        // zero-sized rows, and columns without any space, don't need any memory
+        let ptr = match mem::size_of::<T>() == 0 || capacity == 0 {
+            true => NonNull::dangling(),
+            false => ptr.cast(),
+        };
+
+        // SAFETY: the caller guarantees that the new memory has space for every row, and doesn't
+        // overlap the old memory
+        unsafe { ptr::copy_nonoverlapping(self.ptr.as_ptr(), ptr.as_ptr(), self.len) };
+
+        self.ptr = ptr;
+        self.capacity = capacity;
+    }
+}
+
+impl<T> Default for SingleColumn<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for SingleColumn<T> {
+    fn drop(&mut self) {
+        // SAFETY: the rows are initialized, and the memory is freed by the allocation afterwards
+        unsafe { ptr::drop_in_place(self.as_mut_slice()) };
+    }
+}
+
+impl<T> Deref for SingleColumn<T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> DerefMut for SingleColumn<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SingleColumn<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for SingleColumn<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq> Eq for SingleColumn<T> {}
+
+impl<T: PartialOrd> PartialOrd for SingleColumn<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+
+impl<T: Ord> Ord for SingleColumn<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+/// Hashes the same way as a `Vec`
+impl<T: Hash> Hash for SingleColumn<T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+impl<T> IntoIterator for SingleColumn<T> {
+    type Item = T;
+    type IntoIter = SingleColumnIntoIter<T>;
+
+    /// Move the rows out of the column. The iterator doesn't own the memory, so the allocation
+    /// has to be kept alive until it is dropped
+    #[inline]
+    fn into_iter(self) -> SingleColumnIntoIter<T> {
+        let column = ManuallyDrop::new(self);
+
+        SingleColumnIntoIter {
+            ptr: column.ptr,
+            start: 0,
+            end: column.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// An iterator that moves the rows out of a [`SingleColumn`]
+#[doc(hidden)]
+pub struct SingleColumnIntoIter<T> {
+    ptr: NonNull<T>,
+    /// The rows in `start..end` haven't been yielded yet
+    start: usize,
+    end: usize,
+    _marker: PhantomData<T>,
+}
+
+// SAFETY: the iterator owns the remaining rows, like a `vec::IntoIter`
+unsafe impl<T: Send> Send for SingleColumnIntoIter<T> {}
+unsafe impl<T: Sync> Sync for SingleColumnIntoIter<T> {}
+
+impl<T> SingleColumnIntoIter<T> {
+    /// Drop the remaining rows in `start..end`
+    fn drop_rows(&mut self, start: usize, end: usize) {
+        let rows = ptr::slice_from_raw_parts_mut(
+            // SAFETY: `start` is within the remaining rows
+            unsafe { self.ptr.as_ptr().add(start) },
+            end - start,
+        );
+
+        // SAFETY: the caller has already moved `start..end` out of the remaining rows, so they
+        // won't be dropped again
+        unsafe { ptr::drop_in_place(rows) };
+    }
+}
+
+impl<T> Iterator for SingleColumnIntoIter<T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+
+        self.start += 1;
+        // SAFETY: the row hasn't been yielded yet, and is no longer in the remaining rows
+        Some(unsafe { self.ptr.as_ptr().add(self.start - 1).read() })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len, Some(len))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<T> {
+        let start = self.start;
+        self.start += n.min(self.end - start);
+        self.drop_rows(start, self.start);
+        self.next()
+    }
+}
+
+impl<T> DoubleEndedIterator for SingleColumnIntoIter<T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+
+        self.end -= 1;
+        // SAFETY: the row hasn't been yielded yet, and is no longer in the remaining rows
+        Some(unsafe { self.ptr.as_ptr().add(self.end).read() })
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<T> {
+        let end = self.end;
+        self.end -= n.min(end - self.start);
+        self.drop_rows(self.end, end);
+        self.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for SingleColumnIntoIter<T> {}
+
+impl<T> FusedIterator for SingleColumnIntoIter<T> {}
+
+impl<T> Drop for SingleColumnIntoIter<T> {
+    fn drop(&mut self) {
+        let (start, end) = (self.start, self.end);
+        self.start = end;
+        self.drop_rows(start, end);
+    }
+}
@@ -0,0 +1,1190 @@
+//! Dictionary-encoded storage for fields with few distinct values, which `#[sov(dictionary)]`
+//! stores as a column of `u32` codes into a table of those values.
+//!
+//! Unlike the other nested columns, there is no type whose `StructOfVecs` implementation stores
+//! its rows in a [`DictionaryVec`], since the rows are the field's own type. The generated code
+//! names these types directly instead, and calls the same inherent methods as it does on a
+//! flattened field.
+use alloc::{vec, vec::Vec};
+use core::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    iter::FusedIterator,
+    ops::{Deref, RangeBounds},
+    slice,
+};
+
+use crate::{permutation::Permutation, SovSlice};
+
+/// A `Vec<T>` which stores each distinct value once, in a dictionary, and each row as the `u32`
+/// code of its value, used for `#[sov(dictionary)]` fields.
+///
+/// Adding a row interns its value, so the rows can be filtered by comparing their codes rather
+/// than their values. Values stay in the dictionary after the rows that used them are removed,
+/// so their codes don't change while rows are added and removed. That means the dictionary only
+/// grows, so a vec whose rows keep changing to new values should call
+/// [`DictionaryVec::shrink_dictionary`] or [`DictionaryVec::shrink_to_fit`] now and then, which
+/// remove the values that no row uses and give the rest new codes
+#[derive(Clone)]
+pub struct DictionaryVec<T> {
+    /// The code of every row, which is an index into `dictionary`
+    codes: Vec<u32>,
+    /// Every distinct value, in the order they were first added
+    dictionary: Vec<T>,
+    /// The codes of the dictionary, ordered by their value, for finding the code of a value
+    sorted: Vec<u32>,
+}
+
+impl<T> DictionaryVec<T> {
+    /// Create an empty vec
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            codes: Vec::new(),
+            dictionary: Vec::new(),
+            sorted: Vec::new(),
+        }
+    }
+
+    /// Create an empty vec with space for at least `capacity` rows. The dictionary grows
+    /// separately, as new values are added
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            codes: Vec::with_capacity(capacity),
+            dictionary: Vec::new(),
+            sorted: Vec::new(),
+        }
+    }
+
+    /// The number of rows
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.codes.len()
+    }
+
+    /// Whether there are no rows
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+
+    /// The number of rows the vec can hold without reallocating its codes
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.codes.capacity()
+    }
+
+    /// Reserve space for at least `additional` more rows
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.codes.reserve(additional);
+    }
+
+    /// Reserve space for exactly `additional` more rows
+    #[inline]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.codes.reserve_exact(additional);
+    }
+
+    /// Remove the values that no row uses from the dictionary, and shrink the capacity of the
+    /// codes and the dictionary as much as possible. The values that are left keep their order,
+    /// but may get new codes
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_dictionary();
+        self.codes.shrink_to_fit();
+        self.dictionary.shrink_to_fit();
+        self.sorted.shrink_to_fit();
+    }
+
+    /// Shrink the capacity to at least `min_capacity` and the number of rows
+    #[inline]
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.codes.shrink_to(min_capacity);
+    }
+
+    /// The row at `index`, panicking if it is out of bounds
+    #[inline]
+    pub fn get(&self, index: usize) -> &T {
+        self.as_slice().get(index)
+    }
+
+    /// A proxy for the row at `index`, which can point it at another value in the dictionary,
+    /// panicking if it is out of bounds
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> DictionaryMut<'_, T> {
+        DictionaryMut {
+            code: &mut self.codes[index],
+            dictionary: &self.dictionary,
+            sorted: &self.sorted,
+        }
+    }
+
+    /// Remove every row after the first `len`, doing nothing if there are fewer rows. Their
+    /// values stay in the dictionary
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.codes.truncate(len);
+    }
+
+    /// Remove the values that no row uses from the dictionary, so that it doesn't keep growing as
+    /// rows are removed and added with new values. The values that are left keep their order, but
+    /// may get new codes, so codes from before this call shouldn't be used afterwards
+    pub fn shrink_dictionary(&mut self) {
+        let mut used = vec![false; self.dictionary.len()];
+        for &code in &self.codes {
+            used[code as usize] = true;
+        }
+
+        // the new code of each value that is kept, which is the number of kept values before it
+        let mut new_codes = Vec::with_capacity(self.dictionary.len());
+        let mut kept = 0;
+        for &used in &used {
+            new_codes.push(kept);
+            kept += u32::from(used);
+        }
+
+        if kept as usize == self.dictionary.len() {
+            return;
+        }
+
+        let mut code = 0;
+        self.dictionary.retain(|_| {
+            code += 1;
+            used[code - 1]
+        });
+        self.sorted.retain(|&code| used[code as usize]);
+
+        for code in self.sorted.iter_mut().chain(&mut self.codes) {
+            *code = new_codes[*code as usize];
+        }
+    }
+
+    /// Remove every row, and every value from the dictionary, keeping the capacity
+    #[inline]
+    pub fn clear(&mut self) {
+        self.codes.clear();
+        self.dictionary.clear();
+        self.sorted.clear();
+    }
+
+    /// The code of every row, which is its value's index in [`DictionaryVec::dictionary`]
+    #[inline]
+    pub fn codes(&self) -> &[u32] {
+        &self.codes
+    }
+
+    /// Every distinct value, indexed by its code, in the order they were first added
+    #[inline]
+    pub fn dictionary(&self) -> &[T] {
+        &self.dictionary
+    }
+
+    /// Borrow every row as a view
+    #[inline]
+    pub fn as_slice(&self) -> DictionarySlice<'_, T> {
+        DictionarySlice {
+            codes: &self.codes,
+            dictionary: &self.dictionary,
+            sorted: &self.sorted,
+        }
+    }
+
+    /// Mutably borrow every row as a view
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> DictionarySliceMut<'_, T> {
+        DictionarySliceMut {
+            codes: &mut self.codes,
+            dictionary: &self.dictionary,
+            sorted: &self.sorted,
+        }
+    }
+
+    /// Borrow every row, which for a dictionary column is the same as
+    /// [`DictionaryVec::as_slice`]
+    #[inline]
+    pub fn as_slices(&self) -> DictionarySlice<'_, T> {
+        self.as_slice()
+    }
+
+    /// Mutably borrow every row, which for a dictionary column is the same as
+    /// [`DictionaryVec::as_mut_slice`]
+    #[inline]
+    pub fn as_mut_slices(&mut self) -> DictionarySliceMut<'_, T> {
+        self.as_mut_slice()
+    }
+
+    /// An iterator over each row
+    #[inline]
+    pub fn iter(&self) -> DictionaryIter<'_, T> {
+        self.as_slice().iter()
+    }
+
+    /// An iterator over a proxy for each row
+    #[inline]
+    pub fn iter_mut(&mut self) -> DictionaryIterMut<'_, T> {
+        self.as_mut_slice().into_iter()
+    }
+
+    /// Reorder the rows, in the same way as the other columns of a vec being sorted
+    #[doc(hidden)]
+    #[inline]
+    pub fn apply_permutation(&mut self, permutation: &Permutation) {
+        permutation.apply(&mut self.codes);
+    }
+}
+
+impl<T: Ord> DictionaryVec<T> {
+    /// The code of `value`, if it is in the dictionary
+    #[inline]
+    pub fn code(&self, value: &T) -> Option<u32> {
+        self.as_slice().code(value)
+    }
+
+    /// The code of `value`, adding it to the dictionary if it isn't there yet.
+    ///
+    /// Panics if there are more distinct values than a `u32` code can hold, which counts the
+    /// values that no row uses any more until [`DictionaryVec::shrink_dictionary`] removes them
+    pub fn intern(&mut self, value: T) -> u32 {
+        let dictionary = &self.dictionary;
+
+        match self
+            .sorted
+            .binary_search_by(|&code| dictionary[code as usize].cmp(&value))
+        {
+            Ok(position) => self.sorted[position],
+            Err(position) => {
+                let code = u32::try_from(self.dictionary.len())
+                    .expect("too many distinct values for a dictionary column");
+
+                self.dictionary.push(value);
+                self.sorted.insert(position, code);
+                code
+            }
+        }
+    }
+
+    /// Add a row to the end, interning its value
+    #[inline]
+    pub fn push(&mut self, value: T) {
+        let code = self.intern(value);
+        self.codes.push(code);
+    }
+
+    /// Insert a row at `index`, shifting all rows after it
+    #[inline]
+    pub fn insert(&mut self, index: usize, value: T) {
+        let code = self.intern(value);
+        self.codes.insert(index, code);
+    }
+
+    /// Resize to `new_len` rows, either by removing rows from the end or by adding rows with
+    /// `value`, which is only interned once
+    #[inline]
+    pub fn resize(&mut self, new_len: usize, value: T) {
+        match new_len > self.len() {
+            true => {
+                let code = self.intern(value);
+                self.codes.resize(new_len, code);
+            }
+            false => self.truncate(new_len),
+        }
+    }
+
+    /// Resize to `new_len` rows, either by removing rows from the end or by adding rows returned
+    /// by `f`
+    pub fn resize_with<F>(&mut self, new_len: usize, mut f: F)
+    where
+        F: FnMut() -> T,
+    {
+        match new_len.checked_sub(self.len()) {
+            Some(additional) => {
+                self.reserve(additional);
+                (0..additional).for_each(|_| self.push(f()));
+            }
+            None => self.truncate(new_len),
+        }
+    }
+}
+
+// the dictionary is shared with the remaining rows, so the value of a removed row is cloned out
+// of it
+impl<T: Clone> DictionaryVec<T> {
+    /// Remove the last row, if there is one
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        let code = self.codes.pop()?;
+        Some(self.dictionary[code as usize].clone())
+    }
+
+    /// Remove the row at `index`, shifting all rows after it
+    #[inline]
+    pub fn remove(&mut self, index: usize) -> T {
+        let code = self.codes.remove(index);
+        self.dictionary[code as usize].clone()
+    }
+
+    /// Remove the row at `index`, replacing it with the last row
+    #[inline]
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let code = self.codes.swap_remove(index);
+        self.dictionary[code as usize].clone()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Sync> DictionaryVec<T> {
+    /// A parallel iterator over each row
+    #[inline]
+    pub fn par_iter(&self) -> impl rayon::iter::IndexedParallelIterator<Item = &T> + '_ {
+        use rayon::prelude::*;
+
+        let dictionary = &self.dictionary;
+        self.codes
+            .par_iter()
+            .map(move |&code| &dictionary[code as usize])
+    }
+
+    /// A parallel iterator over a proxy for each row
+    #[inline]
+    pub fn par_iter_mut(
+        &mut self,
+    ) -> impl rayon::iter::IndexedParallelIterator<Item = DictionaryMut<'_, T>> + '_ {
+        use rayon::prelude::*;
+
+        let (dictionary, sorted) = (&self.dictionary, &self.sorted);
+        self.codes.par_iter_mut().map(move |code| DictionaryMut {
+            code,
+            dictionary,
+            sorted,
+        })
+    }
+
+    /// A parallel iterator over views of `chunk_size` rows. The last chunk may be shorter.
+    ///
+    /// Panics if `chunk_size` is 0
+    #[inline]
+    pub fn par_chunks_mut(
+        &mut self,
+        chunk_size: usize,
+    ) -> impl rayon::iter::IndexedParallelIterator<Item = DictionarySliceMut<'_, T>> + '_ {
+        use rayon::prelude::*;
+
+        assert!(chunk_size != 0, "chunk_size must not be zero");
+
+        let (dictionary, sorted) = (&self.dictionary, &self.sorted);
+        self.codes
+            .par_chunks_mut(chunk_size)
+            .map(move |codes| DictionarySliceMut {
+                codes,
+                dictionary,
+                sorted,
+            })
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DictionaryVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.as_slice(), f)
+    }
+}
+
+impl<T> Default for DictionaryVec<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// the codes depend on the order the values were added in, so the vec is compared and hashed by
+// its rows rather than its columns
+
+impl<T: PartialEq> PartialEq for DictionaryVec<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq> Eq for DictionaryVec<T> {}
+
+impl<T: PartialOrd> PartialOrd for DictionaryVec<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord> Ord for DictionaryVec<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash> Hash for DictionaryVec<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+
+        for row in self {
+            row.hash(state);
+        }
+    }
+}
+
+impl<T: Ord> Extend<T> for DictionaryVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for DictionaryVec<T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T: Clone> IntoIterator for DictionaryVec<T> {
+    type Item = T;
+    type IntoIter = DictionaryIntoIter<T>;
+
+    #[inline]
+    fn into_iter(self) -> DictionaryIntoIter<T> {
+        DictionaryIntoIter {
+            codes: self.codes.into_iter(),
+            dictionary: self.dictionary,
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a DictionaryVec<T> {
+    type Item = &'a T;
+    type IntoIter = DictionaryIter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> DictionaryIter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut DictionaryVec<T> {
+    type Item = DictionaryMut<'a, T>;
+    type IntoIter = DictionaryIterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> DictionaryIterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+/// A view of a range of rows of a [`DictionaryVec`], like a `&[T]`. It borrows the whole
+/// dictionary, so codes mean the same in every view of the same vec
+pub struct DictionarySlice<'a, T> {
+    codes: &'a [u32],
+    dictionary: &'a [T],
+    sorted: &'a [u32],
+}
+
+impl<'a, T> DictionarySlice<'a, T> {
+    /// The number of rows
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.codes.len()
+    }
+
+    /// Whether there are no rows
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+
+    /// The row at `index`, panicking if it is out of bounds
+    #[inline]
+    pub fn get(&self, index: usize) -> &'a T {
+        &self.dictionary[self.codes[index] as usize]
+    }
+
+    /// A view of a range of rows, panicking if it is out of bounds
+    #[inline]
+    pub fn slice(self, range: impl RangeBounds<usize>) -> Self {
+        let range = crate::slice::range(range, self.len());
+
+        Self {
+            codes: &self.codes[range],
+            ..self
+        }
+    }
+
+    /// Split the view into the rows before `mid` and the rows from `mid` onwards, panicking if
+    /// `mid > len`
+    #[inline]
+    pub fn split_at(self, mid: usize) -> (Self, Self) {
+        (self.slice(..mid), self.slice(mid..))
+    }
+
+    /// Every row, which for a dictionary column is the view itself
+    #[inline]
+    pub fn as_slices(&self) -> Self {
+        *self
+    }
+
+    /// An iterator over each row
+    #[inline]
+    pub fn iter(&self) -> DictionaryIter<'a, T> {
+        DictionaryIter {
+            codes: self.codes.iter(),
+            dictionary: self.dictionary,
+        }
+    }
+
+    /// The code of every row, which is its value's index in [`DictionarySlice::dictionary`]
+    #[inline]
+    pub fn codes(&self) -> &'a [u32] {
+        self.codes
+    }
+
+    /// Every distinct value in the vec, indexed by its code, including values that no row in
+    /// this view uses
+    #[inline]
+    pub fn dictionary(&self) -> &'a [T] {
+        self.dictionary
+    }
+
+    /// The code of `value`, if it is in the dictionary
+    #[inline]
+    pub fn code(&self, value: &T) -> Option<u32>
+    where
+        T: Ord,
+    {
+        code(self.dictionary, self.sorted, value)
+    }
+}
+
+impl<T> Clone for DictionarySlice<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for DictionarySlice<'_, T> {}
+
+impl<T: fmt::Debug> fmt::Debug for DictionarySlice<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for DictionarySlice<'_, T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for DictionarySlice<'_, T> {}
+
+impl<'a, T> IntoIterator for DictionarySlice<'a, T> {
+    type Item = &'a T;
+    type IntoIter = DictionaryIter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> DictionaryIter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> SovSlice for DictionarySlice<'_, T> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn split_at(self, mid: usize) -> (Self, Self) {
+        self.split_at(mid)
+    }
+}
+
+/// A mutable view of a range of rows of a [`DictionaryVec`]. The dictionary is shared with the
+/// rest of the vec, so rows can only be pointed at values that are already in it
+pub struct DictionarySliceMut<'a, T> {
+    codes: &'a mut [u32],
+    dictionary: &'a [T],
+    sorted: &'a [u32],
+}
+
+impl<'a, T> DictionarySliceMut<'a, T> {
+    /// The number of rows
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.codes.len()
+    }
+
+    /// Whether there are no rows
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+
+    /// The row at `index`, panicking if it is out of bounds
+    #[inline]
+    pub fn get(&self, index: usize) -> &T {
+        self.as_slice().get(index)
+    }
+
+    /// A proxy for the row at `index`, which can point it at another value in the dictionary,
+    /// panicking if it is out of bounds
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> DictionaryMut<'_, T> {
+        DictionaryMut {
+            code: &mut self.codes[index],
+            dictionary: self.dictionary,
+            sorted: self.sorted,
+        }
+    }
+
+    /// Borrow the view immutably, for as long as it is borrowed
+    #[inline]
+    pub fn as_slice(&self) -> DictionarySlice<'_, T> {
+        DictionarySlice {
+            codes: self.codes,
+            dictionary: self.dictionary,
+            sorted: self.sorted,
+        }
+    }
+
+    /// Borrow the view mutably for a shorter lifetime, so that it can be used again afterwards
+    #[inline]
+    pub fn reborrow(&mut self) -> DictionarySliceMut<'_, T> {
+        DictionarySliceMut {
+            codes: self.codes,
+            dictionary: self.dictionary,
+            sorted: self.sorted,
+        }
+    }
+
+    /// A view of a range of rows, panicking if it is out of bounds
+    #[inline]
+    pub fn slice_mut(self, range: impl RangeBounds<usize>) -> Self {
+        let range = crate::slice::range(range, self.len());
+
+        Self {
+            codes: &mut self.codes[range],
+            ..self
+        }
+    }
+
+    /// Split the view into the rows before `mid` and the rows from `mid` onwards, panicking if
+    /// `mid > len`
+    #[inline]
+    pub fn split_at(self, mid: usize) -> (Self, Self) {
+        let (before, after) = self.codes.split_at_mut(mid);
+
+        (
+            Self {
+                codes: before,
+                dictionary: self.dictionary,
+                sorted: self.sorted,
+            },
+            Self {
+                codes: after,
+                dictionary: self.dictionary,
+                sorted: self.sorted,
+            },
+        )
+    }
+
+    /// Every row, which for a dictionary column is the view itself
+    #[inline]
+    pub fn as_slices(&self) -> DictionarySlice<'_, T> {
+        self.as_slice()
+    }
+
+    /// Mutably borrow every row, which for a dictionary column is the view itself
+    #[inline]
+    pub fn as_mut_slices(&mut self) -> DictionarySliceMut<'_, T> {
+        self.reborrow()
+    }
+
+    /// An iterator over each row
+    #[inline]
+    pub fn iter(&self) -> DictionaryIter<'_, T> {
+        self.as_slice().iter()
+    }
+
+    /// An iterator over a proxy for each row
+    #[inline]
+    pub fn iter_mut(&mut self) -> DictionaryIterMut<'_, T> {
+        self.reborrow().into_iter()
+    }
+
+    /// The code of every row, which is its value's index in [`DictionarySliceMut::dictionary`]
+    #[inline]
+    pub fn codes(&self) -> &[u32] {
+        self.codes
+    }
+
+    /// Every distinct value in the vec, indexed by its code, including values that no row in
+    /// this view uses
+    #[inline]
+    pub fn dictionary(&self) -> &'a [T] {
+        self.dictionary
+    }
+
+    /// The code of `value`, if it is in the dictionary
+    #[inline]
+    pub fn code(&self, value: &T) -> Option<u32>
+    where
+        T: Ord,
+    {
+        code(self.dictionary, self.sorted, value)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DictionarySliceMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.as_slice(), f)
+    }
+}
+
+impl<'a, T> IntoIterator for DictionarySliceMut<'a, T> {
+    type Item = DictionaryMut<'a, T>;
+    type IntoIter = DictionaryIterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> DictionaryIterMut<'a, T> {
+        DictionaryIterMut {
+            codes: self.codes.iter_mut(),
+            dictionary: self.dictionary,
+            sorted: self.sorted,
+        }
+    }
+}
+
+impl<T> SovSlice for DictionarySliceMut<'_, T> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn split_at(self, mid: usize) -> (Self, Self) {
+        self.split_at(mid)
+    }
+}
+
+/// A proxy for a single row of a [`DictionaryVec`], which dereferences to its value and can
+/// point it at another value in the dictionary.
+///
+/// The dictionary is shared with the other rows, so a value that isn't in it yet can't be added
+/// through a proxy; intern it with [`DictionaryVec::intern`] first. It compares, orders and
+/// hashes as the `&T` it points to
+pub struct DictionaryMut<'a, T> {
+    code: &'a mut u32,
+    dictionary: &'a [T],
+    sorted: &'a [u32],
+}
+
+impl<T> DictionaryMut<'_, T> {
+    /// The code of the row's value
+    #[inline]
+    pub fn code(&self) -> u32 {
+        *self.code
+    }
+
+    /// Point the row at the value with `code`, panicking if it isn't in the dictionary
+    #[inline]
+    pub fn set_code(&mut self, code: u32) {
+        assert!(
+            (code as usize) < self.dictionary.len(),
+            "code {code} is not in a dictionary of {} values",
+            self.dictionary.len(),
+        );
+
+        *self.code = code;
+    }
+
+    /// Borrow the row mutably for a shorter lifetime, so that this proxy can be used again
+    /// afterwards
+    #[inline]
+    pub fn reborrow(&mut self) -> DictionaryMut<'_, T> {
+        DictionaryMut {
+            code: self.code,
+            dictionary: self.dictionary,
+            sorted: self.sorted,
+        }
+    }
+
+    /// Clone the value of the row
+    #[inline]
+    pub fn to_owned(&self) -> T
+    where
+        T: Clone,
+    {
+        self.as_ref().clone()
+    }
+}
+
+impl<T: Ord> DictionaryMut<'_, T> {
+    /// Whether `value` is in the dictionary, so that the row can be pointed at it
+    #[inline]
+    pub fn contains(&self, value: &T) -> bool {
+        code(self.dictionary, self.sorted, value).is_some()
+    }
+
+    /// Point the row at `value`, panicking if it isn't in the dictionary
+    #[inline]
+    pub fn set(&mut self, value: T) {
+        match code(self.dictionary, self.sorted, &value) {
+            Some(code) => *self.code = code,
+            None => panic!("the value is not in the dictionary, so it must be interned first"),
+        }
+    }
+
+    /// Point the row at `value`, and return a clone of the old value, panicking if `value` isn't
+    /// in the dictionary
+    #[inline]
+    pub fn replace(&mut self, value: T) -> T
+    where
+        T: Clone,
+    {
+        let old = self.to_owned();
+        self.set(value);
+        old
+    }
+}
+
+// `FooRefMut::as_ref` calls `as_ref` on the proxy for a dictionary field, to borrow it as the
+// `&T` that a `FooRef` holds
+impl<T> AsRef<T> for DictionaryMut<'_, T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        &self.dictionary[*self.code as usize]
+    }
+}
+
+impl<T> Deref for DictionaryMut<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        self.as_ref()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DictionaryMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_ref(), f)
+    }
+}
+
+impl<'b, T: PartialEq> PartialEq<DictionaryMut<'b, T>> for DictionaryMut<'_, T> {
+    #[inline]
+    fn eq(&self, other: &DictionaryMut<'b, T>) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl<T: Eq> Eq for DictionaryMut<'_, T> {}
+
+impl<'b, T: PartialOrd> PartialOrd<DictionaryMut<'b, T>> for DictionaryMut<'_, T> {
+    #[inline]
+    fn partial_cmp(&self, other: &DictionaryMut<'b, T>) -> Option<Ordering> {
+        self.as_ref().partial_cmp(other.as_ref())
+    }
+}
+
+impl<T: Ord> Ord for DictionaryMut<'_, T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_ref().cmp(other.as_ref())
+    }
+}
+
+impl<T: Hash> Hash for DictionaryMut<'_, T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_ref().hash(state);
+    }
+}
+
+/// The code of `value` in `dictionary`, given its codes ordered by value
+fn code<T: Ord>(dictionary: &[T], sorted: &[u32], value: &T) -> Option<u32> {
+    sorted
+        .binary_search_by(|&code| dictionary[code as usize].cmp(value))
+        .ok()
+        .map(|position| sorted[position])
+}
+
+/// An iterator over the rows of a [`DictionaryVec`] or [`DictionarySlice`]
+pub struct DictionaryIter<'a, T> {
+    codes: slice::Iter<'a, u32>,
+    dictionary: &'a [T],
+}
+
+impl<T> Clone for DictionaryIter<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            codes: self.codes.clone(),
+            dictionary: self.dictionary,
+        }
+    }
+}
+
+impl<'a, T> Iterator for DictionaryIter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        let code = self.codes.next()?;
+        Some(&self.dictionary[*code as usize])
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.codes.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for DictionaryIter<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a T> {
+        let code = self.codes.next_back()?;
+        Some(&self.dictionary[*code as usize])
+    }
+}
+
+impl<T> ExactSizeIterator for DictionaryIter<'_, T> {}
+
+impl<T> FusedIterator for DictionaryIter<'_, T> {}
+
+/// An iterator over a proxy for each row of a [`DictionaryVec`] or [`DictionarySliceMut`]
+pub struct DictionaryIterMut<'a, T> {
+    codes: slice::IterMut<'a, u32>,
+    dictionary: &'a [T],
+    sorted: &'a [u32],
+}
+
+impl<'a, T> Iterator for DictionaryIterMut<'a, T> {
+    type Item = DictionaryMut<'a, T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<DictionaryMut<'a, T>> {
+        Some(DictionaryMut {
+            code: self.codes.next()?,
+            dictionary: self.dictionary,
+            sorted: self.sorted,
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.codes.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for DictionaryIterMut<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<DictionaryMut<'a, T>> {
+        Some(DictionaryMut {
+            code: self.codes.next_back()?,
+            dictionary: self.dictionary,
+            sorted: self.sorted,
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for DictionaryIterMut<'_, T> {}
+
+impl<T> FusedIterator for DictionaryIterMut<'_, T> {}
+
+/// An iterator over the rows of a [`DictionaryVec`] by value, which clones each value out of the
+/// dictionary
+pub struct DictionaryIntoIter<T> {
+    codes: vec::IntoIter<u32>,
+    dictionary: Vec<T>,
+}
+
+impl<T: Clone> Clone for DictionaryIntoIter<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            codes: self.codes.clone(),
+            dictionary: self.dictionary.clone(),
+        }
+    }
+}
+
+impl<T: Clone> Iterator for DictionaryIntoIter<T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        let code = self.codes.next()?;
+        Some(self.dictionary[code as usize].clone())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.codes.size_hint()
+    }
+}
+
+impl<T: Clone> DoubleEndedIterator for DictionaryIntoIter<T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        let code = self.codes.next_back()?;
+        Some(self.dictionary[code as usize].clone())
+    }
+}
+
+impl<T: Clone> ExactSizeIterator for DictionaryIntoIter<T> {}
+
+impl<T: Clone> FusedIterator for DictionaryIntoIter<T> {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    /// Check that every code points into the dictionary, and that the rows match the model
+    fn assert_matches(vec: &DictionaryVec<&str>, model: &[&str]) {
+        assert_eq!(vec.len(), model.len());
+        assert!(vec
+            .codes()
+            .iter()
+            .all(|&code| (code as usize) < vec.dictionary().len()));
+        assert!(vec.iter().eq(model.iter()));
+        assert_eq!(vec.sorted.len(), vec.dictionary().len());
+    }
+
+    #[test]
+    fn repeated_values_reuse_their_code() {
+        let mut vec: DictionaryVec<_> = ["b", "a", "b", "c", "a"].into_iter().collect();
+
+        assert_eq!(vec.dictionary(), &["b", "a", "c"]);
+        assert_eq!(vec.codes(), &[0, 1, 0, 2, 1]);
+
+        vec.insert(1, "c");
+        vec.resize(8, "a");
+        assert_eq!(vec.intern("b"), 0);
+        assert_eq!(vec.dictionary(), &["b", "a", "c"]);
+        assert_eq!(vec.codes(), &[0, 2, 1, 0, 2, 1, 1, 1]);
+
+        assert_eq!(vec.code(&"c"), Some(2));
+        assert_eq!(vec.code(&"d"), None);
+    }
+
+    #[test]
+    fn remove_and_swap_remove_keep_codes_valid() {
+        let values = ["d", "a", "c", "a", "b", "d", "c", "a"];
+
+        for index in 0..values.len() {
+            let mut model = Vec::from(values);
+            let mut vec: DictionaryVec<_> = values.into_iter().collect();
+
+            assert_eq!(vec.remove(index), model.remove(index));
+            assert_matches(&vec, &model);
+
+            if index < model.len() {
+                assert_eq!(vec.swap_remove(index), model.swap_remove(index));
+                assert_matches(&vec, &model);
+            }
+
+            // the dictionary keeps values that no row uses any more, so they keep their codes
+            assert_eq!(vec.dictionary(), &["d", "a", "c", "b"]);
+            vec.push("b");
+            model.push("b");
+            assert_eq!(vec.codes().last(), Some(&3));
+            assert_matches(&vec, &model);
+        }
+
+        let mut vec: DictionaryVec<_> = values.into_iter().collect();
+        while vec.pop().is_some() {}
+        assert!(vec.is_empty());
+        assert_eq!(vec.dictionary().len(), 4);
+    }
+
+    #[test]
+    fn comparisons_ignore_the_order_of_the_codes() {
+        let first: DictionaryVec<_> = ["x", "y", "x"].into_iter().collect();
+
+        // the same rows, but with the values added to the dictionary in the opposite order
+        let mut second: DictionaryVec<_> = ["y", "x"].into_iter().collect();
+        second.remove(0);
+        second.push("y");
+        second.push("x");
+
+        assert_ne!(first.codes(), second.codes());
+        assert_eq!(first, second);
+        assert_eq!(first.cmp(&second), Ordering::Equal);
+        assert_eq!(first.as_slice(), second.as_slice());
+        assert_eq!(first.clone(), second.clone());
+        assert_eq!(first.clone().codes(), first.codes());
+
+        let mut third = second.clone();
+        third.get_mut(2).set("y");
+        assert_ne!(first, third);
+        assert_eq!(first.cmp(&third), Ordering::Less);
+    }
+
+    #[test]
+    fn set_and_replace_values_in_the_dictionary() {
+        let mut vec: DictionaryVec<_> = ["a", "b", "a"].into_iter().collect();
+
+        vec.get_mut(0).set("b");
+        assert_eq!(vec.get_mut(2).replace("b"), "a");
+        assert_eq!(vec.codes(), &[1, 1, 1]);
+
+        vec.get_mut(1).set_code(0);
+        assert_matches(&vec, &["b", "a", "b"]);
+    }
+
+    #[test]
+    fn shrink_dictionary_removes_unused_values_and_remaps_codes() {
+        let mut vec: DictionaryVec<_> = ["d", "a", "c", "a", "b"].into_iter().collect();
+        vec.remove(0);
+        vec.swap_remove(1);
+
+        vec.shrink_dictionary();
+        assert_eq!(vec.dictionary(), &["a", "b"]);
+        assert_eq!(vec.codes(), &[0, 1, 0]);
+        assert_matches(&vec, &["a", "b", "a"]);
+
+        // the remapped codes are still ordered by value, so values can be found and interned
+        assert_eq!(vec.code(&"b"), Some(1));
+        assert_eq!(vec.code(&"c"), None);
+        vec.push("c");
+        vec.push("a");
+        assert_matches(&vec, &["a", "b", "a", "c", "a"]);
+        assert_eq!(vec.codes(), &[0, 1, 0, 2, 0]);
+
+        vec.clear();
+        vec.extend(["x", "y"]);
+        vec.shrink_to_fit();
+        assert_eq!(vec.dictionary(), &["x", "y"]);
+        assert_eq!(vec.codes(), &[0, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "the value is not in the dictionary, so it must be interned first")]
+    fn set_panics_for_a_value_not_in_the_dictionary() {
+        let mut vec: DictionaryVec<_> = ["a", "b"].into_iter().collect();
+        assert!(vec.get_mut(0).contains(&"b"));
+        assert!(!vec.get_mut(0).contains(&"c"));
+        vec.get_mut(0).set("c");
+    }
+}
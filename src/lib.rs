@@ -7,11 +7,12 @@
 //!     x: u64,
 //!     y: String,
 //! }
+//! # fn main() {}
 //! ```
 //! will generate a struct which looks roughly like this:
 //! ```rust
 //! struct VecFoo {
-//!     x: Vec<u64>
+//!     x: Vec<u64>,
 //!     y: Vec<String>,
 //! }
 //! ```
@@ -39,7 +40,7 @@
 //!
 //!     // equivalent to &vec[0]
 //!     let first = vec.get(0);
-//!     assert_eq!(first.x, 0);
+//!     assert_eq!(*first.x, 0);
 //! }
 //! ```
 //! ## Why would you want to do this?
@@ -119,7 +120,7 @@
 //! extreme performance improvements. For example, the following code will likely see no
 //! performance improvement compared to the naive equivalent (or even worse!):
 //! ```rust
-//! # fn generate_random_vec() -> VecBar { VecBar {x: vec![], y: vec![], } }
+//! # fn generate_random_vec() -> VecBar { VecBar::new() }
 //! # use sov::StructOfVecs;
 //! #[derive(StructOfVecs)]
 //! struct Bar {
@@ -151,37 +152,1376 @@
 //! `VecFoo` representation, that is), since every field is stored separately and stored
 //! contiguously, as guaranteed by `Vec`.
 //!
+//! ## Iterating
+//!
+//! The generated type can be iterated over by reference, mutable reference or value, yielding
+//! `FooRef`, `FooRefMut` and `Foo` respectively. The iterators (`FooIter`, `FooIterMut` and
+//! `FooIntoIter`) walk every column in lockstep, so they don't need any bounds checks:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs, Debug, PartialEq)]
+//! struct Particle {
+//!     position: f32,
+//!     velocity: f32,
+//! }
+//!
+//! fn main() {
+//!     let mut particles = VecParticle::new();
+//!     particles.push(Particle { position: 0.0, velocity: 1.0 });
+//!     particles.push(Particle { position: 5.0, velocity: -1.0 });
+//!
+//!     for ParticleRefMut { position, velocity } in &mut particles {
+//!         *position += *velocity;
+//!     }
+//!
+//!     let last = particles.iter().next_back().unwrap();
+//!     assert_eq!(*last.position, 4.0);
+//!
+//!     let particles: Vec<Particle> = particles.into_iter().collect();
+//!     assert_eq!(particles[0], Particle { position: 1.0, velocity: 1.0 });
+//! }
+//! ```
+//! `drain(range)` removes a range of rows and returns a `FooDrain` that yields them by value, like
+//! `Vec::drain`. The rows after the range are moved up, and any rows that weren't yielded are
+//! dropped along with the iterator:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! # #[derive(StructOfVecs, Debug, PartialEq)]
+//! # struct Particle {
+//! #     position: f32,
+//! #     velocity: f32,
+//! # }
+//! # fn main() {
+//! let mut particles: VecParticle = (0..5)
+//!     .map(|i| Particle { position: i as f32, velocity: 1.0 })
+//!     .collect();
+//!
+//! let mut drained = particles.drain(1..4);
+//! assert_eq!(drained.len(), 3);
+//! assert_eq!(drained.next(), Some(Particle { position: 1.0, velocity: 1.0 }));
+//! assert_eq!(drained.next_back(), Some(Particle { position: 3.0, velocity: 1.0 }));
+//! drop(drained);
+//!
+//! assert_eq!(particles.positions(), &[0.0, 4.0]);
+//! # }
+//! ```
+//! Only the rows from the start of the range onwards are moved, and taking a row from either end is
+//! `O(1)`, so draining the end of a long vec, or draining it in reverse, is as cheap as with a
+//! `Vec`. If the `FooDrain` is leaked, the rows that weren't yielded may be left at the end of the
+//! vec:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs, Clone, Debug, PartialEq)]
+//! enum Token {
+//!     Word(String),
+//!     Number(u32),
+//!     End,
+//! }
+//!
+//! fn main() {
+//!     let tokens: Vec<Token> = (0..6)
+//!         .map(|i: u32| match i % 3 {
+//!             0 => Token::Word(i.to_string()),
+//!             1 => Token::Number(i),
+//!             _ => Token::End,
+//!         })
+//!         .collect();
+//!
+//!     // stopping partway drops the rest of the range
+//!     let (mut vec, mut model) = (VecToken::from(tokens.clone()), tokens.clone());
+//!     let (mut drained, mut expected) = (vec.drain(1..5), model.drain(1..5));
+//!     assert_eq!(drained.next(), expected.next());
+//!     assert_eq!(drained.next_back(), expected.next_back());
+//!     assert_eq!(drained.len(), 2);
+//!     drop((drained, expected));
+//!     assert_eq!(Vec::from(vec), model);
+//!
+//!     // in reverse
+//!     let (mut vec, mut model) = (VecToken::from(tokens.clone()), tokens.clone());
+//!     assert!(vec.drain(2..).rev().eq(model.drain(2..).rev()));
+//!     vec.push(Token::End);
+//!     model.push(Token::End);
+//!     assert_eq!(Vec::from(vec), model);
+//!
+//!     // leaked after yielding one row from the front
+//!     let mut vec = VecToken::from(tokens.clone());
+//!     let mut drained = vec.drain(..3);
+//!     assert_eq!(drained.next(), Some(tokens[0].clone()));
+//!     std::mem::forget(drained);
+//!     assert_eq!(vec.len(), 5);
+//!     assert_eq!(vec.kinds()[0], TokenKind::Word);
+//!     assert_eq!(vec.word_field0s(), ["3"]);
+//! }
+//! ```
+//!
+//! ## Converting
+//!
+//! The generated type implements `FromIterator` and `Extend`, so it can be built with `collect()`,
+//! and converts to and from a `Vec` or an array of the original type. `Extend` reserves space in
+//! every column up front, using the iterator's `size_hint`. Rows can also be cloned from a slice
+//! with `extend_from_slice`, or from another vec's references if every field is `Clone`:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs, Clone, Debug, PartialEq)]
+//! struct Point {
+//!     x: i32,
+//!     y: i32,
+//! }
+//!
+//! fn main() {
+//!     let mut points: VecPoint = (0..3).map(|x| Point { x, y: 0 }).collect();
+//!     points.extend_from_slice(&[Point { x: 3, y: 0 }]);
+//!
+//!     let mut copy = VecPoint::from([Point { x: -1, y: 0 }]);
+//!     copy.extend(points.iter());
+//!     assert_eq!(copy.len(), 5);
+//!
+//!     let points: Vec<Point> = points.into();
+//!     assert_eq!(points[3], Point { x: 3, y: 0 });
+//! }
+//! ```
+//!
+//! ## Capacity
+//!
+//! `capacity`, `reserve`, `reserve_exact`, `shrink_to_fit`, `shrink_to`, `truncate`, `clear`,
+//! `resize` and `resize_with` work like they do for `Vec`, applying the same change to every
+//! column. The capacity of the vec is the smallest capacity of any column. `resize` only needs
+//! each field to be `Clone`, since the value is split into its fields and each column is resized
+//! separately:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs, Debug, PartialEq)]
+//! struct Pixel {
+//!     r: u8,
+//!     g: u8,
+//!     b: u8,
+//! }
+//!
+//! fn main() {
+//!     let mut pixels = VecPixel::with_capacity(16);
+//!     assert!(pixels.capacity() >= 16);
+//!
+//!     pixels.resize(4, Pixel { r: 0, g: 0, b: 0 });
+//!     pixels.resize_with(6, || Pixel { r: 255, g: 255, b: 255 });
+//!     assert_eq!(pixels.rs(), &[0, 0, 0, 0, 255, 255]);
+//!
+//!     pixels.truncate(2);
+//!     pixels.shrink_to_fit();
+//!     assert_eq!(pixels.len(), 2);
+//!     assert!(pixels.capacity() >= 2);
+//!
+//!     pixels.clear();
+//!     assert!(pixels.is_empty());
+//! }
+//! ```
+//!
+//! ## Borrowing several columns
+//!
+//! Accessors like `xs_mut()` borrow the whole vec, so one column can't be read while another is
+//! being written. `as_slices()` and `as_mut_slices()` borrow every column at once, returning a
+//! `FooSlices` or `FooSlicesMut` with one slice per field:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! struct Particle {
+//!     position: f32,
+//!     velocity: f32,
+//! }
+//!
+//! fn main() {
+//!     let mut particles = VecParticle::new();
+//!     particles.push(Particle { position: 0.0, velocity: 1.0 });
+//!
+//!     let ParticleSlicesMut { position, velocity } = particles.as_mut_slices();
+//!     for (position, velocity) in position.iter_mut().zip(velocity.iter()) {
+//!         *position += *velocity;
+//!     }
+//!
+//!     assert_eq!(particles.as_slices().position, &[1.0]);
+//! }
+//! ```
+//! For enums, the slices struct has a field for each column, named as in [Enums](#enums), along with
+//! a shared slice of the kinds.
+//!
+//! ## Slicing
+//!
+//! `as_slice()` and `as_mut_slice()` borrow the rows as a `FooSlice` or `FooSliceMut`, which work
+//! like a `&[Foo]` or `&mut [Foo]`. They can be sliced with `slice(range)`, split with `split_at`
+//! and `split_at_mut`, and iterated over in chunks with `chunks`, `chunks_exact` and `windows` (or
+//! `chunks_mut` and `chunks_exact_mut`), as well as having `len`, `get`, `first`, `last` and `iter`:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! struct Sample {
+//!     time: u32,
+//!     value: f32,
+//! }
+//!
+//! fn main() {
+//!     let mut samples: VecSample = (0..10).map(|time| Sample { time, value: 1.0 }).collect();
+//!
+//!     let mut view = samples.as_mut_slice();
+//!     let (mut before, mut after) = view.split_at_mut(5);
+//!     *before.last_mut().unwrap().value = 0.0;
+//!     for sample in after.iter_mut() {
+//!         *sample.value *= 2.0;
+//!     }
+//!
+//!     let samples = samples.as_slice();
+//!     assert_eq!(*samples.slice(3..).first().unwrap().time, 3);
+//!
+//!     let sums: Vec<f32> = samples
+//!         .chunks(4)
+//!         .map(|chunk| chunk.iter().map(|sample| *sample.value).sum())
+//!         .collect();
+//!     assert_eq!(sums, [4.0, 6.0, 4.0]);
+//! }
+//! ```
+//! The views also implement [`SovSlice`], which is what the chunk iterators are generic over.
+//!
+//! ## Sorting
+//!
+//! `sort_by`, `sort_by_key` and their `sort_unstable_*` versions sort the rows by comparing
+//! `FooRef`s. For each field `x` whose type is `Ord`, there are also `sort_by_x()` and
+//! `sort_unstable_by_x()`, which only need to look at that column. `dedup_by_key` removes
+//! consecutive rows with the same key.
+//!
+//! Rather than moving rows around while sorting, the indices of the rows are sorted, and then every
+//! column is reordered once:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! struct Reading {
+//!     timestamp: u64,
+//!     sensor: u8,
+//!     value: f32,
+//! }
+//!
+//! fn main() {
+//!     let mut readings = VecReading::from([
+//!         Reading { timestamp: 30, sensor: 1, value: 0.5 },
+//!         Reading { timestamp: 10, sensor: 2, value: 1.5 },
+//!         Reading { timestamp: 20, sensor: 1, value: 2.5 },
+//!     ]);
+//!
+//!     readings.sort_by_timestamp();
+//!     assert_eq!(readings.values(), &[1.5, 2.5, 0.5]);
+//!
+//!     readings.sort_by_key(|reading| *reading.sensor);
+//!     readings.dedup_by_key(|reading| *reading.sensor);
+//!     assert_eq!(readings.timestamps(), &[20, 10]);
+//! }
+//! ```
+//! The per-field methods aren't generated for flattened fields, or for a field called `key` (which
+//! would clash with `sort_by_key`), or for enums.
+//!
+//! ## Parallel iteration
+//!
+//! With the `rayon` feature enabled, the generated type also has `par_iter()` and `par_iter_mut()`,
+//! which return indexed parallel iterators over `FooRef` and `FooRefMut`, and
+//! `par_chunks_mut(chunk_size)`, which returns a parallel iterator over `FooSlicesMut` for each
+//! chunk of rows. It also implements `ParallelExtend` and `FromParallelIterator`:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! struct Particle {
+//!     position: f32,
+//!     velocity: f32,
+//! }
+//!
+//! # #[cfg(feature = "rayon")]
+//! fn main() {
+//!     use rayon::prelude::*;
+//!
+//!     let mut particles: VecParticle = (0..1000)
+//!         .into_par_iter()
+//!         .map(|i| Particle { position: i as f32, velocity: 1.0 })
+//!         .collect();
+//!
+//!     particles
+//!         .par_iter_mut()
+//!         .for_each(|ParticleRefMut { position, velocity }| *position += *velocity);
+//!
+//!     assert_eq!(*particles.get(999).position, 1000.0);
+//! }
+//! # #[cfg(not(feature = "rayon"))]
+//! # fn main() {}
+//! ```
+//! Enums only have `par_iter()`, since their rows can't be split into disjoint chunks.
+//!
+//! ## `no_std`
+//!
+//! This crate is `no_std`, and only needs `alloc`. The generated code refers to `Vec` through this
+//! crate rather than through `std`, so it can be derived in a `#![no_std]` crate too, without even
+//! declaring `extern crate alloc`:
+//! ```rust
+//! #![no_std]
+//! # // a doctest is a binary, which needs the allocator and panic handler from `std`
+//! # extern crate std;
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! struct Sample {
+//!     timestamp: u32,
+//!     value: i16,
+//! }
+//!
+//! fn main() {
+//!     let mut samples = VecSample::new();
+//!     samples.push(Sample { timestamp: 0, value: -3 });
+//!     assert_eq!(samples.values(), &[-3]);
+//! }
+//! ```
+//! The `rayon` feature needs `std`, since rayon does. For storage that doesn't allocate at all,
+//! see [fixed-capacity arrays](#fixed-capacity-arrays).
+//!
+//! ## Generics
+//!
+//! Generic structs are supported, including lifetimes, const generics and where clauses. The
+//! generated types have the same generic parameters as the original struct, and the reference types
+//! have an additional lifetime parameter in front of them:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! struct Point<'a, T: Copy, const N: usize>
+//! where
+//!     T: Default,
+//! {
+//!     coords: [T; N],
+//!     label: &'a str,
+//! }
+//!
+//! fn main() {
+//!     let mut points: VecPoint<'static, f32, 2> = VecPoint::new();
+//!     points.push(Point {
+//!         coords: [1.0, 2.0],
+//!         label: "origin-ish",
+//!     });
+//!
+//!     let PointRef { coords, label } = points.get(0);
+//!     assert_eq!(coords, &[1.0, 2.0]);
+//!     assert_eq!(*label, "origin-ish");
+//! }
+//! ```
+//!
+//! ## Tuple structs
+//!
+//! Tuple structs work the same way, with the accessors for each column named after the position of
+//! the field (unless renamed with `#[sov(accessor = "...")]`):
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! struct Pair(u64, #[sov(accessor = "flags")] u8);
+//!
+//! fn main() {
+//!     let mut pairs = VecPair::new();
+//!     pairs.push(Pair(1, 2));
+//!     pairs.push(Pair(3, 4));
+//!
+//!     assert_eq!(pairs.field0s(), &[1, 3]);
+//!     assert_eq!(pairs.flags(), &[2, 4]);
+//!
+//!     let PairRef(first, second) = pairs.get(1);
+//!     assert_eq!((*first, *second), (3, 4));
+//! }
+//! ```
+//!
+//! Structs without any fields (e.g. `struct Marker;`) are also supported. Since there are no
+//! columns, the generated type just counts the number of rows, and the reference types are
+//! zero-sized:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! struct Marker;
+//!
+//! fn main() {
+//!     let mut markers = VecMarker::new();
+//!     markers.push(Marker);
+//!     markers.push(Marker);
+//!
+//!     assert_eq!(markers.len(), 2);
+//!     assert!(markers.pop().is_some());
+//!     assert_eq!(core::mem::size_of_val(&markers.get(0)), 0);
+//! }
+//! ```
+//!
+//! ## Enums
+//!
+//! Enums can also derive `StructOfVecs`. The generated type stores the kind of each row in one
+//! column, and the fields of each variant in their own columns:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! enum Event {
+//!     Click { x: i32, y: i32 },
+//!     Key(char),
+//!     Quit,
+//! }
+//!
+//! fn main() {
+//!     let mut events = VecEvent::new();
+//!     events.push(Event::Click { x: 1, y: 2 });
+//!     events.push(Event::Key('a'));
+//!     events.push(Event::Click { x: 3, y: 4 });
+//!     events.push(Event::Quit);
+//!
+//!     // each variant's fields can be scanned without looking at the other variants
+//!     assert_eq!(events.click_xs(), &[1, 3]);
+//!     assert_eq!(events.key_field0s(), &['a']);
+//!     assert_eq!(events.kinds()[1], EventKind::Key);
+//!
+//!     for event in &events {
+//!         match event {
+//!             EventRef::Click { x, y } => println!("click at {x}, {y}"),
+//!             EventRef::Key(key) => println!("pressed {key}"),
+//!             EventRef::Quit => println!("quit"),
+//!         }
+//!     }
+//! }
+//! ```
+//! Pushing and popping are `O(1)`, but `insert`, `remove` and `swap_remove` are `O(n)`, since the
+//! rows of each variant are kept in order. For the same reason, slicing or splitting a view of an
+//! enum is `O(n)`, since it needs to count the rows of each variant on one side of the split.
+//! Variants without fields don't have any columns, so their rows only take up a kind and a slot:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! # #[derive(StructOfVecs, Debug, PartialEq)]
+//! # enum Event {
+//! #     Click { x: i32, y: i32 },
+//! #     Key(char),
+//! #     Quit,
+//! # }
+//! # fn main() {
+//! let mut events = VecEvent::new();
+//! events.push(Event::Quit);
+//! events.push(Event::Key('a'));
+//! events.push(Event::Quit);
+//! events.push(Event::Quit);
+//! events.push(Event::Key('b'));
+//!
+//! assert_eq!(events.remove(0), Event::Quit);
+//! assert_eq!(events.swap_remove(1), Event::Quit);
+//! events.insert(0, Event::Quit);
+//!
+//! assert_eq!(events.kinds(), &[EventKind::Quit, EventKind::Key, EventKind::Key, EventKind::Quit]);
+//! assert_eq!(events.key_field0s(), &['a', 'b']);
+//! assert_eq!(events.remove(3), Event::Quit);
+//! assert_eq!(events.pop(), Some(Event::Key('b')));
+//! assert_eq!(events.pop(), Some(Event::Key('a')));
+//! assert_eq!(events.pop(), Some(Event::Quit));
+//! assert!(events.is_empty());
+//! # }
+//! ```
+//!
+//! It also isn't known in advance which variants will be pushed, so `with_capacity`, `reserve` and
+//! `reserve_exact` only allocate the column of kinds, and `capacity` only counts that column. The
+//! methods that shrink the vec, like `truncate` and `shrink_to_fit`, apply to every column.
+//!
+//! The name of the kind enum can be changed with `#[sov(kind = "...")]`, which is a compile error
+//! on a struct:
+//! ```rust,compile_fail
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! #[sov(kind = "FooKind")]
+//! struct Foo {
+//!     x: u64,
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! ## Configuration
+//!
+//! The generated code can be customized with `#[sov(...)]` attributes.
+//!
+//! On the struct itself:
+//!  - `#[sov(vec = "...")]` sets the name of the generated `Vec`-like type (default `Vec{name}`)
+//!  - `#[sov(ref = "...")]` sets the name of the shared reference type (default `{name}Ref`)
+//!  - `#[sov(ref_mut = "...")]` sets the name of the mutable reference type (default
+//!    `{name}RefMut`)
+//!  - `#[sov(iter = "...")]`, `iter_mut`, `into_iter`, `drain`, `slices`, `slices_mut`, `slice`
+//!    and `slice_mut` set the names of the other generated types, which default to `{name}Iter`,
+//!    `{name}IterMut`, `{name}IntoIter`, `{name}Drain`, `{name}Slices`, `{name}SlicesMut`,
+//!    `{name}Slice` and `{name}SliceMut`, in case one of them clashes with something else
+//!  - `#[sov(module = "...")]` sets the name of the hidden module that the `Vec`-like type is
+//!    defined in, in case the default clashes with something else
+//!  - `#[sov(kind = "...")]` sets the name of the kind enum (enums only, default `{name}Kind`)
+//!  - `#[sov(crate = "...")]` sets the path to this crate, for when it is re-exported from another
+//!    crate (default `::sov`)
+//!  - `#[sov(expose_columns)]` gives the columns of the `Vec`-like type the visibility of their
+//!    fields (see [below](#column-visibility))
+//!  - `#[sov(layout = "...")]` sets how the columns are stored, either `"separate"` (the default)
+//!    or `"single"` (see [below](#single-allocation))
+//!  - `#[sov(array)]` also generates `Array{name}<N>`, which stores up to `N` rows inline (see
+//!    [below](#fixed-capacity-arrays)). `#[sov(array = "...")]` sets its name, and
+//!    `#[sov(array_into_iter = "...")]` sets the name of its owning iterator (default
+//!    `{name}ArrayIntoIter`)
+//!  - `#[sov(allocator)]` gives the `Vec`-like type an allocator parameter (see
+//!    [below](#custom-allocators))
+//!  - `#[sov(derive(...))]` implements standard traits for the generated types (see
+//!    [below](#standard-traits))
+//!
+//! On individual fields:
+//!  - `#[sov(accessor = "...")]` sets the name of the slice accessor for that field (default is the
+//!    field name, naively pluralized). The mutable accessor has `_mut` appended
+//!  - `#[sov(vis = "...")]` sets the visibility of the accessors for that field (default is the
+//!    visibility of the field)
+//!  - `#[sov(flatten)]` stores the field in the `Vec`-like type generated for the field's own type
+//!    (see [below](#nested-structs))
+//!  - `#[sov(column = "...")]` stores the field in another type that implements [`Column`],
+//!    rather than a `Vec` (see [below](#custom-columns))
+//!  - `#[sov(bitpacked)]` stores a `bool` field one bit per row in a [`BitVec`] (see
+//!    [below](#bit-packed-bools))
+//!  - `#[sov(nullable)]` stores an `Option` field as a column of values and a validity bitmap in
+//!    a [`NullableVec`] (see [below](#nullable-options))
+//!  - `#[sov(dictionary)]` stores a field with few distinct values as a column of `u32` codes
+//!    into a table of those values, in a [`DictionaryVec`] (see [below](#dictionary-columns))
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! #[sov(vec = "Sensors", ref = "Sensor", ref_mut = "SensorMut")]
+//! struct SensorReading {
+//!     #[sov(accessor = "data")]
+//!     data: f64,
+//!     #[sov(accessor = "statuses", vis = "pub(crate)")]
+//!     status: u8,
+//! }
+//!
+//! fn main() {
+//!     let mut sensors = Sensors::new();
+//!     sensors.push(SensorReading { data: 1.5, status: 0 });
+//!
+//!     let Sensor { data, status } = sensors.get(0);
+//!     assert_eq!((*data, *status), (1.5, 0));
+//!     assert_eq!(sensors.data(), &[1.5]);
+//!     assert_eq!(sensors.statuses(), &[0]);
+//! }
+//! ```
+//! Every generated type is exported next to the original type, so renaming them avoids clashes
+//! with existing types:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! /// A type that would clash with the default name of the view
+//! struct PageSlice;
+//!
+//! #[derive(StructOfVecs)]
+//! #[sov(slice = "PageRows", slice_mut = "PageRowsMut", array = "Pages")]
+//! struct Page {
+//!     number: u32,
+//! }
+//!
+//! fn main() {
+//!     let mut pages = VecPage::new();
+//!     pages.push(Page { number: 1 });
+//!
+//!     let rows: PageRows = pages.as_slice();
+//!     assert_eq!(rows.len(), 1);
+//!
+//!     let mut fixed = Pages::<4>::new();
+//!     fixed.push(Page { number: 2 }).ok();
+//!     assert_eq!(fixed.numbers(), &[2]);
+//!     let _ = PageSlice;
+//! }
+//! ```
+//! Unknown options are a compile error:
+//! ```rust,compile_fail
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! #[sov(name = "Oops")]
+//! struct Foo {
+//!     x: u64,
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! ### Standard traits
+//!
+//! `#[sov(derive(...))]` implements any of `Clone`, `Debug`, `Default`, `PartialEq`, `Eq`,
+//! `PartialOrd`, `Ord` and `Hash` for the `Vec`-like type. The `Vec`-like type prints as a list of
+//! rows, and is ordered row by row, like a `Vec` of the original type.
+//!
+//! The reference types get the same traits, except `Clone` and `Default`, and behave like the
+//! row they point to: they print as if they were the original type, can be compared with the
+//! original type, and hash the same way as a derived `Hash` for it. `Clone` gives them
+//! `to_owned()` instead, and `From` impls to convert them into the original type:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs, Debug, PartialEq, PartialOrd)]
+//! #[sov(derive(Clone, Debug, Default, PartialEq, PartialOrd))]
+//! struct Point {
+//!     x: i32,
+//!     y: i32,
+//! }
+//!
+//! fn main() {
+//!     let mut points = VecPoint::default();
+//!     points.push(Point { x: 1, y: 2 });
+//!     points.push(Point { x: 1, y: 0 });
+//!
+//!     let copy = points.clone();
+//!     assert_eq!(points, copy);
+//!     assert_eq!(points.get(0), copy.get(0));
+//!     assert_eq!(format!("{points:?}"), "[Point { x: 1, y: 2 }, Point { x: 1, y: 0 }]");
+//!
+//!     assert!(points.get(0) == Point { x: 1, y: 2 });
+//!     assert!(points.get(1) < points.get(0));
+//!     assert_eq!(points.get(1).to_owned(), Point { x: 1, y: 0 });
+//!     assert_eq!(Point::from(points.get(0)), Point { x: 1, y: 2 });
+//! }
+//! ```
+//! Flattened fields need their own type to derive the same traits.
+//!
+//! ### Reference types
+//!
+//! Regardless of which traits are derived, `FooRef` is `Copy`, like a `&Foo`. `FooRefMut` has
+//! `as_ref()` and `reborrow()` to borrow it for a shorter lifetime, and `set(foo)` and
+//! `replace(foo)` to overwrite the whole row at once:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! struct Point {
+//!     x: i32,
+//!     y: i32,
+//! }
+//!
+//! fn main() {
+//!     let mut points = VecPoint::new();
+//!     points.push(Point { x: 1, y: 2 });
+//!
+//!     let mut point = points.get_mut(0);
+//!     assert_eq!(*point.as_ref().x, 1);
+//!
+//!     let old = point.replace(Point { x: 3, y: 4 });
+//!     assert_eq!((old.x, old.y), (1, 2));
+//!     assert_eq!(*points.get(0).y, 4);
+//! }
+//! ```
+//! The reference types of enums can't overwrite the row, since the new value could be a different
+//! variant.
+//!
+//! ### Column visibility
+//!
+//! Every column must have the same length, so the columns are private to the `Vec`-like type, and
+//! can only be accessed as slices:
+//! ```rust,compile_fail
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! pub struct Foo {
+//!     pub x: u64,
+//! }
+//!
+//! fn main() {
+//!     let mut foos = VecFoo::new();
+//!     foos.x.push(1);
+//! }
+//! ```
+//! `#[sov(expose_columns)]` makes each column as visible as its field, for code that needs the
+//! `Vec` itself. It's then up to that code to keep the columns the same length. In debug builds,
+//! methods that change the length check that the columns are consistent first, and panic if they
+//! aren't.
+//!
+//! ### Single allocation
+//!
+//! By default, each column is its own `Vec`, so growing the `Vec`-like type reallocates every
+//! column separately. With `#[sov(layout = "single")]`, every column is stored in one allocation
+//! instead, one after the other. Growing it moves every column into a new allocation at once,
+//! which makes a difference for structs with many fields, or for lots of small vecs. The API is
+//! the same either way:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! #[sov(layout = "single")]
+//! struct Vertex {
+//!     position: [f32; 3],
+//!     normal: [f32; 3],
+//!     color: u32,
+//! }
+//!
+//! fn main() {
+//!     let mut vertices = VecVertex::with_capacity(2);
+//!     vertices.push(Vertex { position: [0.0; 3], normal: [0.0, 1.0, 0.0], color: 0xff0000 });
+//!     vertices.push(Vertex { position: [1.0; 3], normal: [0.0, 1.0, 0.0], color: 0x00ff00 });
+//!
+//!     // this one doesn't fit, so every column is moved into a bigger allocation together
+//!     vertices.push(Vertex { position: [2.0; 3], normal: [0.0, 1.0, 0.0], color: 0x0000ff });
+//!     assert!(vertices.capacity() >= 3);
+//!
+//!     assert_eq!(vertices.colors(), &[0xff0000, 0x00ff00, 0x0000ff]);
+//!     assert_eq!(vertices.get(1).position, &[1.0; 3]);
+//! }
+//! ```
+//! The columns aren't `Vec`s with this layout, so it can't be combined with `expose_columns` or
+//! `flatten`, and it isn't available for enums:
+//! ```rust,compile_fail
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! #[sov(layout = "single", expose_columns)]
+//! struct Foo {
+//!     x: u64,
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! ### Fixed-capacity arrays
+//!
+//! `#[sov(array)]` generates `Array{name}<N>` next to the `Vec`-like type. It stores every column
+//! inline, as `N` rows that may or may not be initialized, so it never allocates. That makes it
+//! useful for embedded code, or for scratch space in hot loops. `push` and `insert` give the row
+//! back in an `Err` once the array is full, and otherwise it has the same accessors, `get`,
+//! slices and iterators as the `Vec`-like type. The traits in `#[sov(derive(...))]` are
+//! implemented for it too:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs, Clone, Debug, PartialEq)]
+//! #[sov(array, derive(Clone, Debug, PartialEq))]
+//! struct Hit {
+//!     distance: f32,
+//!     id: u32,
+//! }
+//!
+//! fn main() {
+//!     // `new` is a `const fn`, so an empty array can be a constant
+//!     const NO_HITS: ArrayHit<2> = ArrayHit::new();
+//!
+//!     let mut hits = NO_HITS;
+//!     hits.push(Hit { distance: 2.5, id: 7 }).unwrap();
+//!     hits.push(Hit { distance: 0.5, id: 3 }).unwrap();
+//!     assert!(hits.is_full());
+//!     assert_eq!(hits.push(Hit { distance: 1.0, id: 9 }), Err(Hit { distance: 1.0, id: 9 }));
+//!
+//!     assert_eq!(hits.ids(), &[7, 3]);
+//!     assert_eq!(*hits.get(1).distance, 0.5);
+//!
+//!     let closest = hits.iter().min_by(|a, b| a.distance.total_cmp(b.distance)).unwrap();
+//!     assert_eq!(*closest.id, 3);
+//!
+//!     let copy = hits.clone();
+//!     assert_eq!(copy, hits);
+//!     assert_eq!(format!("{copy:?}"), "[Hit { distance: 2.5, id: 7 }, Hit { distance: 0.5, id: 3 }]");
+//!
+//!     let hits: Vec<Hit> = hits.into_iter().collect();
+//!     assert_eq!(hits[0], Hit { distance: 2.5, id: 7 });
+//! }
+//! ```
+//! The array has nowhere to store the columns of a nested struct, so it can't be combined with
+//! `flatten`, and it isn't available for enums:
+//! ```rust,compile_fail
+//! # use sov::StructOfVecs;
+//! # #[derive(StructOfVecs)]
+//! # struct Inner {
+//! #     x: u64,
+//! # }
+//! #[derive(StructOfVecs)]
+//! #[sov(array)]
+//! struct Outer {
+//!     #[sov(flatten)]
+//!     inner: Inner,
+//! }
+//! # fn main() {}
+//! ```
+//! Naming the array's iterator without asking for the array is an error too:
+//! ```rust,compile_fail
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! #[sov(array_into_iter = "HitRows")]
+//! struct Hit {
+//!     distance: f32,
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! ### Custom allocators
+//!
+//! With the `allocator-api2` feature enabled, `#[sov(allocator)]` adds an allocator parameter to
+//! the `Vec`-like type, which defaults to the global allocator. Each column is then an
+//! `allocator_api2::vec::Vec`, and `new_in(alloc)` and `with_capacity_in(capacity, alloc)` create
+//! every column in (a clone of) the same allocator, so the allocator has to be `Clone`. Any
+//! allocator that implements `allocator_api2::alloc::Allocator` works, such as `&bumpalo::Bump`
+//! with bumpalo's `allocator-api2` feature, and on nightly, `allocator-api2` can forward to the
+//! standard library's allocators:
+//! ```rust
+//! # #[cfg(feature = "allocator-api2")]
+//! # mod example {
+//! use std::{alloc::Layout, cell::Cell, ptr::NonNull};
+//!
+//! use allocator_api2::alloc::{AllocError, Allocator, Global};
+//! use sov::StructOfVecs;
+//!
+//! /// An allocator that counts how many allocations it has made
+//! #[derive(Clone, Copy)]
+//! struct Counting<'a>(&'a Cell<usize>);
+//!
+//! unsafe impl Allocator for Counting<'_> {
+//!     fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+//!         self.0.set(self.0.get() + 1);
+//!         Global.allocate(layout)
+//!     }
+//!
+//!     unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+//!         Global.deallocate(ptr, layout)
+//!     }
+//! }
+//!
+//! #[derive(StructOfVecs)]
+//! #[sov(allocator)]
+//! struct Contact {
+//!     id: u32,
+//!     normal: [f32; 2],
+//! }
+//!
+//! pub fn main() {
+//!     let count = Cell::new(0);
+//!     let mut contacts = VecContact::with_capacity_in(16, Counting(&count));
+//!     contacts.push(Contact { id: 1, normal: [0.0, 1.0] });
+//!
+//!     // one allocation for each column
+//!     assert_eq!(count.get(), 2);
+//!
+//!     // without an allocator, it's a `VecContact<Global>`
+//!     let contacts = VecContact::new();
+//!     assert!(contacts.is_empty());
+//! }
+//! # }
+//! # #[cfg(feature = "allocator-api2")]
+//! # fn main() {
+//! #     example::main()
+//! # }
+//! # #[cfg(not(feature = "allocator-api2"))]
+//! # fn main() {}
+//! ```
+//! Only `new`, `with_capacity`, `Default`, `FromIterator` and the other ways of creating a vec
+//! from scratch, and the `SovVec` trait, are limited to the global allocator. The allocator can't
+//! be combined with `layout = "single"` or `flatten`, and it isn't available for enums:
+//! ```rust,compile_fail
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! #[sov(allocator)]
+//! enum Shape {
+//!     Circle(f32),
+//!     Square(f32),
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! ### Nested structs
+//!
+//! By default, a field whose type is itself a struct is stored in a single `Vec` column. If that
+//! struct also derives `StructOfVecs`, `#[sov(flatten)]` stores the field in its generated type
+//! instead, so each of its fields gets its own column too:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! struct Position {
+//!     x: f32,
+//!     y: f32,
+//! }
+//!
+//! #[derive(StructOfVecs)]
+//! struct Body {
+//!     #[sov(flatten)]
+//!     position: Position,
+//!     mass: f32,
+//! }
+//!
+//! fn main() {
+//!     let mut bodies = VecBody::new();
+//!     bodies.push(Body {
+//!         position: Position { x: 1.0, y: 2.0 },
+//!         mass: 3.0,
+//!     });
+//!
+//!     let BodyRef { position, mass } = bodies.get(0);
+//!     let PositionRef { x, y } = position;
+//!     assert_eq!((*x, *y, *mass), (1.0, 2.0, 3.0));
+//!
+//!     assert_eq!(bodies.positions().xs(), &[1.0]);
+//! }
+//! ```
+//! The generated types for the field are found through the [`StructOfVecs`] trait, so they can be
+//! renamed freely. The nested struct can be generic too, and the generated methods and `derive`s
+//! are bounded by whatever its fields need:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs, Clone, Debug, PartialEq)]
+//! #[sov(derive(Clone, Debug, PartialEq))]
+//! struct Labelled<T> {
+//!     label: T,
+//!     id: u32,
+//! }
+//!
+//! #[derive(StructOfVecs, Clone, Debug, PartialEq)]
+//! #[sov(derive(Clone, Debug, PartialEq))]
+//! struct Marker<T: Clone> {
+//!     #[sov(flatten)]
+//!     labelled: Labelled<T>,
+//!     size: u8,
+//! }
+//!
+//! fn main() {
+//!     let mut markers = VecMarker::new();
+//!     markers.push(Marker { labelled: Labelled { label: "start", id: 1 }, size: 2 });
+//!     markers.resize(3, Marker { labelled: Labelled { label: "end", id: 2 }, size: 1 });
+//!
+//!     // `MarkerRef` is `Copy`, since `Labelled<T>`'s reference type is
+//!     let first = markers.get(0);
+//!     let copy = first;
+//!     assert_eq!(*copy.labelled.label, "start");
+//!     assert_eq!(first.to_owned(), Marker { labelled: Labelled { label: "start", id: 1 }, size: 2 });
+//!     assert_eq!(markers.labelleds().labels(), &["start", "end", "end"]);
+//!     assert_eq!(markers.clone(), markers);
+//! }
+//! ```
+//!
+//! ### Custom columns
+//!
+//! `#[sov(column = "...")]` stores a field in any type that implements the [`Column`] trait,
+//! rather than a `Vec`. The generated methods that add, remove and get rows call through the
+//! trait, and the slices, iterators and views borrow the column with [`Column::as_slice`], so
+//! they work the same way as for a `Vec`. That means a custom column has to store its rows
+//! contiguously; bit vectors and dictionary encoding can't, so they're separate options (see
+//! [Bit-packed bools](#bit-packed-bools) and [Dictionary columns](#dictionary-columns)). With the
+//! `smallvec` feature enabled, `SmallVec` implements [`Column`], which keeps short columns inline.
+//! Any other type with contiguous storage can implement it too:
+//! ```rust
+//! use sov::{Column, StructOfVecs};
+//!
+//! /// A column that remembers how many rows have ever been added to it
+//! #[derive(Clone, Debug)]
+//! pub struct Counted<T> {
+//!     rows: Vec<T>,
+//!     added: usize,
+//! }
+//!
+//! impl<T> IntoIterator for Counted<T> {
+//!     type Item = T;
+//!     type IntoIter = std::vec::IntoIter<T>;
+//!
+//!     fn into_iter(self) -> Self::IntoIter {
+//!         self.rows.into_iter()
+//!     }
+//! }
+//!
+//! impl<T> Column<T> for Counted<T> {
+//!     fn new() -> Self {
+//!         Self { rows: Vec::new(), added: 0 }
+//!     }
+//!
+//!     fn with_capacity(capacity: usize) -> Self {
+//!         Self { rows: Vec::with_capacity(capacity), added: 0 }
+//!     }
+//!
+//!     fn len(&self) -> usize {
+//!         self.rows.len()
+//!     }
+//!
+//!     fn capacity(&self) -> usize {
+//!         self.rows.capacity()
+//!     }
+//!
+//!     fn reserve(&mut self, additional: usize) {
+//!         self.rows.reserve(additional)
+//!     }
+//!
+//!     fn shrink_to_fit(&mut self) {
+//!         self.rows.shrink_to_fit()
+//!     }
+//!
+//!     fn push(&mut self, value: T) {
+//!         self.added += 1;
+//!         self.rows.push(value);
+//!     }
+//!
+//!     fn insert(&mut self, index: usize, value: T) {
+//!         self.added += 1;
+//!         self.rows.insert(index, value);
+//!     }
+//!
+//!     fn pop(&mut self) -> Option<T> {
+//!         self.rows.pop()
+//!     }
+//!
+//!     fn remove(&mut self, index: usize) -> T {
+//!         self.rows.remove(index)
+//!     }
+//!
+//!     fn swap_remove(&mut self, index: usize) -> T {
+//!         self.rows.swap_remove(index)
+//!     }
+//!
+//!     fn truncate(&mut self, len: usize) {
+//!         self.rows.truncate(len)
+//!     }
+//!
+//!     fn as_slice(&self) -> &[T] {
+//!         &self.rows
+//!     }
+//!
+//!     fn as_mut_slice(&mut self) -> &mut [T] {
+//!         &mut self.rows
+//!     }
+//! }
+//!
+//! #[derive(StructOfVecs)]
+//! #[sov(expose_columns)]
+//! pub struct Order {
+//!     #[sov(column = "Counted<u64>")]
+//!     pub id: u64,
+//!     pub price: f64,
+//! }
+//!
+//! fn main() {
+//!     let mut orders = VecOrder::new();
+//!     orders.push(Order { id: 1, price: 9.5 });
+//!     orders.push(Order { id: 2, price: 3.0 });
+//!     orders.swap_remove(0);
+//!
+//!     assert_eq!(orders.ids(), &[2]);
+//!     assert_eq!(*orders.get(0).id, 2);
+//!     assert_eq!(orders.id.added, 2);
+//! }
+//! ```
+//! With `#[sov(derive(...))]`, custom columns are compared and hashed by their rows, and need to
+//! be `Clone` for the vec to be. A custom column manages its own memory, so it can't be combined
+//! with `layout = "single"`, `allocator` or `flatten`, and it isn't available for enums.
+//! `Array{name}<N>` stores every column inline regardless.
+//! ```rust,compile_fail
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! enum Shape {
+//!     Circle(#[sov(column = "Vec<f32>")] f32),
+//!     Square(f32),
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! ### Bit-packed bools
+//!
+//! A `Vec<bool>` uses a whole byte for each row. `#[sov(bitpacked)]` on a `bool` field stores it in
+//! a [`BitVec`] instead, which packs the rows into words one bit each. The accessors borrow the
+//! column as a [`BitSlice`] or [`BitSliceMut`], which can count the rows that are set and iterate
+//! over their indices a word at a time, for filtering the other columns:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! struct Entity {
+//!     id: u32,
+//!     #[sov(bitpacked)]
+//!     alive: bool,
+//! }
+//!
+//! fn main() {
+//!     let mut entities = VecEntity::new();
+//!
+//!     for id in 0..10 {
+//!         entities.push(Entity { id, alive: id % 3 == 0 });
+//!     }
+//!
+//!     assert_eq!(entities.alives().count_ones(), 4);
+//!
+//!     let alive: Vec<u32> = entities
+//!         .alives()
+//!         .iter_ones()
+//!         .map(|index| entities.ids()[index])
+//!         .collect();
+//!     assert_eq!(alive, [0, 3, 6, 9]);
+//!
+//!     // `EntityRef` holds the bool itself, and `EntityRefMut` a proxy that can set it
+//!     let alive: bool = entities.get(3).alive;
+//!     assert!(alive);
+//!
+//!     entities.get_mut(3).alive.set(false);
+//!     assert_eq!(entities.alives().count_ones(), 3);
+//! }
+//! ```
+//! A bit can't be borrowed as a `&mut bool`, so `FooRefMut` holds a [`BitMut`] for the field,
+//! which sets the bit atomically, since the rows next to it may be being set through another
+//! mutable view at the same time. Bit-packing is opt-in, because the accessors no longer return a
+//! `&[bool]`. It works like `#[sov(flatten)]` with `bool`'s implementation of [`StructOfVecs`],
+//! so like a flattened field it can't be combined with `layout = "single"`, `array`, `allocator`
+//! or `column`, it isn't available for enums, and there is no `sort_by_` method for it. Any other
+//! type is a compile error:
+//! ```rust,compile_fail
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! struct Flags {
+//!     #[sov(bitpacked)]
+//!     bits: u32,
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! ### Nullable options
+//!
+//! A `Vec<Option<T>>` usually needs padding for the discriminant of every row. `#[sov(nullable)]`
+//! on an `Option<T>` field stores it in a [`NullableVec`] instead, which keeps a dense column of
+//! `T` alongside a [`BitVec`] of which rows are `Some`. The accessors borrow the column as a
+//! [`NullableSlice`] or [`NullableSliceMut`], which give the values and the validity bitmap
+//! separately, for computing over the values directly:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! struct Reading {
+//!     id: u32,
+//!     #[sov(nullable)]
+//!     value: Option<f32>,
+//! }
+//!
+//! fn main() {
+//!     let mut readings = VecReading::new();
+//!     readings.push(Reading { id: 0, value: Some(1.5) });
+//!     readings.push(Reading { id: 1, value: None });
+//!     readings.push(Reading { id: 2, value: Some(2.5) });
+//!
+//!     assert_eq!(readings.values().null_count(), 1);
+//!     // a null row holds `T::default()` in the values column
+//!     assert_eq!(readings.values().values(), &[1.5, 0.0, 2.5]);
+//!
+//!     let total: f32 = readings
+//!         .values()
+//!         .validity()
+//!         .iter_ones()
+//!         .map(|index| readings.values().values()[index])
+//!         .sum();
+//!     assert_eq!(total, 4.0);
+//!
+//!     // `ReadingRef` holds an `Option<&f32>`, and `ReadingRefMut` a proxy that can set it
+//!     assert_eq!(readings.get(1).value, None);
+//!     assert_eq!(readings.get(2).value, Some(&2.5));
+//!
+//!     readings.get_mut(1).value.set(Some(0.5));
+//!     assert_eq!(readings.values().null_count(), 0);
+//! }
+//! ```
+//! An `Option<&mut T>` couldn't set the row to `None`, so `FooRefMut` holds a [`NullableMut`] for
+//! the field, with `set`, `replace` and `take` alongside `as_ref` and `as_mut`. The values column
+//! needs something in the rows that are `None`, so the field's type must implement `Default`; a
+//! generic struct needs a `T: Default` bound for a nullable `Option<T>` field. The filler values
+//! are ignored when comparing or hashing. Like `#[sov(bitpacked)]`, it works like
+//! `#[sov(flatten)]` with `Option<T>`'s implementation of [`StructOfVecs`], so it has the same
+//! restrictions.
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! struct Entry<T: Default> {
+//!     #[sov(nullable)]
+//!     value: Option<T>,
+//!     row: u32,
+//! }
+//!
+//! fn main() {
+//!     // `T` can borrow, since it only needs to implement `Default`
+//!     let text = String::from("borrowed");
+//!     let mut entries = VecEntry::new();
+//!     entries.push(Entry { value: Some(text.as_str()), row: 0 });
+//!     entries.push(Entry { value: None, row: 1 });
+//!
+//!     let first = entries.get(0);
+//!     let copy = first;
+//!     assert_eq!(first.value, Some(&"borrowed"));
+//!     assert_eq!(copy.row, &0);
+//!     assert_eq!(entries.get_mut(1).value.replace(Some("set")), None);
+//!     assert_eq!(entries.values().values(), &["borrowed", "set"]);
+//! }
+//! ```
+//! A field that isn't an `Option` has no `None` to store in the bitmap:
+//! ```rust,compile_fail
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! struct Reading {
+//!     #[sov(nullable)]
+//!     value: f32,
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! ### Dictionary columns
+//!
+//! A field like a region or a status often has only a handful of distinct values across all of
+//! its rows. `#[sov(dictionary)]` stores it in a [`DictionaryVec`], which keeps each distinct
+//! value once and each row as the `u32` code of its value. Adding a row interns its value, and
+//! the accessors borrow the column as a [`DictionarySlice`] or [`DictionarySliceMut`], which give
+//! the codes directly, for filtering the rows with integer comparisons:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! struct Order {
+//!     id: u32,
+//!     #[sov(dictionary)]
+//!     region: String,
+//! }
+//!
+//! fn main() {
+//!     let mut orders = VecOrder::new();
+//!
+//!     for (id, region) in ["us", "eu", "us", "ap", "eu"].into_iter().enumerate() {
+//!         orders.push(Order { id: id as u32, region: region.to_string() });
+//!     }
+//!
+//!     // every distinct value, indexed by its code
+//!     assert_eq!(orders.regions().dictionary(), ["us", "eu", "ap"]);
+//!
+//!     let eu = orders.regions().code(&"eu".to_string()).unwrap();
+//!     let in_eu: Vec<u32> = orders
+//!         .regions()
+//!         .codes()
+//!         .iter()
+//!         .zip(orders.ids())
+//!         .filter(|(code, _)| **code == eu)
+//!         .map(|(_, id)| *id)
+//!         .collect();
+//!     assert_eq!(in_eu, [1, 4]);
+//!
+//!     // `OrderRef` holds a `&String`, and `OrderRefMut` a proxy that can change the code
+//!     assert_eq!(orders.get(3).region, "ap");
+//!
+//!     orders.get_mut(3).region.set("us".to_string());
+//!     assert_eq!(orders.get(3).region, "us");
+//! }
+//! ```
+//! The dictionary is shared by every row, so it can't grow through `FooRefMut`, or through a view
+//! that might be one of several being used at the same time. The [`DictionaryMut`] proxy can
+//! only point its row at a value that is already in the dictionary, and panics otherwise; add new
+//! values with `push`, or with [`DictionaryVec::intern`] on a vec of the field's type. Values
+//! stay in the dictionary after their rows are removed, so codes don't change as rows come and
+//! go, but the dictionary only grows. `shrink_to_fit` removes the values that no row uses any
+//! more, which gives the rest new codes:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! # #[derive(StructOfVecs)]
+//! # struct Order {
+//! #     id: u32,
+//! #     #[sov(dictionary)]
+//! #     region: String,
+//! # }
+//! # fn main() {
+//! let mut orders = VecOrder::new();
+//! for (id, region) in ["us", "eu", "ap"].into_iter().enumerate() {
+//!     orders.push(Order { id: id as u32, region: region.to_string() });
+//! }
+//!
+//! orders.remove(0);
+//! assert_eq!(orders.regions().dictionary(), ["us", "eu", "ap"]);
+//!
+//! orders.shrink_to_fit();
+//! assert_eq!(orders.regions().dictionary(), ["eu", "ap"]);
+//! assert_eq!(orders.regions().codes(), &[0, 1]);
+//! # }
+//! ```
+//!
+//! `FooRefMut::set` and `FooRefMut::replace` panic in the same way, including for the dictionary
+//! fields of flattened structs. Every dictionary field is checked before any field is written, so
+//! the row is left as it was:
+//! ```rust
+//! # use sov::StructOfVecs;
+//! # use std::panic::{self, AssertUnwindSafe};
+//! #[derive(StructOfVecs, Debug, PartialEq)]
+//! struct Order {
+//!     id: u32,
+//!     #[sov(dictionary)]
+//!     region: String,
+//! }
+//!
+//! fn main() {
+//!     let mut orders = VecOrder::new();
+//!     orders.push(Order { id: 0, region: "eu".to_string() });
+//!
+//!     // "us" hasn't been pushed, so it isn't in the dictionary
+//!     let result = panic::catch_unwind(AssertUnwindSafe(|| {
+//!         orders.get_mut(0).set(Order { id: 1, region: "us".to_string() });
+//!     }));
+//!     assert!(result.is_err());
+//!     assert_eq!(Vec::from(orders), [Order { id: 0, region: "eu".to_string() }]);
+//! }
+//! ```
+//!
+//! Values are looked up by their order, so the field's type must implement `Ord`, and rows are
+//! cloned out of the dictionary when they're removed or iterated by value, so it must implement
+//! `Clone`; a generic struct needs a `T: Ord + Clone` bound for a dictionary field of type `T`.
+//! There is no `StructOfVecs` implementation behind a dictionary field, since its rows are still
+//! the field's own type, but it has the same restrictions as `#[sov(flatten)]`, and it can't be
+//! combined with the other ways of storing a field:
+//! ```rust,compile_fail
+//! # use sov::StructOfVecs;
+//! #[derive(StructOfVecs)]
+//! struct Order {
+//!     #[sov(dictionary, nullable)]
+//!     region: Option<String>,
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! ## Traits
+//!
+//! Every derive also implements [`StructOfVecs`] for the original type and [`SovVec`] for the
+//! generated `Vec`-like type, so code can be written generically over any struct of vecs:
+//! ```rust
+//! use sov::{SovVec, StructOfVecs};
+//!
+//! #[derive(StructOfVecs)]
+//! struct Point {
+//!     x: i32,
+//!     y: i32,
+//! }
+//!
+//! fn collect<T: StructOfVecs>(items: impl IntoIterator<Item = T>) -> T::Vec {
+//!     let mut vec = T::Vec::new();
+//!     for item in items {
+//!         vec.push(item);
+//!     }
+//!     vec
+//! }
+//!
+//! fn main() {
+//!     let points = collect([Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]);
+//!     assert_eq!(points.len(), 2);
+//!     assert_eq!(points.xs(), &[1, 3]);
+//! }
+//! ```
+//!
 //! [godbolt]: https://godbolt.org/z/YqMz8G6K9
-use codegen::codegen;
-use proc_macro2::TokenStream;
-use syn::{parse_macro_input, DeriveInput};
+#![no_std]
 
-macro_rules! bail {
-    ($span:expr => $($t:tt)*) => {
-        return Err(quote::quote_spanned! { $span.span() => compile_error!($($t)*)})
-    };
-    ($($t:tt)*) => {
-        return Err(quote::quote! { compile_error!($($t)*)})
-    };
-}
+extern crate alloc;
 
-mod codegen;
-mod parse;
-mod util;
+#[cfg(target_has_atomic = "ptr")]
+pub use bits::{BitIntoIter, BitIter, BitIterMut, BitMut, BitSlice, BitSliceMut, BitVec, Ones};
+pub use dictionary::{
+    DictionaryIntoIter, DictionaryIter, DictionaryIterMut, DictionaryMut, DictionarySlice,
+    DictionarySliceMut, DictionaryVec,
+};
+#[cfg(target_has_atomic = "ptr")]
+pub use nullable::{
+    NullableIntoIter, NullableIter, NullableIterMut, NullableMut, NullableSlice, NullableSliceMut,
+    NullableVec,
+};
+pub use slice::{Chunks, ChunksExact, SovSlice, Windows};
+pub use sov_derive::StructOfVecs;
+pub use traits::{Column, SovVec, StructOfVecs};
 
-struct Foo {
-    x: u64,
-    y: String,
-}
+mod array;
+#[cfg(target_has_atomic = "ptr")]
+mod bits;
+mod column;
+mod dictionary;
+#[cfg(target_has_atomic = "ptr")]
+mod nullable;
+mod permutation;
+mod slice;
+mod traits;
 
-pub(crate) type Result<T> = core::result::Result<T, TokenStream>;
+/// Dependencies of the generated code, which aren't part of the public API
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "rayon")]
+    pub use rayon;
 
-/// Generate a struct of `Vec`s. See the crate-level docs for more detail
-#[proc_macro_derive(StructOfVecs, attributes(sov))]
-pub fn struct_of_vecs(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let input = parse_macro_input!(tokens as DeriveInput);
-    match parse::Input::from_derive_input(input) {
-        Ok(input) => codegen(input).into(),
-        Err(tokens) => tokens.into(),
-    }
+    #[cfg(feature = "allocator-api2")]
+    pub use allocator_api2;
+
+    pub use alloc::vec::{IntoIter as VecIntoIter, Vec};
+
+    pub use crate::{
+        array::{ArrayColumn, ArrayColumnIntoIter},
+        column::{Allocation, RawColumn, SingleColumn, SingleColumnIntoIter},
+        permutation::Permutation,
+        slice::range,
+    };
 }
@@ -0,0 +1,1237 @@
+//! Validity-bitmap storage for `Option<T>` fields, which `#[sov(nullable)]` stores as a dense
+//! column of values alongside a [`BitVec`] of which rows are `Some`.
+//!
+//! `Option<T>` implements [`StructOfVecs`] with [`NullableVec`] as its vec, so a nullable field is
+//! handled by the generated code in the same way as a flattened field. The rows that are `None`
+//! hold `T::default()` in the values column, so that it can be borrowed as a plain `&[T]`.
+use alloc::vec::{self, Vec};
+use core::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    iter::FusedIterator,
+    mem,
+    ops::RangeBounds,
+    slice,
+};
+
+use crate::{
+    bits::{BitIntoIter, BitIter, BitIterMut, BitMut, BitSlice, BitSliceMut, BitVec},
+    permutation::Permutation,
+    SovSlice, SovVec, StructOfVecs,
+};
+
+/// A `Vec<Option<T>>` which stores the values densely, with a bitmap of which rows are `Some`,
+/// used for `#[sov(nullable)]` fields.
+///
+/// The rows are borrowed as a [`NullableSlice`] or [`NullableSliceMut`], which give access to the
+/// values and the bitmap as separate columns. Mutable access to a single row is through a
+/// [`NullableMut`] proxy rather than a `&mut Option<T>`
+#[derive(Clone)]
+pub struct NullableVec<T> {
+    /// The value of every row, which is `T::default()` for the rows that are `None`
+    values: Vec<T>,
+    /// Whether each row is `Some`
+    validity: BitVec,
+}
+
+impl<T> NullableVec<T> {
+    /// Create an empty vec
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            values: Vec::new(),
+            validity: BitVec::new(),
+        }
+    }
+
+    /// Create an empty vec with space for at least `capacity` rows
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            values: Vec::with_capacity(capacity),
+            validity: BitVec::with_capacity(capacity),
+        }
+    }
+
+    /// The number of rows
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether there are no rows
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The number of rows the vec can hold without reallocating
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.values.capacity().min(self.validity.capacity())
+    }
+
+    /// Reserve space for at least `additional` more rows
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.values.reserve(additional);
+        self.validity.reserve(additional);
+    }
+
+    /// Reserve space for exactly `additional` more rows
+    #[inline]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.values.reserve_exact(additional);
+        self.validity.reserve_exact(additional);
+    }
+
+    /// Shrink the capacity as much as possible
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.values.shrink_to_fit();
+        self.validity.shrink_to_fit();
+    }
+
+    /// Shrink the capacity to at least `min_capacity` and the number of rows
+    #[inline]
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.values.shrink_to(min_capacity);
+        self.validity.shrink_to(min_capacity);
+    }
+
+    /// The row at `index`, panicking if it is out of bounds
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.as_slice().get(index)
+    }
+
+    /// A proxy for the row at `index`, which can change it, panicking if it is out of bounds
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> NullableMut<'_, T> {
+        NullableMut {
+            value: &mut self.values[index],
+            valid: self.validity.get_mut(index),
+        }
+    }
+
+    /// Remove the last row, if there is one
+    #[inline]
+    pub fn pop(&mut self) -> Option<Option<T>> {
+        let value = self.values.pop()?;
+        let valid = self.validity.pop()?;
+
+        Some(valid.then_some(value))
+    }
+
+    /// Remove the row at `index`, shifting all rows after it
+    #[inline]
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let value = self.values.remove(index);
+        self.validity.remove(index).then_some(value)
+    }
+
+    /// Remove the row at `index`, replacing it with the last row
+    #[inline]
+    pub fn swap_remove(&mut self, index: usize) -> Option<T> {
+        let value = self.values.swap_remove(index);
+        self.validity.swap_remove(index).then_some(value)
+    }
+
+    /// Remove every row after the first `len`, doing nothing if there are fewer rows
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.values.truncate(len);
+        self.validity.truncate(len);
+    }
+
+    /// Remove every row, keeping the capacity
+    #[inline]
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.validity.clear();
+    }
+
+    /// The value of every row, which is `T::default()` for the rows that are `None`
+    #[inline]
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Mutably borrow the value of every row. Changing the value of a row that is `None` doesn't
+    /// make it `Some`
+    #[inline]
+    pub fn values_mut(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+
+    /// Whether each row is `Some`
+    #[inline]
+    pub fn validity(&self) -> BitSlice<'_> {
+        self.validity.as_slice()
+    }
+
+    /// Mutably borrow whether each row is `Some`. A row that is made `Some` this way has the value
+    /// it had before, which is `T::default()` if it was `None`
+    #[inline]
+    pub fn validity_mut(&mut self) -> BitSliceMut<'_> {
+        self.validity.as_mut_slice()
+    }
+
+    /// The number of rows that are `None`
+    #[inline]
+    pub fn null_count(&self) -> usize {
+        self.validity.count_zeros()
+    }
+
+    /// Borrow every row as a view
+    #[inline]
+    pub fn as_slice(&self) -> NullableSlice<'_, T> {
+        NullableSlice {
+            values: &self.values,
+            validity: self.validity.as_slice(),
+        }
+    }
+
+    /// Mutably borrow every row as a view
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> NullableSliceMut<'_, T> {
+        NullableSliceMut {
+            values: &mut self.values,
+            validity: self.validity.as_mut_slice(),
+        }
+    }
+
+    /// Borrow every row, which for a nullable column is the same as [`NullableVec::as_slice`]
+    #[inline]
+    pub fn as_slices(&self) -> NullableSlice<'_, T> {
+        self.as_slice()
+    }
+
+    /// Mutably borrow every row, which for a nullable column is the same as
+    /// [`NullableVec::as_mut_slice`]
+    #[inline]
+    pub fn as_mut_slices(&mut self) -> NullableSliceMut<'_, T> {
+        self.as_mut_slice()
+    }
+
+    /// An iterator over each row
+    #[inline]
+    pub fn iter(&self) -> NullableIter<'_, T> {
+        self.as_slice().iter()
+    }
+
+    /// An iterator over a proxy for each row
+    #[inline]
+    pub fn iter_mut(&mut self) -> NullableIterMut<'_, T> {
+        self.as_mut_slice().into_iter()
+    }
+
+    /// Reorder the rows, in the same way as the other columns of a vec being sorted
+    #[doc(hidden)]
+    #[inline]
+    pub fn apply_permutation(&mut self, permutation: &Permutation) {
+        permutation.apply(&mut self.values);
+        self.validity.apply_permutation(permutation);
+    }
+}
+
+impl<T: Default> NullableVec<T> {
+    /// Add a row to the end
+    #[inline]
+    pub fn push(&mut self, value: Option<T>) {
+        self.validity.push(value.is_some());
+        self.values.push(value.unwrap_or_default());
+    }
+
+    /// Insert a row at `index`, shifting all rows after it
+    #[inline]
+    pub fn insert(&mut self, index: usize, value: Option<T>) {
+        self.values.insert(index, T::default());
+        self.validity.insert(index, false);
+        self.get_mut(index).set(value);
+    }
+
+    /// Resize to `new_len` rows, either by removing rows from the end or by adding clones of
+    /// `value`
+    #[inline]
+    pub fn resize(&mut self, new_len: usize, value: Option<T>)
+    where
+        Option<T>: Clone,
+    {
+        self.resize_with(new_len, || value.clone());
+    }
+
+    /// Resize to `new_len` rows, either by removing rows from the end or by adding rows returned
+    /// by `f`
+    pub fn resize_with<F>(&mut self, new_len: usize, mut f: F)
+    where
+        F: FnMut() -> Option<T>,
+    {
+        match new_len.checked_sub(self.len()) {
+            Some(additional) => {
+                self.reserve(additional);
+                (0..additional).for_each(|_| self.push(f()));
+            }
+            None => self.truncate(new_len),
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T> NullableVec<T> {
+    /// A parallel iterator over each row
+    #[inline]
+    pub fn par_iter(&self) -> impl rayon::iter::IndexedParallelIterator<Item = Option<&T>> + '_
+    where
+        T: Sync,
+    {
+        use rayon::prelude::*;
+
+        self.values
+            .par_iter()
+            .zip(self.validity.par_iter())
+            .map(|(value, valid)| valid.then_some(value))
+    }
+
+    /// A parallel iterator over a proxy for each row
+    #[inline]
+    pub fn par_iter_mut(
+        &mut self,
+    ) -> impl rayon::iter::IndexedParallelIterator<Item = NullableMut<'_, T>> + '_
+    where
+        T: Send,
+    {
+        use rayon::prelude::*;
+
+        self.values
+            .par_iter_mut()
+            .zip(self.validity.par_iter_mut())
+            .map(|(value, valid)| NullableMut { value, valid })
+    }
+
+    /// A parallel iterator over views of `chunk_size` rows. The last chunk may be shorter.
+    ///
+    /// Panics if `chunk_size` is 0
+    #[inline]
+    pub fn par_chunks_mut(
+        &mut self,
+        chunk_size: usize,
+    ) -> impl rayon::iter::IndexedParallelIterator<Item = NullableSliceMut<'_, T>> + '_
+    where
+        T: Send,
+    {
+        use rayon::prelude::*;
+
+        assert!(chunk_size != 0, "chunk_size must not be zero");
+
+        self.values
+            .par_chunks_mut(chunk_size)
+            .zip(self.validity.par_chunks_mut(chunk_size))
+            .map(|(values, validity)| NullableSliceMut { values, validity })
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for NullableVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.as_slice(), f)
+    }
+}
+
+impl<T> Default for NullableVec<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// the values of the rows that are `None` aren't part of the vec, so it's compared and hashed by
+// its rows rather than its columns
+
+impl<T: PartialEq> PartialEq for NullableVec<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq> Eq for NullableVec<T> {}
+
+impl<T: PartialOrd> PartialOrd for NullableVec<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord> Ord for NullableVec<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash> Hash for NullableVec<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+
+        for row in self {
+            row.hash(state);
+        }
+    }
+}
+
+impl<T: Default> Extend<Option<T>> for NullableVec<T> {
+    fn extend<I: IntoIterator<Item = Option<T>>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T: Default> FromIterator<Option<T>> for NullableVec<T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = Option<T>>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T> IntoIterator for NullableVec<T> {
+    type Item = Option<T>;
+    type IntoIter = NullableIntoIter<T>;
+
+    #[inline]
+    fn into_iter(self) -> NullableIntoIter<T> {
+        NullableIntoIter {
+            values: self.values.into_iter(),
+            validity: self.validity.into_iter(),
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a NullableVec<T> {
+    type Item = Option<&'a T>;
+    type IntoIter = NullableIter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> NullableIter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut NullableVec<T> {
+    type Item = NullableMut<'a, T>;
+    type IntoIter = NullableIterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> NullableIterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T: Default> StructOfVecs for Option<T> {
+    type Vec = NullableVec<T>;
+    type Ref<'a>
+        = Option<&'a T>
+    where
+        T: 'a;
+    type RefMut<'a>
+        = NullableMut<'a, T>
+    where
+        T: 'a;
+    type Slices<'a>
+        = NullableSlice<'a, T>
+    where
+        T: 'a;
+    type SlicesMut<'a>
+        = NullableSliceMut<'a, T>
+    where
+        T: 'a;
+    type Slice<'a>
+        = NullableSlice<'a, T>
+    where
+        T: 'a;
+    type SliceMut<'a>
+        = NullableSliceMut<'a, T>
+    where
+        T: 'a;
+}
+
+impl<T: Default> SovVec for NullableVec<T> {
+    type Iter<'a>
+        = NullableIter<'a, T>
+    where
+        T: 'a;
+    type IterMut<'a>
+        = NullableIterMut<'a, T>
+    where
+        T: 'a;
+
+    #[inline]
+    fn new() -> Self {
+        Self::new()
+    }
+
+    #[inline]
+    fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity(capacity)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.capacity()
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        self.reserve(additional);
+    }
+
+    #[inline]
+    fn reserve_exact(&mut self, additional: usize) {
+        self.reserve_exact(additional);
+    }
+
+    #[inline]
+    fn shrink_to_fit(&mut self) {
+        self.shrink_to_fit();
+    }
+
+    #[inline]
+    fn shrink_to(&mut self, min_capacity: usize) {
+        self.shrink_to(min_capacity);
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        self.truncate(len);
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.clear();
+    }
+
+    #[inline]
+    fn resize_with<F>(&mut self, new_len: usize, f: F)
+    where
+        F: FnMut() -> Option<T>,
+    {
+        self.resize_with(new_len, f);
+    }
+
+    #[inline]
+    fn get(&self, index: usize) -> Option<&T> {
+        self.get(index)
+    }
+
+    #[inline]
+    fn get_mut(&mut self, index: usize) -> NullableMut<'_, T> {
+        self.get_mut(index)
+    }
+
+    #[inline]
+    fn as_slices(&self) -> NullableSlice<'_, T> {
+        self.as_slices()
+    }
+
+    #[inline]
+    fn as_mut_slices(&mut self) -> NullableSliceMut<'_, T> {
+        self.as_mut_slices()
+    }
+
+    #[inline]
+    fn as_slice(&self) -> NullableSlice<'_, T> {
+        self.as_slice()
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> NullableSliceMut<'_, T> {
+        self.as_mut_slice()
+    }
+
+    #[inline]
+    fn iter(&self) -> NullableIter<'_, T> {
+        self.iter()
+    }
+
+    #[inline]
+    fn iter_mut(&mut self) -> NullableIterMut<'_, T> {
+        self.iter_mut()
+    }
+
+    #[inline]
+    fn push(&mut self, value: Option<T>) {
+        self.push(value);
+    }
+
+    #[inline]
+    fn insert(&mut self, index: usize, value: Option<T>) {
+        self.insert(index, value);
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<Option<T>> {
+        self.pop()
+    }
+
+    #[inline]
+    fn remove(&mut self, index: usize) -> Option<T> {
+        self.remove(index)
+    }
+
+    #[inline]
+    fn swap_remove(&mut self, index: usize) -> Option<T> {
+        self.swap_remove(index)
+    }
+}
+
+/// A view of a range of rows of a [`NullableVec`], like a `&[Option<T>]`
+pub struct NullableSlice<'a, T> {
+    values: &'a [T],
+    validity: BitSlice<'a>,
+}
+
+impl<'a, T> NullableSlice<'a, T> {
+    /// The number of rows
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether there are no rows
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The row at `index`, panicking if it is out of bounds
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&'a T> {
+        let value = &self.values[index];
+        self.validity.get(index).then_some(value)
+    }
+
+    /// A view of a range of rows, panicking if it is out of bounds
+    #[inline]
+    pub fn slice(self, range: impl RangeBounds<usize>) -> Self {
+        let range = crate::slice::range(range, self.len());
+
+        Self {
+            values: &self.values[range.clone()],
+            validity: self.validity.slice(range),
+        }
+    }
+
+    /// Split the view into the rows before `mid` and the rows from `mid` onwards, panicking if
+    /// `mid > len`
+    #[inline]
+    pub fn split_at(self, mid: usize) -> (Self, Self) {
+        (self.slice(..mid), self.slice(mid..))
+    }
+
+    /// Every row, which for a nullable column is the view itself
+    #[inline]
+    pub fn as_slices(&self) -> Self {
+        *self
+    }
+
+    /// An iterator over each row
+    #[inline]
+    pub fn iter(&self) -> NullableIter<'a, T> {
+        NullableIter {
+            values: self.values.iter(),
+            validity: self.validity.iter(),
+        }
+    }
+
+    /// The value of every row, which is `T::default()` for the rows that are `None`
+    #[inline]
+    pub fn values(&self) -> &'a [T] {
+        self.values
+    }
+
+    /// Whether each row is `Some`
+    #[inline]
+    pub fn validity(&self) -> BitSlice<'a> {
+        self.validity
+    }
+
+    /// The number of rows that are `None`
+    #[inline]
+    pub fn null_count(&self) -> usize {
+        self.validity.count_zeros()
+    }
+}
+
+impl<T> Clone for NullableSlice<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for NullableSlice<'_, T> {}
+
+impl<T: fmt::Debug> fmt::Debug for NullableSlice<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for NullableSlice<'_, T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for NullableSlice<'_, T> {}
+
+impl<'a, T> IntoIterator for NullableSlice<'a, T> {
+    type Item = Option<&'a T>;
+    type IntoIter = NullableIter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> NullableIter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> SovSlice for NullableSlice<'_, T> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn split_at(self, mid: usize) -> (Self, Self) {
+        self.split_at(mid)
+    }
+}
+
+/// A mutable view of a range of rows of a [`NullableVec`], like a `&mut [Option<T>]`
+pub struct NullableSliceMut<'a, T> {
+    values: &'a mut [T],
+    validity: BitSliceMut<'a>,
+}
+
+impl<'a, T> NullableSliceMut<'a, T> {
+    /// The number of rows
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether there are no rows
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The row at `index`, panicking if it is out of bounds
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.as_slice().get(index)
+    }
+
+    /// A proxy for the row at `index`, which can change it, panicking if it is out of bounds
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> NullableMut<'_, T> {
+        NullableMut {
+            value: &mut self.values[index],
+            valid: self.validity.get_mut(index),
+        }
+    }
+
+    /// Borrow the view immutably, for as long as it is borrowed
+    #[inline]
+    pub fn as_slice(&self) -> NullableSlice<'_, T> {
+        NullableSlice {
+            values: self.values,
+            validity: self.validity.as_slice(),
+        }
+    }
+
+    /// Borrow the view mutably for a shorter lifetime, so that it can be used again afterwards
+    #[inline]
+    pub fn reborrow(&mut self) -> NullableSliceMut<'_, T> {
+        NullableSliceMut {
+            values: self.values,
+            validity: self.validity.reborrow(),
+        }
+    }
+
+    /// A view of a range of rows, panicking if it is out of bounds
+    #[inline]
+    pub fn slice_mut(self, range: impl RangeBounds<usize>) -> Self {
+        let range = crate::slice::range(range, self.len());
+
+        Self {
+            values: &mut self.values[range.clone()],
+            validity: self.validity.slice_mut(range),
+        }
+    }
+
+    /// Split the view into the rows before `mid` and the rows from `mid` onwards, panicking if
+    /// `mid > len`
+    #[inline]
+    pub fn split_at(self, mid: usize) -> (Self, Self) {
+        let (values_before, values_after) = self.values.split_at_mut(mid);
+        let (validity_before, validity_after) = self.validity.split_at(mid);
+
+        (
+            Self {
+                values: values_before,
+                validity: validity_before,
+            },
+            Self {
+                values: values_after,
+                validity: validity_after,
+            },
+        )
+    }
+
+    /// Every row, which for a nullable column is the view itself
+    #[inline]
+    pub fn as_slices(&self) -> NullableSlice<'_, T> {
+        self.as_slice()
+    }
+
+    /// Mutably borrow every row, which for a nullable column is the view itself
+    #[inline]
+    pub fn as_mut_slices(&mut self) -> NullableSliceMut<'_, T> {
+        self.reborrow()
+    }
+
+    /// An iterator over each row
+    #[inline]
+    pub fn iter(&self) -> NullableIter<'_, T> {
+        self.as_slice().iter()
+    }
+
+    /// An iterator over a proxy for each row
+    #[inline]
+    pub fn iter_mut(&mut self) -> NullableIterMut<'_, T> {
+        self.reborrow().into_iter()
+    }
+
+    /// The value of every row, which is `T::default()` for the rows that are `None`
+    #[inline]
+    pub fn values(&self) -> &[T] {
+        self.values
+    }
+
+    /// Mutably borrow the value of every row. Changing the value of a row that is `None` doesn't
+    /// make it `Some`
+    #[inline]
+    pub fn values_mut(&mut self) -> &mut [T] {
+        self.values
+    }
+
+    /// Whether each row is `Some`
+    #[inline]
+    pub fn validity(&self) -> BitSlice<'_> {
+        self.validity.as_slice()
+    }
+
+    /// Mutably borrow whether each row is `Some`. A row that is made `Some` this way has the value
+    /// it had before, which is `T::default()` if it was `None`
+    #[inline]
+    pub fn validity_mut(&mut self) -> BitSliceMut<'_> {
+        self.validity.reborrow()
+    }
+
+    /// The number of rows that are `None`
+    #[inline]
+    pub fn null_count(&self) -> usize {
+        self.validity.count_zeros()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for NullableSliceMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.as_slice(), f)
+    }
+}
+
+impl<'a, T> IntoIterator for NullableSliceMut<'a, T> {
+    type Item = NullableMut<'a, T>;
+    type IntoIter = NullableIterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> NullableIterMut<'a, T> {
+        NullableIterMut {
+            values: self.values.iter_mut(),
+            validity: self.validity.into_iter(),
+        }
+    }
+}
+
+impl<T> SovSlice for NullableSliceMut<'_, T> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn split_at(self, mid: usize) -> (Self, Self) {
+        self.split_at(mid)
+    }
+}
+
+/// A proxy for a single row of a [`NullableVec`], which can read and change it like a
+/// `&mut Option<T>`.
+///
+/// It compares, orders and hashes as the `Option<&T>` it points to
+pub struct NullableMut<'a, T> {
+    value: &'a mut T,
+    valid: BitMut<'a>,
+}
+
+impl<T> NullableMut<'_, T> {
+    /// Whether the row is `Some`
+    #[inline]
+    pub fn is_some(&self) -> bool {
+        self.valid.get()
+    }
+
+    /// Whether the row is `None`
+    #[inline]
+    pub fn is_none(&self) -> bool {
+        !self.is_some()
+    }
+
+    /// Borrow the value of the row, which is what a `FooRef` holds for a nullable field
+    #[inline]
+    pub fn as_ref(&self) -> Option<&T> {
+        self.is_some().then_some(&*self.value)
+    }
+
+    /// Mutably borrow the value of the row, if it is `Some`
+    #[inline]
+    pub fn as_mut(&mut self) -> Option<&mut T> {
+        self.is_some().then_some(&mut *self.value)
+    }
+
+    /// Borrow the row mutably for a shorter lifetime, so that this proxy can be used again
+    /// afterwards
+    #[inline]
+    pub fn reborrow(&mut self) -> NullableMut<'_, T> {
+        NullableMut {
+            value: self.value,
+            valid: self.valid.reborrow(),
+        }
+    }
+
+    /// Clone the row into an `Option<T>`
+    #[inline]
+    pub fn to_owned(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.as_ref().cloned()
+    }
+}
+
+impl<T: Default> NullableMut<'_, T> {
+    /// Overwrite the row with `value`
+    #[inline]
+    pub fn set(&mut self, value: Option<T>) {
+        self.replace(value);
+    }
+
+    /// Overwrite the row with `value`, and return the old value
+    #[inline]
+    pub fn replace(&mut self, value: Option<T>) -> Option<T> {
+        let valid = value.is_some();
+        let old = mem::replace(self.value, value.unwrap_or_default());
+
+        self.valid.replace(valid).then_some(old)
+    }
+
+    /// Take the value out of the row, leaving `None` in its place
+    #[inline]
+    pub fn take(&mut self) -> Option<T> {
+        self.replace(None)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for NullableMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.as_ref(), f)
+    }
+}
+
+impl<'b, T: PartialEq> PartialEq<NullableMut<'b, T>> for NullableMut<'_, T> {
+    #[inline]
+    fn eq(&self, other: &NullableMut<'b, T>) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl<T: Eq> Eq for NullableMut<'_, T> {}
+
+impl<'b, T: PartialOrd> PartialOrd<NullableMut<'b, T>> for NullableMut<'_, T> {
+    #[inline]
+    fn partial_cmp(&self, other: &NullableMut<'b, T>) -> Option<Ordering> {
+        self.as_ref().partial_cmp(&other.as_ref())
+    }
+}
+
+impl<T: Ord> Ord for NullableMut<'_, T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_ref().cmp(&other.as_ref())
+    }
+}
+
+impl<T: Hash> Hash for NullableMut<'_, T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_ref().hash(state);
+    }
+}
+
+/// An iterator over the rows of a [`NullableVec`] or [`NullableSlice`]
+pub struct NullableIter<'a, T> {
+    values: slice::Iter<'a, T>,
+    validity: BitIter<'a>,
+}
+
+impl<T> Clone for NullableIter<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            values: self.values.clone(),
+            validity: self.validity.clone(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for NullableIter<'a, T> {
+    type Item = Option<&'a T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Option<&'a T>> {
+        let value = self.values.next()?;
+        Some(self.validity.next()?.then_some(value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for NullableIter<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Option<&'a T>> {
+        let value = self.values.next_back()?;
+        Some(self.validity.next_back()?.then_some(value))
+    }
+}
+
+impl<T> ExactSizeIterator for NullableIter<'_, T> {}
+
+impl<T> FusedIterator for NullableIter<'_, T> {}
+
+/// An iterator over a proxy for each row of a [`NullableVec`] or [`NullableSliceMut`]
+pub struct NullableIterMut<'a, T> {
+    values: slice::IterMut<'a, T>,
+    validity: BitIterMut<'a>,
+}
+
+impl<'a, T> Iterator for NullableIterMut<'a, T> {
+    type Item = NullableMut<'a, T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<NullableMut<'a, T>> {
+        let value = self.values.next()?;
+        let valid = self.validity.next()?;
+
+        Some(NullableMut { value, valid })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for NullableIterMut<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<NullableMut<'a, T>> {
+        let value = self.values.next_back()?;
+        let valid = self.validity.next_back()?;
+
+        Some(NullableMut { value, valid })
+    }
+}
+
+impl<T> ExactSizeIterator for NullableIterMut<'_, T> {}
+
+impl<T> FusedIterator for NullableIterMut<'_, T> {}
+
+/// An iterator over the rows of a [`NullableVec`] by value
+pub struct NullableIntoIter<T> {
+    values: vec::IntoIter<T>,
+    validity: BitIntoIter,
+}
+
+impl<T: Clone> Clone for NullableIntoIter<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            values: self.values.clone(),
+            validity: self.validity.clone(),
+        }
+    }
+}
+
+impl<T> Iterator for NullableIntoIter<T> {
+    type Item = Option<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Option<T>> {
+        let value = self.values.next()?;
+        Some(self.validity.next()?.then_some(value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for NullableIntoIter<T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Option<T>> {
+        let value = self.values.next_back()?;
+        Some(self.validity.next_back()?.then_some(value))
+    }
+}
+
+impl<T> ExactSizeIterator for NullableIntoIter<T> {}
+
+impl<T> FusedIterator for NullableIntoIter<T> {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        string::{String, ToString},
+        vec::Vec,
+    };
+
+    use super::*;
+    use crate::bits::BITS;
+
+    fn some(value: &str) -> Option<String> {
+        Some(String::from(value))
+    }
+
+    /// Check that each row's value is paired with its validity bit, and that every `None` row
+    /// holds the default value rather than a stale one
+    fn assert_in_sync(vec: &NullableVec<String>, model: &[Option<String>]) {
+        assert_eq!(vec.values().len(), model.len());
+        assert_eq!(vec.validity().len(), model.len());
+        assert!(vec.iter().eq(model.iter().map(Option::as_ref)));
+
+        for (index, row) in model.iter().enumerate() {
+            assert_eq!(vec.validity().get(index), row.is_some());
+            assert_eq!(&vec.values()[index], row.as_deref().unwrap_or_default());
+        }
+    }
+
+    #[test]
+    fn row_operations_keep_the_columns_in_sync() {
+        let mut model = Vec::new();
+        let mut vec = NullableVec::new();
+
+        for row in [some("a"), None, some("b"), None, some("c")] {
+            model.push(row.clone());
+            vec.push(row);
+            assert_in_sync(&vec, &model);
+        }
+
+        model.insert(0, None);
+        vec.insert(0, None);
+        assert_in_sync(&vec, &model);
+
+        model.insert(3, some("d"));
+        vec.insert(3, some("d"));
+        assert_in_sync(&vec, &model);
+
+        assert_eq!(vec.remove(1), model.remove(1));
+        assert_in_sync(&vec, &model);
+
+        assert_eq!(vec.swap_remove(0), model.swap_remove(0));
+        assert_in_sync(&vec, &model);
+
+        assert_eq!(vec.pop(), model.pop());
+        assert_in_sync(&vec, &model);
+
+        model.truncate(2);
+        vec.truncate(2);
+        assert_in_sync(&vec, &model);
+
+        model.resize(4, None);
+        vec.resize(4, None);
+        assert_in_sync(&vec, &model);
+
+        model.resize(6, some("e"));
+        vec.resize(6, some("e"));
+        assert_in_sync(&vec, &model);
+    }
+
+    #[test]
+    fn set_take_and_replace_reset_none_rows_to_the_default() {
+        let mut model = [some("a"), None, some("b"), some("c")];
+        let mut vec: NullableVec<_> = model.iter().cloned().collect();
+
+        vec.get_mut(0).set(None);
+        model[0] = None;
+        assert_in_sync(&vec, &model);
+
+        vec.get_mut(1).set(some("set"));
+        model[1] = some("set");
+        assert_in_sync(&vec, &model);
+
+        assert_eq!(vec.get_mut(2).take(), model[2].take());
+        assert_in_sync(&vec, &model);
+        assert_eq!(vec.get_mut(2).take(), None);
+
+        assert_eq!(vec.get_mut(3).replace(None), model[3].take());
+        assert_in_sync(&vec, &model);
+        assert_eq!(vec.get_mut(0).replace(some("replaced")), None);
+        model[0] = some("replaced");
+        assert_in_sync(&vec, &model);
+    }
+
+    #[test]
+    fn values_stay_paired_when_validity_carries_across_words() {
+        // every row's value is distinct, so a value shifted without its bit would be caught
+        let mut model: Vec<_> = (0..=BITS)
+            .map(|index| (index % 2 == 0).then(|| index.to_string()))
+            .collect();
+        let mut vec: NullableVec<_> = model.iter().cloned().collect();
+
+        model.insert(0, some("first"));
+        vec.insert(0, some("first"));
+        assert_in_sync(&vec, &model);
+
+        assert_eq!(vec.remove(0), model.remove(0));
+        assert_eq!(vec.remove(0), model.remove(0));
+        assert_in_sync(&vec, &model);
+    }
+}
@@ -0,0 +1,80 @@
+use alloc::{vec, vec::Vec};
+
+/// A reordering of the rows of a vec, which can be applied to each column in turn.
+///
+/// The order is turned into a list of swaps up front, so applying it to a column doesn't allocate.
+/// It may leave the rows before an offset in place, so that only the end of each column is visited
+#[doc(hidden)]
+pub struct Permutation {
+    offset: usize,
+    order: Vec<usize>,
+    swaps: Vec<(usize, usize)>,
+}
+
+impl Permutation {
+    /// A permutation that moves the row at `order[i]` to index `i`. `order` must contain every
+    /// index from `0` to `order.len()` exactly once
+    pub fn new(order: Vec<usize>) -> Self {
+        Self::with_offset(0, order)
+    }
+
+    /// A permutation that moves the row at `order[i]` to index `offset + i`, and leaves the rows
+    /// before `offset` in place. `order` must contain every index from `offset` to
+    /// `offset + order.len()` exactly once
+    pub fn with_offset(offset: usize, order: Vec<usize>) -> Self {
+        let mut visited = vec![false; order.len()];
+        let mut swaps = Vec::new();
+
+        // follow each cycle, swapping the row that belongs at each position into place
+        for start in 0..order.len() {
+            let mut position = start;
+
+            while !visited[position] {
+                visited[position] = true;
+                let next = order[position] - offset;
+
+                if next != start {
+                    swaps.push((offset + position, offset + next));
+                }
+
+                position = next;
+            }
+        }
+
+        Self {
+            offset,
+            order,
+            swaps,
+        }
+    }
+
+    /// The length of the columns that the permutation applies to
+    pub fn column_len(&self) -> usize {
+        self.offset + self.order.len()
+    }
+
+    /// The index of the first row that may be moved
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The index that each row from the offset onwards is moved from
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    /// The swaps that apply the permutation, in order, for columns that aren't stored as a slice
+    pub fn swaps(&self) -> &[(usize, usize)] {
+        &self.swaps
+    }
+
+    /// Reorder a column, which must have the same length as the order
+    #[inline]
+    pub fn apply<T>(&self, column: &mut [T]) {
+        debug_assert_eq!(column.len(), self.column_len());
+
+        for &(a, b) in &self.swaps {
+            column.swap(a, b);
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use core::{
+    iter::FusedIterator,
+    ops::{Bound, Range, RangeBounds},
+};
+
+/// A view of a contiguous range of rows, like a `&[Foo]` or `&mut [Foo]`.
+///
+/// This is implemented by the `FooSlice` and `FooSliceMut` types generated by
+/// `#[derive(StructOfVecs)]`, which have inherent methods for everything else. This trait is what
+/// the chunk iterators ([`Chunks`], [`ChunksExact`] and [`Windows`]) are generic over.
+pub trait SovSlice: Sized {
+    /// The number of rows
+    fn len(&self) -> usize;
+
+    /// Whether there are no rows
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Split the view into the rows before `mid` and the rows from `mid` onwards, panicking if
+    /// `mid > len`.
+    ///
+    /// This consumes the view, so that a mutable view can be split into two views with the same
+    /// lifetime
+    fn split_at(self, mid: usize) -> (Self, Self);
+}
+
+/// An iterator over views of `size` rows at a time, returned by `chunks` and `chunks_mut`. The last
+/// chunk is shorter if the number of rows isn't a multiple of `size`
+pub struct Chunks<S> {
+    /// The rows that haven't been yielded yet. This is only `None` while it's being split
+    rest: Option<S>,
+    size: usize,
+}
+
+impl<S: SovSlice> Chunks<S> {
+    #[doc(hidden)]
+    pub fn new(slice: S, size: usize) -> Self {
+        assert!(size != 0, "chunk size must be non-zero");
+
+        Self {
+            rest: Some(slice),
+            size,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.rest.as_ref().map_or(0, S::len).div_ceil(self.size)
+    }
+}
+
+impl<S: SovSlice> Iterator for Chunks<S> {
+    type Item = S;
+
+    #[inline]
+    fn next(&mut self) -> Option<S> {
+        let rest = self.rest.take()?;
+
+        if rest.is_empty() {
+            self.rest = Some(rest);
+            return None;
+        }
+
+        let mid = self.size.min(rest.len());
+        let (chunk, rest) = rest.split_at(mid);
+        self.rest = Some(rest);
+
+        Some(chunk)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<S: SovSlice> DoubleEndedIterator for Chunks<S> {
+    #[inline]
+    fn next_back(&mut self) -> Option<S> {
+        let rest = self.rest.take()?;
+
+        if rest.is_empty() {
+            self.rest = Some(rest);
+            return None;
+        }
+
+        let len = rest.len();
+        let last = match len % self.size {
+            0 => self.size,
+            remainder => remainder,
+        };
+
+        let (rest, chunk) = rest.split_at(len - last);
+        self.rest = Some(rest);
+
+        Some(chunk)
+    }
+}
+
+impl<S: SovSlice> ExactSizeIterator for Chunks<S> {}
+
+impl<S: SovSlice> FusedIterator for Chunks<S> {}
+
+/// An iterator over views of exactly `size` rows at a time, returned by `chunks_exact` and
+/// `chunks_exact_mut`. If the number of rows isn't a multiple of `size`, the rows left over at the
+/// end are available from [`remainder`](Self::remainder) instead
+pub struct ChunksExact<S> {
+    chunks: Chunks<S>,
+    remainder: S,
+}
+
+impl<S: SovSlice> ChunksExact<S> {
+    #[doc(hidden)]
+    pub fn new(slice: S, size: usize) -> Self {
+        assert!(size != 0, "chunk size must be non-zero");
+
+        let len = slice.len();
+        let (rest, remainder) = slice.split_at(len - len % size);
+
+        Self {
+            chunks: Chunks::new(rest, size),
+            remainder,
+        }
+    }
+
+    /// The rows at the end that don't fill a whole chunk
+    pub fn remainder(&self) -> &S {
+        &self.remainder
+    }
+
+    /// The rows at the end that don't fill a whole chunk, consuming the iterator
+    pub fn into_remainder(self) -> S {
+        self.remainder
+    }
+}
+
+impl<S: SovSlice> Iterator for ChunksExact<S> {
+    type Item = S;
+
+    #[inline]
+    fn next(&mut self) -> Option<S> {
+        self.chunks.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl<S: SovSlice> DoubleEndedIterator for ChunksExact<S> {
+    #[inline]
+    fn next_back(&mut self) -> Option<S> {
+        self.chunks.next_back()
+    }
+}
+
+impl<S: SovSlice> ExactSizeIterator for ChunksExact<S> {}
+
+impl<S: SovSlice> FusedIterator for ChunksExact<S> {}
+
+/// An iterator over every overlapping view of `size` consecutive rows, returned by `windows`
+pub struct Windows<S> {
+    rest: S,
+    size: usize,
+}
+
+impl<S: SovSlice + Copy> Windows<S> {
+    #[doc(hidden)]
+    pub fn new(slice: S, size: usize) -> Self {
+        assert!(size != 0, "window size must be non-zero");
+
+        Self { rest: slice, size }
+    }
+}
+
+impl<S: SovSlice + Copy> Iterator for Windows<S> {
+    type Item = S;
+
+    #[inline]
+    fn next(&mut self) -> Option<S> {
+        if self.rest.len() < self.size {
+            return None;
+        }
+
+        let (window, _) = self.rest.split_at(self.size);
+        self.rest = self.rest.split_at(1).1;
+
+        Some(window)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.rest.len() + 1).saturating_sub(self.size);
+        (len, Some(len))
+    }
+}
+
+impl<S: SovSlice + Copy> DoubleEndedIterator for Windows<S> {
+    #[inline]
+    fn next_back(&mut self) -> Option<S> {
+        let len = self.rest.len();
+
+        if len < self.size {
+            return None;
+        }
+
+        let (_, window) = self.rest.split_at(len - self.size);
+        self.rest = self.rest.split_at(len - 1).0;
+
+        Some(window)
+    }
+}
+
+impl<S: SovSlice + Copy> ExactSizeIterator for Windows<S> {}
+
+impl<S: SovSlice + Copy> FusedIterator for Windows<S> {}
+
+/// Convert the range passed to `slice` into a `Range`, panicking with the same messages as slicing
+/// a `&[T]` if it's out of bounds
+#[doc(hidden)]
+pub fn range(range: impl RangeBounds<usize>, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start
+            .checked_add(1)
+            .expect("attempted to index slice from after maximum usize"),
+        Bound::Unbounded => 0,
+    };
+
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end
+            .checked_add(1)
+            .expect("attempted to index slice up to maximum usize"),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+
+    assert!(
+        start <= end,
+        "slice index starts at {start} but ends at {end}"
+    );
+    assert!(
+        end <= len,
+        "range end index {end} out of range for slice of length {len}"
+    );
+
+    start..end
+}
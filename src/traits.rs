@@ -0,0 +1,403 @@
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+
+use crate::SovSlice;
+
+/// A type which has a struct-of-vecs representation.
+///
+/// This is implemented by `#[derive(StructOfVecs)]`, and links the original type to the types that
+/// are generated for it, so that code can be written generically over any of them:
+/// ```rust
+/// use sov::{SovVec, StructOfVecs};
+///
+/// fn fill<T: StructOfVecs>(make: impl Fn(usize) -> T, count: usize) -> T::Vec {
+///     let mut vec = T::Vec::with_capacity(count);
+///
+///     for i in 0..count {
+///         vec.push(make(i));
+///     }
+///
+///     vec
+/// }
+///
+/// #[derive(StructOfVecs)]
+/// struct Foo {
+///     x: usize,
+/// }
+///
+/// fn main() {
+///     let foos: VecFoo = fill(|x| Foo { x }, 10);
+///     assert_eq!(foos.len(), 10);
+///     assert_eq!(*foos.get(3).x, 3);
+/// }
+/// ```
+pub trait StructOfVecs: Sized {
+    /// The `Vec`-like type that stores each field in its own column, e.g. `VecFoo`
+    type Vec: SovVec<Item = Self>;
+
+    /// A shared reference to a single row, e.g. `FooRef<'a>`. It's `Copy` like a `&Foo`, which
+    /// lets a type that flattens this one be `Copy` too
+    type Ref<'a>: Copy
+    where
+        Self: 'a;
+
+    /// A mutable reference to a single row, e.g. `FooRefMut<'a>`
+    type RefMut<'a>
+    where
+        Self: 'a;
+
+    /// A shared slice of every column, e.g. `FooSlices<'a>`
+    type Slices<'a>
+    where
+        Self: 'a;
+
+    /// A mutable slice of every column, e.g. `FooSlicesMut<'a>`
+    type SlicesMut<'a>
+    where
+        Self: 'a;
+
+    /// A shared view of a range of rows, e.g. `FooSlice<'a>`
+    type Slice<'a>: SovSlice + Copy + IntoIterator<Item = Self::Ref<'a>>
+    where
+        Self: 'a;
+
+    /// A mutable view of a range of rows, e.g. `FooSliceMut<'a>`
+    type SliceMut<'a>: SovSlice + IntoIterator<Item = Self::RefMut<'a>>
+    where
+        Self: 'a;
+}
+
+/// The core API of the `Vec`-like types generated by `#[derive(StructOfVecs)]`.
+///
+/// The generated types also have inherent methods with the same names, so this trait only needs to
+/// be imported when writing generic code (see [`StructOfVecs`]).
+///
+/// Iterating over the vec by value yields the original type, so the type it is a struct-of-vecs
+/// representation of is its [`IntoIterator::Item`].
+pub trait SovVec:
+    Sized
+    + IntoIterator<
+        Item: StructOfVecs<Vec = Self>,
+        IntoIter: DoubleEndedIterator + ExactSizeIterator + FusedIterator,
+    >
+{
+    /// An iterator over shared references to each row, e.g. `FooIter<'a>`
+    type Iter<'a>: DoubleEndedIterator<Item = <Self::Item as StructOfVecs>::Ref<'a>>
+        + ExactSizeIterator
+        + FusedIterator
+    where
+        Self: 'a;
+
+    /// An iterator over mutable references to each row, e.g. `FooIterMut<'a>`
+    type IterMut<'a>: DoubleEndedIterator<Item = <Self::Item as StructOfVecs>::RefMut<'a>>
+        + ExactSizeIterator
+        + FusedIterator
+    where
+        Self: 'a;
+
+    /// Create an empty vec
+    fn new() -> Self;
+
+    /// Create an empty vec with space for at least `capacity` rows
+    fn with_capacity(capacity: usize) -> Self;
+
+    /// The number of rows
+    fn len(&self) -> usize;
+
+    /// The number of rows the vec can hold without reallocating
+    fn capacity(&self) -> usize;
+
+    /// Reserve space for at least `additional` more rows
+    fn reserve(&mut self, additional: usize);
+
+    /// Reserve space for exactly `additional` more rows
+    fn reserve_exact(&mut self, additional: usize);
+
+    /// Shrink the capacity as much as possible
+    fn shrink_to_fit(&mut self);
+
+    /// Shrink the capacity to at least `min_capacity` and the number of rows
+    fn shrink_to(&mut self, min_capacity: usize);
+
+    /// Remove every row after the first `len`
+    fn truncate(&mut self, len: usize);
+
+    /// Remove every row, keeping the capacity
+    fn clear(&mut self);
+
+    /// Resize to `new_len` rows, adding rows returned by `f` if it grows
+    fn resize_with<F>(&mut self, new_len: usize, f: F)
+    where
+        F: FnMut() -> Self::Item;
+
+    /// Whether there are no rows
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// A shared reference to the row at `index`, panicking if it is out of bounds
+    fn get(&self, index: usize) -> <Self::Item as StructOfVecs>::Ref<'_>;
+
+    /// A mutable reference to the row at `index`, panicking if it is out of bounds
+    fn get_mut(&mut self, index: usize) -> <Self::Item as StructOfVecs>::RefMut<'_>;
+
+    /// Borrow every column at once
+    fn as_slices(&self) -> <Self::Item as StructOfVecs>::Slices<'_>;
+
+    /// Mutably borrow every column at once
+    fn as_mut_slices(&mut self) -> <Self::Item as StructOfVecs>::SlicesMut<'_>;
+
+    /// Borrow every row as a view
+    fn as_slice(&self) -> <Self::Item as StructOfVecs>::Slice<'_>;
+
+    /// Mutably borrow every row as a view
+    fn as_mut_slice(&mut self) -> <Self::Item as StructOfVecs>::SliceMut<'_>;
+
+    /// Iterate over shared references to each row
+    fn iter(&self) -> Self::Iter<'_>;
+
+    /// Iterate over mutable references to each row
+    fn iter_mut(&mut self) -> Self::IterMut<'_>;
+
+    /// Add a row to the end
+    fn push(&mut self, value: Self::Item);
+
+    /// Insert a row at `index`, shifting all rows after it
+    fn insert(&mut self, index: usize, value: Self::Item);
+
+    /// Remove the last row, if there is one
+    fn pop(&mut self) -> Option<Self::Item>;
+
+    /// Remove the row at `index`, shifting all rows after it
+    fn remove(&mut self, index: usize) -> Self::Item;
+
+    /// Remove the row at `index`, replacing it with the last row
+    fn swap_remove(&mut self, index: usize) -> Self::Item;
+}
+
+/// The storage of a single column of a `Vec`-like type.
+///
+/// Every column is a `Vec` by default, and `#[sov(column = "...")]` on a field stores it in any
+/// other type that implements this trait instead. The generated methods that add or remove rows
+/// call through this trait, rather than any inherent methods with the same names.
+///
+/// The rows of a column must be stored contiguously, since the slices and iterators of the
+/// generated types borrow them through [`Column::as_slice`] and [`Column::as_mut_slice`]. That
+/// rules out storage that doesn't hold a `T` for every row, like a bit vector or a dictionary
+/// encoding: those are `#[sov(bitpacked)]` and `#[sov(dictionary)]` instead, which have their own
+/// reference and slice types.
+pub trait Column<T>:
+    IntoIterator<Item = T, IntoIter: DoubleEndedIterator + ExactSizeIterator + FusedIterator>
+{
+    /// Create an empty column
+    fn new() -> Self;
+
+    /// Create an empty column with space for at least `capacity` rows
+    fn with_capacity(capacity: usize) -> Self;
+
+    /// The number of rows
+    fn len(&self) -> usize;
+
+    /// Whether there are no rows
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of rows the column can hold without reallocating
+    fn capacity(&self) -> usize;
+
+    /// Reserve space for at least `additional` more rows
+    fn reserve(&mut self, additional: usize);
+
+    /// Reserve space for exactly `additional` more rows, which is the same as
+    /// [`Column::reserve`] unless the column can avoid over-allocating
+    fn reserve_exact(&mut self, additional: usize) {
+        self.reserve(additional);
+    }
+
+    /// Shrink the capacity as much as possible
+    fn shrink_to_fit(&mut self);
+
+    /// Shrink the capacity to at least `min_capacity` and the number of rows, which does nothing
+    /// unless the column can shrink partially
+    fn shrink_to(&mut self, min_capacity: usize) {
+        let _ = min_capacity;
+    }
+
+    /// Add a row to the end
+    fn push(&mut self, value: T);
+
+    /// Remove the last row, if there is one
+    fn pop(&mut self) -> Option<T>;
+
+    /// Insert a row at `index`, shifting all rows after it
+    fn insert(&mut self, index: usize, value: T);
+
+    /// Remove the row at `index`, shifting all rows after it
+    fn remove(&mut self, index: usize) -> T;
+
+    /// Remove the row at `index`, replacing it with the last row
+    fn swap_remove(&mut self, index: usize) -> T;
+
+    /// Remove every row after the first `len`
+    fn truncate(&mut self, len: usize);
+
+    /// Remove every row, keeping the capacity
+    fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Resize to `new_len` rows, adding clones of `value` if it grows
+    fn resize(&mut self, new_len: usize, value: T)
+    where
+        T: Clone,
+    {
+        match new_len.checked_sub(self.len()) {
+            Some(additional) => {
+                self.reserve(additional);
+                (0..additional).for_each(|_| self.push(value.clone()));
+            }
+            None => self.truncate(new_len),
+        }
+    }
+
+    /// Add clones of every value in `values` to the end
+    fn extend_from_slice(&mut self, values: &[T])
+    where
+        T: Clone,
+    {
+        self.reserve(values.len());
+        values.iter().for_each(|value| self.push(value.clone()));
+    }
+
+    /// A shared reference to the row at `index`, if it is in bounds
+    fn get(&self, index: usize) -> Option<&T> {
+        self.as_slice().get(index)
+    }
+
+    /// A mutable reference to the row at `index`, if it is in bounds
+    fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.as_mut_slice().get_mut(index)
+    }
+
+    /// Borrow every row
+    fn as_slice(&self) -> &[T];
+
+    /// Mutably borrow every row
+    fn as_mut_slice(&mut self) -> &mut [T];
+}
+
+/// Forward the methods of [`Column`] to the inherent methods of a `Vec`-like type
+macro_rules! forward_column {
+    ($item:ty) => {
+        #[inline]
+        fn new() -> Self {
+            Self::new()
+        }
+
+        #[inline]
+        fn with_capacity(capacity: usize) -> Self {
+            Self::with_capacity(capacity)
+        }
+
+        #[inline]
+        fn len(&self) -> usize {
+            self.len()
+        }
+
+        #[inline]
+        fn capacity(&self) -> usize {
+            self.capacity()
+        }
+
+        #[inline]
+        fn reserve(&mut self, additional: usize) {
+            self.reserve(additional);
+        }
+
+        #[inline]
+        fn reserve_exact(&mut self, additional: usize) {
+            self.reserve_exact(additional);
+        }
+
+        #[inline]
+        fn shrink_to_fit(&mut self) {
+            self.shrink_to_fit();
+        }
+
+        #[inline]
+        fn push(&mut self, value: $item) {
+            self.push(value);
+        }
+
+        #[inline]
+        fn pop(&mut self) -> Option<$item> {
+            self.pop()
+        }
+
+        #[inline]
+        fn insert(&mut self, index: usize, value: $item) {
+            self.insert(index, value);
+        }
+
+        #[inline]
+        fn remove(&mut self, index: usize) -> $item {
+            self.remove(index)
+        }
+
+        #[inline]
+        fn swap_remove(&mut self, index: usize) -> $item {
+            self.swap_remove(index)
+        }
+
+        #[inline]
+        fn truncate(&mut self, len: usize) {
+            self.truncate(len);
+        }
+
+        #[inline]
+        fn clear(&mut self) {
+            self.clear();
+        }
+
+        #[inline]
+        fn resize(&mut self, new_len: usize, value: $item)
+        where
+            $item: Clone,
+        {
+            self.resize(new_len, value);
+        }
+
+        #[inline]
+        fn as_slice(&self) -> &[$item] {
+            self.as_slice()
+        }
+
+        #[inline]
+        fn as_mut_slice(&mut self) -> &mut [$item] {
+            self.as_mut_slice()
+        }
+    };
+}
+
+impl<T> Column<T> for Vec<T> {
+    forward_column!(T);
+
+    #[inline]
+    fn shrink_to(&mut self, min_capacity: usize) {
+        self.shrink_to(min_capacity);
+    }
+
+    #[inline]
+    fn extend_from_slice(&mut self, values: &[T])
+    where
+        T: Clone,
+    {
+        self.extend_from_slice(values);
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array> Column<A::Item> for smallvec::SmallVec<A> {
+    forward_column!(A::Item);
+}